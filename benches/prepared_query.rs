@@ -0,0 +1,63 @@
+//! Executing a `PreparedQuery` against building the same query each time.
+//!
+//! The transport answers in-process without looking at the request, so the difference
+//! between the two is path construction and serialization.
+//!
+//!     cargo bench --bench prepared_query
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rama_client::{Client, HttpTransport, TransportFuture, TransportResponse};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::hint::black_box;
+use std::sync::Arc;
+use url::Url;
+
+// Answers every request with `[1]`.
+struct Canned;
+
+impl HttpTransport for Canned {
+    fn post(&self, _url: Url, _headers: HeaderMap, _body: Bytes) -> TransportFuture<'_> {
+        Box::pin(async { Ok(TransportResponse::from_bytes(StatusCode::OK, HeaderMap::new(), "[1]")) })
+    }
+
+    fn get(&self, _url: Url, _headers: HeaderMap) -> TransportFuture<'_> {
+        Box::pin(async { Ok(TransportResponse::from_bytes(StatusCode::OK, HeaderMap::new(), "[1]")) })
+    }
+}
+
+// The static navigators around the key: a query deep into nested maps.
+const FIELDS: [&str; 8] = ["profile", "settings", "notifications", "email", "digest", "schedule", "weekly", "day"];
+
+fn select(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let client = Client::with_transport("http://conductor:1984", Arc::new(Canned)).unwrap();
+    let prepared = FIELDS
+        .iter()
+        .fold(client.pstate_query("profiles", "$$profiles").key_param("user_id"), |query, field| query.key(*field))
+        .prepare();
+
+    let mut group = c.benchmark_group("select");
+    group.bench_function("builder", |b| {
+        let mut user_id = 0i64;
+        b.iter(|| {
+            user_id += 1;
+            let query = FIELDS.iter().fold(client.pstate_query("profiles", "$$profiles").key(user_id), |query, field| query.key(*field));
+            black_box(runtime.block_on(query.select::<Value>()).unwrap())
+        })
+    });
+    group.bench_function("prepared", |b| {
+        let mut user_id = 0i64;
+        b.iter(|| {
+            user_id += 1;
+            let query = prepared.clone().bind("user_id", user_id).unwrap();
+            black_box(runtime.block_on(query.execute::<Vec<Value>>(&client)).unwrap())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, select);
+criterion_main!(benches);
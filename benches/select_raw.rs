@@ -0,0 +1,57 @@
+//! Decoding a large select result into a `Value` against passing it through with
+//! `select_raw`, as a proxy would.
+//!
+//! The transport answers in-process with the same ~10 MB body every time, so the
+//! difference between the two is building the `Value` tree.
+//!
+//!     cargo bench --bench select_raw
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rama_client::{Client, HttpTransport, TransportFuture, TransportResponse};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::sync::Arc;
+use url::Url;
+
+// Answers every request with the same body.
+struct Canned(Bytes);
+
+impl HttpTransport for Canned {
+    fn post(&self, _url: Url, _headers: HeaderMap, _body: Bytes) -> TransportFuture<'_> {
+        Box::pin(async { Ok(TransportResponse::from_bytes(StatusCode::OK, HeaderMap::new(), self.0.clone())) })
+    }
+
+    fn get(&self, _url: Url, _headers: HeaderMap) -> TransportFuture<'_> {
+        Box::pin(async { Ok(TransportResponse::from_bytes(StatusCode::OK, HeaderMap::new(), self.0.clone())) })
+    }
+}
+
+// 100k profiles of about 100 bytes each.
+fn body() -> Bytes {
+    let profiles: Vec<Value> = (0..100_000)
+        .map(|n| json!({"id": n, "name": format!("user-{n}"), "email": format!("user-{n}@example.com"), "tags": ["a", "b"], "score": n as f64 / 3.0}))
+        .collect();
+    Bytes::from(serde_json::to_vec(&profiles).unwrap())
+}
+
+fn select(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let body = body();
+    let client = Client::with_transport("http://conductor:1984", Arc::new(Canned(body.clone()))).unwrap();
+
+    let mut group = c.benchmark_group(format!("select {} MB", body.len() >> 20));
+    group.sample_size(20);
+    group.bench_function("value", |b| {
+        b.iter(|| black_box(runtime.block_on(client.pstate_query("profiles", "$$profiles").all().select::<Value>()).unwrap()))
+    });
+    group.bench_function("raw", |b| {
+        b.iter(|| black_box(runtime.block_on(client.pstate_query("profiles", "$$profiles").all().select_raw()).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, select);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! Selects from a PState in a browser.
+//!
+//! Build for the web and generate the JS bindings:
+//!
+//!     cargo build --example wasm_select --target wasm32-unknown-unknown --no-default-features
+//!     wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/debug/examples/wasm_select.wasm
+//!
+//! then, from a page served by an origin the cluster allows (CORS):
+//!
+//!     import init, { select_profile } from "./pkg/wasm_select.js";
+//!     await init();
+//!     console.log(await select_profile("https://conductor:8888", "alice"));
+//!
+//! The browser follows 308 redirects itself, so requests always go through the conductor.
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use rama_client::Client;
+    use serde_json::Value;
+    use wasm_bindgen::prelude::wasm_bindgen;
+
+    /// Returns `user`'s profile from `$$profiles` in module `com.example.ProfileModule`,
+    /// as JSON.
+    #[wasm_bindgen]
+    pub async fn select_profile(conductor: String, user: String) -> Result<String, String> {
+        let client = Client::new(conductor).map_err(|e| e.to_string())?;
+        let profile: Value = client
+            .pstate_query("com.example.ProfileModule", "$$profiles")
+            .key(user)
+            .select_one()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(profile.to_string())
+    }
+}
+
+fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("This example runs in a browser; build it for wasm32-unknown-unknown (see its docs).");
+}
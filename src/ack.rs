@@ -0,0 +1,117 @@
+use crate::rama_value::RamaValue;
+use crate::ClientError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The ack of an append made with `AckLevel::Ack`: each streaming topology's ack return
+/// value, keyed by topology name. Use it as the result type of `append`, e.g.
+/// `append::<AckReturn>()`.
+///
+/// Ack values often hold Rama special types; `decoded` and `get_as` decode them (see
+/// `RamaValue`), `raw` gives the values as received.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AckReturn {
+    topologies: HashMap<String, Value>,
+}
+
+impl AckReturn {
+    /// The ack values as received, keyed by topology name.
+    pub fn raw(&self) -> &HashMap<String, Value> {
+        &self.topologies
+    }
+
+    /// Every ack value with Rama special types decoded, keyed by topology name.
+    pub fn decoded(&self) -> HashMap<String, RamaValue> {
+        self.topologies
+            .iter()
+            .map(|(topology, value)| (topology.clone(), RamaValue::decode(value)))
+            .collect()
+    }
+
+    /// Decodes `topology`'s ack value, then deserializes it as `T` (a Rama Long as an
+    /// `i64`, a map with keyword keys as a struct, and so on).
+    /// Returns None if the topology didn't return an ack value.
+    pub fn get_as<T: DeserializeOwned>(&self, topology: &str) -> Result<Option<T>, ClientError> {
+        // Guard: No ack value from this topology
+        let Some(value) = self.topologies.get(topology) else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_value(RamaValue::decode(value).into_json())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AckReturn;
+    use crate::testing::FakeCluster;
+    use crate::{ClientError, Keyword, RamaValue};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Totals {
+        count: i64,
+        by_region: HashMap<String, i64>,
+        status: String,
+    }
+
+    fn ack(value: Value) -> AckReturn {
+        serde_json::from_value(value).unwrap()
+    }
+
+    // Counts per region, with keyword keys and values as Rama Longs
+    fn nested_ack() -> Value {
+        json!({"#__Kcount": "#__L3", "#__Kby_region": {"#__Keu": "#__L2", "#__Kus": "#__L1"}, "#__Kstatus": "#__Kok"})
+    }
+
+    #[test]
+    fn decodes_a_rama_long() {
+        let ack = ack(json!({"counter": "#__L9007199254740993"}));
+        assert_eq!(ack.decoded()["counter"], RamaValue::Long(9_007_199_254_740_993));
+        assert_eq!(ack.get_as::<i64>("counter").unwrap(), Some(9_007_199_254_740_993));
+        // `raw` keeps the value as received
+        assert_eq!(ack.raw()["counter"], json!("#__L9007199254740993"));
+    }
+
+    #[test]
+    fn decodes_a_nested_map() {
+        let ack = ack(json!({"totals": nested_ack()}));
+        let by_region = BTreeMap::from([("eu".to_string(), RamaValue::Long(2)), ("us".to_string(), RamaValue::Long(1))]);
+        let expected = BTreeMap::from([
+            ("count".to_string(), RamaValue::Long(3)),
+            ("by_region".to_string(), RamaValue::Map(by_region)),
+            ("status".to_string(), RamaValue::Keyword(Keyword("ok".to_string()))),
+        ]);
+        assert_eq!(ack.decoded()["totals"], RamaValue::Map(expected));
+
+        let totals = ack.get_as::<Totals>("totals").unwrap().unwrap();
+        assert_eq!(totals, Totals { count: 3, by_region: HashMap::from([("eu".to_string(), 2), ("us".to_string(), 1)]), status: "ok".to_string() });
+    }
+
+    #[test]
+    fn missing_topologies_and_mismatched_types() {
+        let ack = ack(json!({"counter": "#__L7"}));
+        assert_eq!(ack.get_as::<i64>("other").unwrap(), None);
+        let err = ack.get_as::<String>("counter").unwrap_err();
+        assert!(matches!(err, ClientError::Json(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn ack_values_from_an_append_are_decoded() {
+        let cluster = FakeCluster::new();
+        cluster
+            .depot("analytics", "*events")
+            .ack_return("analytics", "*events", "counter", "#__L42")
+            .ack_return("analytics", "*events", "totals", nested_ack());
+        let client = cluster.client();
+
+        let ack: AckReturn = client.depot_append("analytics", "*events", json!({"region": "eu"})).append().await.unwrap();
+        assert_eq!(ack.decoded().len(), 2);
+        assert_eq!(ack.get_as::<i64>("counter").unwrap(), Some(42));
+        assert_eq!(ack.get_as::<Totals>("totals").unwrap().unwrap().by_region["eu"], 2);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::budget::{self, BudgetTracker, Subsystem};
+use crate::builder::{AckLevel, DepotHandle, DepotTransform};
+use crate::logging::{debug, warn};
+use crate::{Client, ClientError};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// Options for `Client::depot_appender_with`.
+#[derive(Debug, Clone)]
+pub struct AppenderOptions {
+    /// Records buffered before `send` waits for room.
+    pub capacity: usize,
+    /// Appends in flight at once.
+    pub max_in_flight: usize,
+    /// Ack level for each append (None = server default).
+    pub ack_level: Option<AckLevel>,
+}
+
+impl Default for AppenderOptions {
+    fn default() -> Self {
+        Self { capacity: 1024, max_in_flight: 16, ack_level: None }
+    }
+}
+
+/// A long-lived sink for depot records, for continuously running ingestion.
+///
+/// Records sent to it are appended by a background task with bounded concurrency.
+/// `send` applies backpressure once the buffer is full. Append failures don't stop the
+/// sink; they are logged and reported by `close`, which also waits for every buffered
+/// record to be appended. Buffered records count towards the client's `MemoryBudget`.
+/// With the `tokio` feature, must be created inside a Tokio runtime.
+#[derive(Debug)]
+pub struct DepotAppender<T> {
+    depot: String,
+    budget: Arc<BudgetTracker>,
+    // Buffered records, serialized, with their size as accounted in the budget
+    sender: Option<mpsc::Sender<(Value, usize)>>,
+    worker: Option<oneshot::Receiver<Vec<ClientError>>>,
+    record: PhantomData<fn(T)>,
+}
+
+impl<T: Serialize + Send + 'static> DepotAppender<T> {
+    fn spawn(client: Client, module: String, depot: String, transforms: Vec<DepotTransform>, opts: AppenderOptions) -> Self {
+        let (sender, receiver) = mpsc::channel::<(Value, usize)>(opts.capacity.max(1));
+        let worker_depot = depot.clone();
+        let budget = client.budget.clone();
+        let worker_budget = budget.clone();
+        let worker = crate::rt::spawn(async move {
+            let handle = transforms
+                .into_iter()
+                .fold(client.depot(&module, &worker_depot), DepotHandle::with_transform);
+            let records = stream::unfold(receiver, |mut receiver| async move {
+                receiver.recv().await.map(|record| (record, receiver))
+            });
+            records
+                .map(|(record, size)| {
+                    worker_budget.remove(Subsystem::AppendQueue, size);
+                    let mut append = handle.append(record);
+                    if let Some(level) = opts.ack_level {
+                        append = append.ack_level(level);
+                    }
+                    append.append::<Value>()
+                })
+                .buffer_unordered(opts.max_in_flight.max(1))
+                .filter_map(|result| {
+                    let error = result.err().inspect(|e| {
+                        warn!("[{}] Appender append to depot '{}' failed: {}", e.code(), handle.depot_name(), e);
+                    });
+                    async move { error }
+                })
+                .collect::<Vec<ClientError>>()
+                .await
+        });
+        Self { depot, budget, sender: Some(sender), worker: Some(worker), record: PhantomData }
+    }
+
+    /// Queues a record, waiting while the buffer is full. Fails with
+    /// `ClientError::BudgetExceeded` if the client's `MemoryBudget` rejects appends and
+    /// the record doesn't fit.
+    pub async fn send(&self, record: T) -> Result<(), ClientError> {
+        let sender = self.sender.as_ref().ok_or_else(|| ClientError::AppenderClosed(self.depot.clone()))?;
+        let record = serde_json::to_value(record)?;
+        let size = budget::value_size(&record);
+        self.budget.reserve_append(size)?;
+        sender.send((record, size)).await.map_err(|_| {
+            self.budget.remove(Subsystem::AppendQueue, size);
+            ClientError::AppenderClosed(self.depot.clone())
+        })
+    }
+
+    /// Stops accepting records, waits for buffered ones to be appended, and reports
+    /// any append that failed since the appender was created.
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        // Dropping the sender ends the worker's stream once the buffer drains.
+        self.sender.take();
+        let Some(worker) = self.worker.take() else { return Ok(()) };
+        let mut errors = worker.await.map_err(|_| ClientError::AppenderClosed(self.depot.clone()))?;
+        debug!("Appender for depot '{}' closed with {} failed appends", self.depot, errors.len());
+
+        // Guard: Everything was appended
+        if errors.is_empty() {
+            return Ok(());
+        }
+        let failed = errors.len();
+        Err(ClientError::AppendsFailed { depot: self.depot.clone(), failed, first: Box::new(errors.swap_remove(0)) })
+    }
+}
+
+impl<T> Drop for DepotAppender<T> {
+    fn drop(&mut self) {
+        // Guard: Closed properly
+        if self.sender.is_none() {
+            return;
+        }
+        warn!("DepotAppender for depot '{}' dropped without close(); buffered records may not be appended and errors are lost", self.depot);
+    }
+}
+
+impl Client {
+    /// Creates a `DepotAppender` with default options.
+    pub fn depot_appender<T: Serialize + Send + 'static>(&self, module: &str, depot: &str) -> DepotAppender<T> {
+        self.depot_appender_with(module, depot, AppenderOptions::default())
+    }
+
+    /// Creates a `DepotAppender` with the given options.
+    pub fn depot_appender_with<T: Serialize + Send + 'static>(&self, module: &str, depot: &str, opts: AppenderOptions) -> DepotAppender<T> {
+        self.depot(module, depot).appender(opts)
+    }
+}
+
+impl DepotHandle<'_> {
+    /// Creates a `DepotAppender` that applies this handle's transforms to every record.
+    pub fn appender<T: Serialize + Send + 'static>(&self, opts: AppenderOptions) -> DepotAppender<T> {
+        DepotAppender::spawn(
+            self.client().clone(),
+            self.module_name().to_string(),
+            self.depot_name().to_string(),
+            self.transform_list().to_vec(),
+            opts,
+        )
+    }
+}
@@ -0,0 +1,95 @@
+use crate::builder::PreparedQuery;
+use crate::logging::debug;
+use crate::{Client, ClientError};
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+
+// Queries `select_batch` runs at once.
+const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+
+impl Client {
+    /// Runs several queries concurrently (up to 16 at once) and returns their results in
+    /// the order given, e.g. the many small selects behind one dashboard page.
+    ///
+    /// Each query is sent on its own, as the REST API has no multi-select endpoint, so a
+    /// failure only affects its own entry. Queries from `prepare` give a list, ones from
+    /// `prepare_one` a single value (or `ClientError::NotFound`).
+    pub async fn select_batch(&self, queries: Vec<PreparedQuery>) -> Vec<Result<Value, ClientError>> {
+        self.select_batch_with(queries, DEFAULT_MAX_IN_FLIGHT).await
+    }
+
+    /// `select_batch` with at most `max_in_flight` queries running at once (0 is treated
+    /// as 1).
+    pub async fn select_batch_with(&self, queries: Vec<PreparedQuery>, max_in_flight: usize) -> Vec<Result<Value, ClientError>> {
+        debug!("Running a batch of {} queries, {} at a time", queries.len(), max_in_flight.max(1));
+        stream::iter(&queries)
+            .map(|query| query.execute::<Value>(self))
+            .buffered(max_in_flight.max(1))
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::FakeCluster;
+    use crate::ClientError;
+    use serde_json::json;
+
+    fn profiles() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30, "bob": 25}));
+        cluster
+    }
+
+    #[tokio::test]
+    async fn results_keep_input_order_and_failures_stay_isolated() {
+        let cluster = profiles();
+        let client = cluster.client();
+        client.discover_supervisors("profiles").await.unwrap();
+        let queries = vec![
+            client.pstate_query("profiles", "$$profiles").key("alice").prepare_one(),
+            client.pstate_query("profiles", "$$missing").key("alice").prepare(),
+            client.pstate_query("profiles", "$$profiles").key("bob").prepare(),
+            client.pstate_query("profiles", "$$profiles").key("carol").prepare_one(),
+        ];
+
+        let results = client.select_batch_with(queries, 2).await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &json!(30));
+        assert_eq!(results[1].as_ref().unwrap_err().code(), "RAMA-SERVER-NOTFOUND");
+        assert_eq!(results[2].as_ref().unwrap(), &json!([25]));
+        let err = results[3].as_ref().unwrap_err();
+        assert!(matches!(err.kind(), ClientError::NotFound { .. }), "{:?}", err);
+        assert_eq!(err.code(), "RAMA-QUERY-NOTFOUND");
+        // The failure didn't cost the other queries a retry
+        assert_eq!(cluster.requests().iter().filter(|r| r.path_suffix.starts_with("pstate/$$profiles/")).count(), 3);
+    }
+
+    #[tokio::test]
+    async fn an_empty_batch_sends_nothing() {
+        let cluster = profiles();
+        assert!(cluster.client().select_batch(Vec::new()).await.is_empty());
+        assert!(cluster.requests().is_empty());
+    }
+
+    // With every reply taking 100ms, a batch of 4 takes 100ms per round of `max_in_flight`.
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn at_most_max_in_flight_queries_run_at_once() {
+        use std::time::Duration;
+        let cluster = profiles();
+        let client = cluster.client();
+        // Warm the supervisor cache, so every query is a single request
+        client.discover_supervisors("profiles").await.unwrap();
+        cluster.latency("*", Duration::from_millis(100));
+
+        for (max_in_flight, rounds) in [(16, 1), (2, 2), (1, 4), (0, 4)] {
+            let queries = (0..4).map(|_| client.pstate_query("profiles", "$$profiles").key("alice").prepare()).collect();
+            let started = tokio::time::Instant::now();
+            let results = client.select_batch_with(queries, max_in_flight).await;
+            assert!(results.iter().all(Result::is_ok), "{:?}", results);
+            assert_eq!(started.elapsed(), Duration::from_millis(100 * rounds), "max_in_flight {}", max_in_flight);
+        }
+    }
+}
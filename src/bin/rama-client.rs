@@ -0,0 +1,75 @@
+use rama_client::lint::{analyze_path, LintSeverity, PStateSchemaHint};
+use rama_client::Path;
+use serde_json::Value;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: rama-client analyze [--bounded] [--allow <lint>]... [<path-json>]
+
+Runs the advisory path lints over a query path, given as a JSON array of navigators
+(as sent to the REST API, e.g. '[\"alice\", [\"all\"], \"#__fOps.IS_EVEN\"]'), or read
+from stdin when omitted. Prints one finding per line and exits with 0.
+
+  --bounded        every collection in the PState is known to stay small
+  --allow <lint>   don't report findings of this kind (e.g. duplicate-must-keys)";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Guard: The only subcommand is `analyze`
+    let Some(("analyze", rest)) = args.split_first().map(|(command, rest)| (command.as_str(), rest)) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    match analyze(rest) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn analyze(args: &[String]) -> Result<(), String> {
+    let mut hint = PStateSchemaHint::default();
+    let mut allowed = Vec::new();
+    let mut path_json = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bounded" => hint.bounded_collections = true,
+            "--allow" => allowed.push(args.next().ok_or("--allow needs a lint identifier")?.as_str()),
+            _ if path_json.is_none() => path_json = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    let path_json = match path_json {
+        Some(json) => json,
+        None => {
+            let mut json = String::new();
+            std::io::stdin().read_to_string(&mut json).map_err(|e| format!("can't read stdin: {}", e))?;
+            json
+        }
+    };
+    let navigators: Vec<Value> = serde_json::from_str(&path_json).map_err(|e| format!("the path isn't a JSON array: {}", e))?;
+
+    for lint in analyze_path(&Path::from(navigators), Some(&hint)) {
+        if allowed.contains(&lint.kind.as_str()) {
+            continue;
+        }
+        let severity = match lint.severity {
+            LintSeverity::Info => "info",
+            LintSeverity::Warning => "warning",
+        };
+        println!(
+            "{}[{}] navigators {}..{}: {}\n  suggestion: {}",
+            severity,
+            lint.kind.as_str(),
+            lint.span.start,
+            lint.span.end,
+            lint.message,
+            lint.suggestion
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,206 @@
+//! A synchronous client for non-async code (CLI tools, rayon jobs).
+//!
+//! `blocking::Client` drives the async `Client` on a private single-worker runtime, so
+//! routing, redirects, the supervisor cache and every other client option behave
+//! exactly as in async code. The query/append builders are the same types as the
+//! async ones (parameterized by client), so paths are built with identical methods;
+//! only `select`/`append`/`invoke` are synchronous here.
+//!
+//! Like `reqwest::blocking`, this must not be used from within an async runtime:
+//! calls (and dropping the client) will panic there.
+
+use crate::builder::{DepotAppendBuilder, DepotHandle, PStateQueryBuilder, PreparedQuery, QueryInvokeBuilder};
+use crate::{ClientBuilder, ClientError, DetachedFailures, RequestMeta, WithMeta};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+
+/// Synchronous counterpart of `crate::Client`.
+#[derive(Debug)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> Result<Self, ClientError> {
+        Self::from_async(crate::Client::new(base_url)?)
+    }
+
+    /// Wraps an already-configured async client, e.g. one from `ClientBuilder`.
+    pub fn from_async(inner: crate::Client) -> Result<Self, ClientError> {
+        // One worker thread keeps background work (e.g. stale-result refreshes)
+        // progressing between calls.
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("rama-client-blocking")
+            .enable_all()
+            .build()
+            .map_err(ClientError::Runtime)?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The async client this wraps, for operations without a blocking wrapper.
+    pub fn as_async(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Runs a future to completion on this client's runtime, e.g. one from `as_async()`.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// Blocking `crate::Client::raw_request`.
+    pub fn raw_request<T: Serialize, R: DeserializeOwned>(&self, module: &str, path_suffix: &str, body: &T) -> Result<R, ClientError> {
+        self.block_on(self.inner.raw_request(module, path_suffix, body))
+    }
+
+    /// Blocking `crate::Client::ping`.
+    pub fn ping(&self) -> Result<Duration, ClientError> {
+        self.block_on(self.inner.ping())
+    }
+
+    /// Blocking `crate::Client::ping_module`.
+    pub fn ping_module(&self, module: &str) -> Result<Duration, ClientError> {
+        self.block_on(self.inner.ping_module(module))
+    }
+
+    /// Blocking `crate::Client::select_batch`.
+    pub fn select_batch(&self, queries: Vec<PreparedQuery>) -> Vec<Result<Value, ClientError>> {
+        self.block_on(self.inner.select_batch(queries))
+    }
+
+    /// Blocking `crate::Client::select_batch_with`.
+    pub fn select_batch_with(&self, queries: Vec<PreparedQuery>, max_in_flight: usize) -> Vec<Result<Value, ClientError>> {
+        self.block_on(self.inner.select_batch_with(queries, max_in_flight))
+    }
+
+    /// `crate::Client::depot_append_detached`; the appends are sent by this client's
+    /// runtime.
+    pub fn depot_append_detached<T: Serialize>(&self, module: &str, depot: &str, data: T) -> Result<(), ClientError> {
+        let _runtime = self.runtime.enter();
+        self.inner.depot_append_detached(module, depot, data)
+    }
+
+    /// Blocking `crate::Client::flush`.
+    pub fn flush(&self, timeout: Duration) -> Result<(), ClientError> {
+        self.block_on(self.inner.flush(timeout))
+    }
+
+    /// `crate::Client::detached_failures`.
+    pub fn detached_failures(&self) -> DetachedFailures {
+        self.inner.detached_failures()
+    }
+
+    // --- Builder Entry Points ---
+
+    /// Starts building a query against a PState of the given module.
+    pub fn pstate_query(&self, module: &str, pstate: &str) -> PStateQueryBuilder<'_, Client> {
+        PStateQueryBuilder::new(self, module, pstate)
+    }
+
+    /// Starts building an invocation of a query topology of the given module.
+    pub fn query_invoke(&self, module: &str, query: &str) -> QueryInvokeBuilder<'_, Client> {
+        QueryInvokeBuilder::new(self, module, query)
+    }
+
+    /// Starts building an append of `data` to a depot of the given module.
+    pub fn depot_append<T: Serialize>(&self, module: &str, depot: &str, data: T) -> DepotAppendBuilder<'_, T, Client> {
+        DepotAppendBuilder::new(self, module, depot, data)
+    }
+
+    /// Returns a handle to a depot of the given module. See `crate::builder::DepotHandle`.
+    pub fn depot(&self, module: &str, depot: &str) -> DepotHandle<'_, Client> {
+        DepotHandle::new(self, module, depot)
+    }
+}
+
+impl ClientBuilder {
+    /// Builds a `blocking::Client` with this configuration.
+    pub fn build_blocking(self) -> Result<Client, ClientError> {
+        Client::from_async(self.build()?)
+    }
+}
+
+// --- Blocking Execution Methods ---
+
+impl PStateQueryBuilder<'_, Client> {
+    /// Blocking `PStateQueryBuilder::select`.
+    pub fn select<R: DeserializeOwned>(self) -> Result<Vec<R>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_one`.
+    pub fn select_one<R: DeserializeOwned>(self) -> Result<R, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_one())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_with_meta`.
+    pub fn select_with_meta<R: DeserializeOwned>(self) -> Result<(Vec<R>, RequestMeta), ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_with_meta())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_one_with_meta`.
+    pub fn select_one_with_meta<R: DeserializeOwned>(self) -> Result<(R, RequestMeta), ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_one_with_meta())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_raw`.
+    pub fn select_raw(self) -> Result<Box<RawValue>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_raw())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_one_raw`.
+    pub fn select_one_raw(self) -> Result<Box<RawValue>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_one_raw())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_or_stale`.
+    pub fn select_or_stale<R: DeserializeOwned>(self) -> Result<WithMeta<Vec<R>>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_or_stale())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_one_opt`.
+    pub fn select_one_opt<R: DeserializeOwned>(self) -> Result<Option<R>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_one_opt())
+    }
+
+    /// Blocking `PStateQueryBuilder::select_one_or_stale`.
+    pub fn select_one_or_stale<R: DeserializeOwned>(self) -> Result<WithMeta<R>, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).select_one_or_stale())
+    }
+}
+
+impl QueryInvokeBuilder<'_, Client> {
+    /// Blocking `QueryInvokeBuilder::invoke`.
+    pub fn invoke<R: DeserializeOwned>(self) -> Result<R, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).invoke())
+    }
+
+    /// Blocking `QueryInvokeBuilder::invoke_with_meta`.
+    pub fn invoke_with_meta<R: DeserializeOwned>(self) -> Result<(R, RequestMeta), ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).invoke_with_meta())
+    }
+}
+
+impl<T: Serialize> DepotAppendBuilder<'_, T, Client> {
+    /// Blocking `DepotAppendBuilder::append`.
+    pub fn append<R: DeserializeOwned>(self) -> Result<R, ClientError> {
+        let client = self.client();
+        client.block_on(self.with_client(&client.inner).append())
+    }
+}
@@ -0,0 +1,261 @@
+use crate::ClientError;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bounds the approximate memory a client (and its clones) retains in its caches and
+/// append queues.
+///
+/// When usage exceeds `max_bytes`, the stale-result store evicts its oldest entries and
+/// the call inventory stops recording new entries. The supervisor cache is accounted
+/// for but never evicted, since routing depends on it. Records waiting in append queues
+/// (`DepotAppender`, `Client::depot_append_detached`) are accounted for too; with
+/// `reject_appends`, a record that would take usage past `max_bytes` is refused with
+/// `ClientError::BudgetExceeded` instead of being queued.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryBudget {
+    pub max_bytes: usize,
+    /// Whether append queues refuse records over the budget. Off: they only count them.
+    pub reject_appends: bool,
+}
+
+/// Approximate bytes retained by each part of a client. See `Client::memory_usage`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub supervisor_cache_bytes: usize,
+    pub stale_results_bytes: usize,
+    pub inventory_bytes: usize,
+    /// Records queued for appending and not sent yet.
+    pub append_queue_bytes: usize,
+    /// The configured budget, if any.
+    pub limit_bytes: Option<usize>,
+}
+
+impl MemoryUsage {
+    pub fn total_bytes(&self) -> usize {
+        self.supervisor_cache_bytes + self.stale_results_bytes + self.inventory_bytes + self.append_queue_bytes
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Subsystem {
+    SupervisorCache,
+    StaleResults,
+    Inventory,
+    AppendQueue,
+}
+
+// Shared accounting for all subsystems of a client. Always tracks usage; only enforces
+// anything when a budget is configured.
+#[derive(Debug, Default)]
+pub(crate) struct BudgetTracker {
+    limit: Option<usize>,
+    reject_appends: bool,
+    supervisor_cache: AtomicUsize,
+    stale_results: AtomicUsize,
+    inventory: AtomicUsize,
+    append_queue: AtomicUsize,
+}
+
+impl BudgetTracker {
+    pub(crate) fn new(budget: Option<MemoryBudget>) -> Self {
+        Self {
+            limit: budget.map(|b| b.max_bytes),
+            reject_appends: budget.is_some_and(|b| b.reject_appends),
+            ..Self::default()
+        }
+    }
+
+    fn counter(&self, subsystem: Subsystem) -> &AtomicUsize {
+        match subsystem {
+            Subsystem::SupervisorCache => &self.supervisor_cache,
+            Subsystem::StaleResults => &self.stale_results,
+            Subsystem::Inventory => &self.inventory,
+            Subsystem::AppendQueue => &self.append_queue,
+        }
+    }
+
+    pub(crate) fn add(&self, subsystem: Subsystem, bytes: usize) {
+        self.counter(subsystem).fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove(&self, subsystem: Subsystem, bytes: usize) {
+        // Saturate rather than wrap if estimates ever disagree.
+        let _ = self.counter(subsystem).fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+            Some(current.saturating_sub(bytes))
+        });
+    }
+
+    // Accounts for a record entering an append queue, or refuses it if appends are
+    // rejected over the budget and it doesn't fit. `remove` it once it leaves the queue.
+    pub(crate) fn reserve_append(&self, bytes: usize) -> Result<(), ClientError> {
+        if let Some(limit) = self.limit.filter(|_| self.reject_appends) {
+            let used = self.usage().total_bytes();
+            // Guard: Doesn't fit
+            if used + bytes > limit {
+                return Err(ClientError::BudgetExceeded { requested: bytes, used, limit });
+            }
+        }
+        self.add(Subsystem::AppendQueue, bytes);
+        Ok(())
+    }
+
+    // True if a budget is configured and usage exceeds it.
+    pub(crate) fn over_budget(&self) -> bool {
+        self.limit.is_some_and(|limit| self.usage().total_bytes() > limit)
+    }
+
+    pub(crate) fn usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            supervisor_cache_bytes: self.supervisor_cache.load(Ordering::Relaxed),
+            stale_results_bytes: self.stale_results.load(Ordering::Relaxed),
+            inventory_bytes: self.inventory.load(Ordering::Relaxed),
+            append_queue_bytes: self.append_queue.load(Ordering::Relaxed),
+            limit_bytes: self.limit,
+        }
+    }
+}
+
+// Rough in-memory size of a JSON value; good enough for budgeting, not exact.
+pub(crate) fn value_size(value: &Value) -> usize {
+    let nested = match value {
+        Value::String(s) => s.len(),
+        Value::Array(items) => items.iter().map(value_size).sum(),
+        Value::Object(map) => map.iter().map(|(k, v)| k.len() + value_size(v)).sum(),
+        _ => 0,
+    };
+    std::mem::size_of::<Value>() + nested
+}
+
+// Rough in-memory size of a list of strings (supervisor locations).
+pub(crate) fn strings_size(strings: &[String]) -> usize {
+    strings.iter().map(|s| std::mem::size_of::<String>() + s.len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryBudget;
+    use crate::testing::FakeCluster;
+    use crate::{ClientBuilder, RetryPolicy, ServeStale};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        let profiles: serde_json::Map<String, Value> = (0..20).map(|n| (format!("user-{n}"), json!("x".repeat(100)))).collect();
+        cluster.pstate("profiles", "$$profiles", Value::Object(profiles));
+        cluster
+    }
+
+    fn budgeted(cluster: &FakeCluster, max_bytes: usize) -> ClientBuilder {
+        cluster.client_builder().memory_budget(MemoryBudget { max_bytes, reject_appends: false })
+    }
+
+    #[tokio::test]
+    async fn stale_results_evict_the_oldest_under_pressure() {
+        let cluster = cluster();
+        let client = budgeted(&cluster, 2_000)
+            .serve_stale(ServeStale { max_staleness: Duration::from_secs(60) })
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        let select = |n: usize| client.pstate_query("profiles", "$$profiles").key(format!("user-{n}")).select_or_stale::<String>();
+
+        for n in 0..20 {
+            select(n).await.unwrap();
+        }
+        let usage = client.memory_usage();
+        assert!(usage.total_bytes() <= 2_000, "{:?}", usage);
+        assert!(usage.stale_results_bytes > 0);
+
+        // The cluster goes down: only the most recent results are still there to serve
+        cluster.respond_with("profiles", "pstate/$$profiles/select", StatusCode::SERVICE_UNAVAILABLE, "down");
+        assert!(select(19).await.unwrap().stale);
+        assert!(select(0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn inventory_stops_recording_when_over_budget() {
+        let cluster = cluster();
+        let client = budgeted(&cluster, 1).record_inventory().build().unwrap();
+
+        // Recorded before anything else is retained; the reply then fills the budget
+        client.pstate_query("profiles", "$$profiles").key("user-0").select::<Value>().await.unwrap();
+        client.pstate_query("profiles", "$$profiles").key("user-0").key("name").select::<Value>().await.unwrap();
+
+        let inventory = client.inventory().unwrap();
+        assert_eq!(inventory.calls.len(), 1);
+        assert!(inventory.truncated);
+    }
+
+    #[tokio::test]
+    async fn supervisor_cache_is_accounted_but_never_evicted() {
+        let cluster = cluster();
+        let client = budgeted(&cluster, 1).build().unwrap();
+
+        client.pstate_query("profiles", "$$profiles").key("user-0").select::<Value>().await.unwrap();
+        let usage = client.memory_usage();
+        assert!(usage.supervisor_cache_bytes > 1);
+        assert_eq!(usage.limit_bytes, Some(1));
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, ["fake-supervisor-1:1984"]);
+    }
+
+    #[cfg(feature = "tokio")]
+    fn rejecting(cluster: &FakeCluster, max_bytes: usize) -> crate::Client {
+        cluster.depot("telemetry", "*events");
+        cluster.client_builder().memory_budget(MemoryBudget { max_bytes, reject_appends: true }).build().unwrap()
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn detached_appends_over_budget_are_rejected() {
+        let cluster = cluster();
+        let client = rejecting(&cluster, 1_000);
+        let record = json!({"payload": "x".repeat(600)});
+
+        client.depot_append_detached("telemetry", "*events", &record).unwrap();
+        assert!(client.memory_usage().append_queue_bytes >= 600);
+        let err = client.depot_append_detached("telemetry", "*events", &record).unwrap_err();
+        assert!(matches!(err, crate::ClientError::BudgetExceeded { limit: 1_000, .. }), "{err:?}");
+        assert_eq!(err.code(), "RAMA-APPEND-BUDGET");
+
+        // The rejected record was never queued, and sent records leave the budget
+        client.flush(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(client.memory_usage().append_queue_bytes, 0);
+        assert_eq!(cluster.appended("telemetry", "*events"), [record]);
+        assert_eq!(client.detached_failures().dropped, 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn appender_sends_over_budget_are_rejected() {
+        let cluster = cluster();
+        let client = rejecting(&cluster, 1_000);
+        let record = json!({"payload": "x".repeat(600)});
+
+        let appender = client.depot_appender::<Value>("telemetry", "*events");
+        appender.send(record.clone()).await.unwrap();
+        assert!(client.memory_usage().append_queue_bytes >= 600);
+        let err = appender.send(record.clone()).await.unwrap_err();
+        assert!(matches!(err, crate::ClientError::BudgetExceeded { limit: 1_000, .. }), "{err:?}");
+        assert_eq!(err.code(), "RAMA-APPEND-BUDGET");
+
+        appender.close().await.unwrap();
+        assert_eq!(client.memory_usage().append_queue_bytes, 0);
+        assert_eq!(cluster.appended("telemetry", "*events"), [record]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn appends_are_only_counted_unless_rejected() {
+        let cluster = cluster();
+        cluster.depot("telemetry", "*events");
+        let client = budgeted(&cluster, 1).build().unwrap();
+
+        client.depot_append_detached("telemetry", "*events", json!({"n": 1})).unwrap();
+        assert!(client.memory_usage().append_queue_bytes > 1);
+        client.flush(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(client.memory_usage().append_queue_bytes, 0);
+        assert_eq!(cluster.appended("telemetry", "*events"), [json!({"n": 1})]);
+    }
+}
@@ -1,7 +1,21 @@
-use crate::{Client, ClientError};
+use crate::canonical::CanonicalPath;
+use crate::idempotency;
+use crate::lint::{analyze_navigators, PathLint};
+use crate::params::{self, param_name, BodyTemplate};
+use crate::path_limits;
+use crate::projection::Projection;
+use crate::request_id;
+use crate::{Client, ClientError, Path, PathStats, PStatePager, PageKey, RequestMeta, VisibilityPolling, WithMeta};
+use futures::stream::{self, Stream, StreamExt};
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use bytes::Bytes;
+use serde_json::value::RawValue;
 use serde_json::Value;
+use crate::logging::warn;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
 // --- Helper functions for Rama Special Types ---
 
@@ -25,16 +39,79 @@ pub fn rama_float(val: f32) -> Value {
     Value::String(format!("#__F{}", val))
 }
 
+/// Creates a JSON value representing a Rama Double. Doubles have no special-type prefix:
+/// plain JSON numbers with a fraction already decode as doubles. NaN and infinities have no
+/// JSON representation and become null.
+pub fn rama_double(val: f64) -> Value {
+    Value::from(val)
+}
+
 /// Creates a JSON string value representing a Rama Char.
 pub fn rama_char(val: char) -> Value {
     Value::String(format!("#__C{}", val))
 }
 
-/// Creates a JSON string value representing a Rama Clojure Keyword.
+/// Creates a JSON string value representing a Rama Clojure Keyword, e.g. `"id"` for `:id`.
+/// A namespaced keyword can be given whole (`"user/id"`) or via `rama_keyword_ns`.
+///
+/// # Panics
+/// If the keyword is empty, starts with `:`, or contains whitespace or commas. Keywords
+/// are almost always literals, so a bad one is a programming error rather than a runtime
+/// condition.
 pub fn rama_keyword(val: &str) -> Value {
+    check_namespaced("keyword", val);
     Value::String(format!("#__K{}", val))
 }
 
+/// Creates a JSON string value representing a namespaced Rama Clojure Keyword,
+/// e.g. `rama_keyword_ns("user", "id")` for `:user/id`.
+///
+/// # Panics
+/// Under the same conditions as `rama_keyword`, for either part, or if `name` contains `/`.
+pub fn rama_keyword_ns(ns: &str, name: &str) -> Value {
+    let keyword = format!("{}/{}", ns, name);
+    check_name_part("keyword", &keyword, ns);
+    check_name_part("keyword", &keyword, name);
+    assert!(!name.contains('/'), "invalid Rama keyword '{}': the name can't contain '/'", keyword);
+    Value::String(format!("#__K{}", keyword))
+}
+
+/// Creates a JSON string value representing a Clojure Symbol, e.g. `"clojure.core/inc"`,
+/// as some path arguments take: `"#__Y<symbol>"`.
+///
+/// # Panics
+/// Under the same conditions as `rama_keyword`: empty, a leading `:`, whitespace or commas.
+pub fn rama_symbol(val: &str) -> Value {
+    check_namespaced("symbol", val);
+    Value::String(format!("#__Y{}", val))
+}
+
+// Panics unless `val` is a valid `kind` ("keyword" or "symbol"), namespaced or not.
+fn check_namespaced(kind: &str, val: &str) {
+    let (ns, name) = match val.split_once('/') {
+        // `:/` and `/` are valid; only split when there is a namespace before the slash.
+        Some((ns, name)) if !ns.is_empty() => (Some(ns), name),
+        _ => (None, val),
+    };
+    if let Some(ns) = ns {
+        check_name_part(kind, val, ns);
+    }
+    check_name_part(kind, val, name);
+}
+
+// Panics with a message naming `whole` if `part` can't appear in a Clojure `kind`.
+fn check_name_part(kind: &str, whole: &str, part: &str) {
+    assert!(!part.is_empty(), "invalid Rama {} '{}': empty namespace or name", kind, whole);
+    assert!(!part.starts_with(':'), "invalid Rama {} '{}': leave off the leading ':'", kind, whole);
+    assert!(
+        !part.chars().any(|c| c.is_whitespace() || c == ','),
+        "invalid Rama {} '{}': {}s can't contain whitespace or commas",
+        kind,
+        whole,
+        kind
+    );
+}
+
 /// Creates a JSON string value representing a Rama Function reference.
 pub fn rama_function(name: &str) -> Value {
     Value::String(format!("#__f{}", name))
@@ -45,43 +122,327 @@ pub fn rama_ops_function(name: &str) -> Value {
     rama_function(&format!("Ops.{}", name))
 }
 
+/// Creates a JSON value representing an instant. The REST API has no instant type, so it
+/// is encoded as a Rama Long of milliseconds since the Unix epoch (what
+/// `Instant.toEpochMilli` gives on the module side).
+#[cfg(feature = "chrono")]
+pub fn rama_instant(val: chrono::DateTime<chrono::Utc>) -> Value {
+    rama_long(val.timestamp_millis())
+}
+
+/// Creates a JSON value representing a UUID. The REST API has no UUID type, so it is
+/// encoded as its hyphenated string form (what `UUID.toString` gives on the module side).
+#[cfg(feature = "uuid")]
+pub fn rama_uuid(val: uuid::Uuid) -> Value {
+    Value::String(val.hyphenated().to_string())
+}
+
+// --- Native Rust Values ---
+
+/// Encodes a Rust value as the JSON the REST API decodes into the matching Java type.
+/// Navigator arguments (`nav`, `key`, `must`, the range navigators) take this instead of
+/// `Into<Value>`, so e.g. an `i64` key reaches the module as a Long rather than an Integer.
+///
+/// | Rust              | Java         | JSON                     |
+/// |-------------------|--------------|--------------------------|
+/// | `i8`              | `Byte`       | `"#__B<n>"`              |
+/// | `i16`             | `Short`      | `"#__S<n>"`              |
+/// | `i32`             | `Integer`    | number                   |
+/// | `i64`             | `Long`       | `"#__L<n>"`              |
+/// | `f32`             | `Float`      | `"#__F<n>"`              |
+/// | `f64`             | `Double`     | number                   |
+/// | `char`            | `Character`  | `"#__C<c>"`              |
+/// | `&str`, `String`  | `String`     | string                   |
+/// | `bool`            | `Boolean`    | `true` / `false`         |
+/// | `Keyword`         | `Keyword`    | `"#__K<name>"`           |
+/// | `Value`           | as decoded   | unchanged                |
+///
+/// A `Value` is passed through as-is, so values built with the `rama_*` helpers or `json!`
+/// still work.
+pub trait ToRamaValue {
+    fn to_rama_value(&self) -> Value;
+}
+
+impl ToRamaValue for i8 {
+    fn to_rama_value(&self) -> Value {
+        rama_byte(*self)
+    }
+}
+
+impl ToRamaValue for i16 {
+    fn to_rama_value(&self) -> Value {
+        rama_short(*self)
+    }
+}
+
+impl ToRamaValue for i32 {
+    fn to_rama_value(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl ToRamaValue for i64 {
+    fn to_rama_value(&self) -> Value {
+        rama_long(*self)
+    }
+}
+
+impl ToRamaValue for f32 {
+    fn to_rama_value(&self) -> Value {
+        rama_float(*self)
+    }
+}
+
+impl ToRamaValue for f64 {
+    fn to_rama_value(&self) -> Value {
+        rama_double(*self)
+    }
+}
+
+impl ToRamaValue for char {
+    fn to_rama_value(&self) -> Value {
+        rama_char(*self)
+    }
+}
+
+impl ToRamaValue for str {
+    fn to_rama_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToRamaValue for String {
+    fn to_rama_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl ToRamaValue for bool {
+    fn to_rama_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToRamaValue for Value {
+    fn to_rama_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: ToRamaValue + ?Sized> ToRamaValue for &T {
+    fn to_rama_value(&self) -> Value {
+        (**self).to_rama_value()
+    }
+}
+
+/// A Clojure keyword, without the leading `:` (e.g. `Keyword("user/id".into())` for
+/// `:user/id`). Encoded with `rama_keyword`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Keyword(pub String);
+
+impl ToRamaValue for Keyword {
+    fn to_rama_value(&self) -> Value {
+        rama_keyword(&self.0)
+    }
+}
+
+// --- Object Names ---
+
+/// The canonical form of a PState name: `name` unchanged if it already starts with `$$`,
+/// otherwise `$$` + `name` (`"profiles"` and `"$$profiles"` both give `"$$profiles"`).
+/// Applied to every PState name the client is given.
+pub fn pstate_name(name: &str) -> String {
+    match name.starts_with("$$") {
+        true => name.to_string(),
+        false => format!("$${}", name),
+    }
+}
+
+/// The canonical form of a depot name: `name` unchanged if it already starts with `*`,
+/// otherwise `*` + `name` (`"registerDepot"` and `"*registerDepot"` both give
+/// `"*registerDepot"`). Applied to every depot name the client is given.
+pub fn depot_name(name: &str) -> String {
+    match name.starts_with('*') {
+        true => name.to_string(),
+        false => format!("*{}", name),
+    }
+}
+
+// --- Decoding Rama Special Types ---
+
+/// Reads a Rama Long from a response value, in either its `#__L` string form or as a
+/// plain JSON integer (which some endpoints return). Exact across the whole `i64` range.
+pub fn decode_rama_long(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.strip_prefix("#__L")?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads a Rama Clojure Keyword from a response value, without the leading `:`
+/// (e.g. `"user/id"` for `:user/id`).
+pub fn decode_rama_keyword(value: &Value) -> Option<&str> {
+    value.as_str()?.strip_prefix("#__K")
+}
+
+/// Reads a Clojure Symbol encoded as by `rama_symbol`.
+pub fn decode_rama_symbol(value: &Value) -> Option<&str> {
+    value.as_str()?.strip_prefix("#__Y")
+}
+
+/// Reads a Rama Double from a response value (a JSON number). Integers are widened.
+pub fn decode_rama_double(value: &Value) -> Option<f64> {
+    value.as_f64()
+}
+
+/// Reads an instant encoded as by `rama_instant` (epoch milliseconds, as a Rama Long or
+/// a JSON integer).
+#[cfg(feature = "chrono")]
+pub fn decode_rama_instant(value: &Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp_millis(decode_rama_long(value)?)
+}
+
+/// Reads a UUID encoded as by `rama_uuid`.
+#[cfg(feature = "uuid")]
+pub fn decode_rama_uuid(value: &Value) -> Option<uuid::Uuid> {
+    uuid::Uuid::parse_str(value.as_str()?).ok()
+}
+
+/// `deserialize_with` helper for `i64` fields that may hold Rama Longs, e.g.
+/// `#[serde(deserialize_with = "rama_client::builder::deserialize_rama_long")]`.
+/// Accepts the same forms as `decode_rama_long`.
+pub fn deserialize_rama_long<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    decode_rama_long(&value)
+        .ok_or_else(|| serde::de::Error::custom(format!("expected a Rama Long, got {}", value)))
+}
+
 
 // --- PState Query Builder ---
 
 /// Builds a PState query path.
 ///
 /// Use the methods to add navigators to the path, then call `select` or `select_one`.
+///
+/// ```
+/// # #[cfg(feature = "test-util")]
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), rama_client::ClientError> {
+/// # let cluster = rama_client::doc_harness::profiles();
+/// # let client = cluster.client();
+/// let tags: Vec<String> = client
+///     .pstate_query("ProfilesModule", "$$profiles")
+///     .key("alice")
+///     .key("tags")
+///     .all()
+///     .select()
+///     .await?;
+/// assert_eq!(tags, ["admin", "beta"]);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "test-util"))]
+/// # fn main() {}
+/// ```
+///
+/// `C` is the client executing the query: the async `Client` by default, or
+/// `blocking::Client` (with the `blocking` feature). Path building is shared by both.
+///
+/// Builders are cheap to clone, so a partially built query can serve as a template and be
+/// finished several ways.
 #[derive(Debug)]
-pub struct PStateQueryBuilder<'a> {
+pub struct PStateQueryBuilder<'a, C = Client> {
     // Need a mutable reference or owned client? Let's try shared ref first.
-    client: &'a Client,
+    client: &'a C,
     module: String,
     pstate: String,
     path: Vec<Value>,
+    hedge: Option<Duration>, // Overrides the client's default hedge delay
+    projection: Option<Projection>, // Client-side field selection on results
+    request_id: Option<String>, // Overrides the generated request ID
+    partition: Option<u32>, // Routes to this partition's supervisors, if known
 }
 
-impl<'a> PStateQueryBuilder<'a> {
-    pub(crate) fn new(client: &'a Client, module: &str, pstate: &str) -> Self {
+impl<C> Clone for PStateQueryBuilder<'_, C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client,
+            module: self.module.clone(),
+            pstate: self.pstate.clone(),
+            path: self.path.clone(),
+            hedge: self.hedge,
+            projection: self.projection.clone(),
+            request_id: self.request_id.clone(),
+            partition: self.partition,
+        }
+    }
+}
+
+impl<'a, C> PStateQueryBuilder<'a, C> {
+    pub(crate) fn new(client: &'a C, module: &str, pstate: &str) -> Self {
         Self {
             client,
             module: module.to_string(),
-            pstate: pstate.to_string(),
+            pstate: pstate_name(pstate),
             path: Vec::new(),
+            hedge: None,
+            projection: None,
+            request_id: None,
+            partition: None,
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn client(&self) -> &'a C {
+        self.client
+    }
+
+    // Moves the query onto another client type (e.g. from the blocking client to the async one it wraps).
+    #[cfg(feature = "blocking")]
+    pub(crate) fn with_client<D>(self, client: &'a D) -> PStateQueryBuilder<'a, D> {
+        PStateQueryBuilder {
+            client,
+            module: self.module,
+            pstate: self.pstate,
+            path: self.path,
+            hedge: self.hedge,
+            projection: self.projection,
+            request_id: self.request_id,
+            partition: self.partition,
         }
     }
 
     // --- Implicit Navigators ---
 
-    /// Adds an implicit navigator (e.g., String, number, boolean, null, special type).
+    /// Adds an implicit navigator (e.g., String, number, boolean, null, special type),
+    /// encoded per `ToRamaValue`.
     /// Often equivalent to `key` for strings/keywords or `filterPred` for functions.
-    pub fn nav(mut self, value: impl Into<Value>) -> Self {
-        self.path.push(value.into());
+    pub fn nav(mut self, value: impl ToRamaValue) -> Self {
+        self.path.push(value.to_rama_value());
+        self
+    }
+
+    /// Adds a key navigator, encoded per `ToRamaValue` (so an `i64` key is a Long).
+    ///
+    /// A missing key still navigates, to null: `select` returns `[null]` rather than
+    /// nothing. Use `must_key` to select nothing instead.
+    pub fn key(self, key: impl ToRamaValue) -> Self {
+        self.nav(key)
+    }
+
+    /// Adds a key navigator whose key is filled in later, with `PreparedQuery::bind`.
+    /// For queries run many times with different keys: `prepare` the query once, then
+    /// bind and execute copies of it. Executing the builder itself fails with
+    /// `ClientError::UnboundQueryParameter`.
+    pub fn key_param(mut self, name: &str) -> Self {
+        self.path.push(params::placeholder(name));
         self
     }
 
-    /// Adds a key navigator (implicitly wraps the string key).
-    pub fn key(self, key: impl Into<String>) -> Self {
-        self.nav(key.into())
+    /// Appends every navigator of a prebuilt `Path`.
+    pub fn nav_path(mut self, path: &Path) -> Self {
+        self.path.extend_from_slice(path.navigators());
+        self
     }
 
     /// Adds a filterPred navigator using a Rama function reference (e.g., "#__fOps.IS_EVEN").
@@ -92,26 +453,52 @@ impl<'a> PStateQueryBuilder<'a> {
     // --- Explicit Navigators (Examples) ---
     // These construct a JSON array: `["opName", arg1, arg2, ...]`
 
-    fn add_explicit_nav(mut self, op: &str, args: Vec<Value>) -> Self {
+    /// Adds any explicit navigator: `["op", arg1, arg2, ...]`.
+    ///
+    /// The escape hatch for navigators without a dedicated method (including ones newer
+    /// than this crate); the dedicated methods build the same JSON, so both mix freely
+    /// in one path. E.g. `.explicit_nav("sortedMapRange", [rama_long(0), rama_long(10)])`.
+    pub fn explicit_nav(mut self, op: &str, args: impl IntoIterator<Item = impl Into<Value>>) -> Self {
         let mut nav_array = vec![Value::String(op.to_string())];
-        nav_array.extend(args);
+        nav_array.extend(args.into_iter().map(Into::into));
         self.path.push(Value::Array(nav_array));
         self
     }
 
     /// Adds the "all" navigator: `["all"]`.
     pub fn all(self) -> Self {
-        self.add_explicit_nav("all", vec![])
+        self.explicit_nav("all", Vec::<Value>::new())
+    }
+
+    /// Adds the "must" navigator: `["must", key1, key2, ...]`, keys encoded as by `key`.
+    ///
+    /// Unlike `key`, navigation stops at a missing key, so `select` returns `[]` (and
+    /// `select_one_opt` None) rather than `[null]`. This matters when null is a legitimate
+    /// value, or when more navigators follow and shouldn't run on an absent entry.
+    pub fn must(self, keys: impl IntoIterator<Item = impl ToRamaValue>) -> Self {
+        self.explicit_nav("must", keys.into_iter().map(|key| key.to_rama_value()))
+    }
+
+    /// Adds a "must" navigator for a single key: `["must", key]`. See `must`.
+    pub fn must_key(self, key: impl ToRamaValue) -> Self {
+        self.must([key])
+    }
+
+    /// Adds the "sortedMapRange" navigator: `["sortedMapRange", start, end]`, for keys from
+    /// `start` (inclusive) to `end` (exclusive).
+    pub fn sorted_map_range(self, start: impl ToRamaValue, end: impl ToRamaValue) -> Self {
+        self.explicit_nav("sortedMapRange", [start.to_rama_value(), end.to_rama_value()])
     }
 
-    /// Adds the "must" navigator: `["must", key1, key2, ...]`.
-    pub fn must(self, keys: impl IntoIterator<Item = impl Into<Value>>) -> Self {
-        self.add_explicit_nav("must", keys.into_iter().map(Into::into).collect())
+    /// Adds the "sortedMapRangeFrom" navigator: `["sortedMapRangeFrom", start, max]`, for
+    /// at most `max` keys from `start` (inclusive).
+    pub fn sorted_map_range_from(self, start: impl ToRamaValue, max: u32) -> Self {
+        self.explicit_nav("sortedMapRangeFrom", [start.to_rama_value(), Value::from(max)])
     }
 
     /// Adds the "mapVals" navigator: `["mapVals"]`.
     pub fn map_vals(self) -> Self {
-         self.add_explicit_nav("mapVals", vec![])
+         self.explicit_nav("mapVals", Vec::<Value>::new())
     }
 
     /// Adds a "filterSelected" navigator: `["filterSelected", path...]`.
@@ -137,32 +524,531 @@ impl<'a> PStateQueryBuilder<'a> {
     }
 
     // Add more explicit navigator methods here based on the documentation...
-    // e.g., multiPath, view, termVal, sortedMapRange, etc.
+    // e.g., multiPath, view, termVal, etc.
+
+    // --- Analysis ---
+
+    /// The size of the path built so far, as checked against `ClientBuilder::max_path_navigators`
+    /// and `ClientBuilder::max_path_bytes` when the query runs.
+    pub fn path_stats(&self) -> PathStats {
+        path_limits::measure(&self.path)
+    }
+
+    /// Runs the advisory path lints over the path built so far. See `lint::analyze_path`.
+    pub fn lints(&self) -> Vec<PathLint> {
+        analyze_navigators(&self.path, None)
+    }
+
+    /// The path built so far, as it will be sent (a JSON array of navigators).
+    /// Doesn't consume the builder, so more navigators can still be added.
+    /// `Display` prints the same JSON, pretty-printed. Objects are sent with their keys
+    /// sorted (see `Path::to_canonical_json`), whatever order this `Value` has them in.
+    pub fn path_json(&self) -> Value {
+        Value::Array(self.path.clone())
+    }
+
+    /// The path built so far as an owned `Path`, e.g. to cache and reuse with `nav_path`.
+    pub fn into_path(self) -> Path {
+        Path::from(self.path)
+    }
+
+    // --- Execution Options ---
+
+    /// Hedges this read: if no response arrives within `delay`, a second attempt is sent to
+    /// a different cached supervisor and whichever answers first wins.
+    /// Overrides the client's default hedge delay. The two requests share the client's
+    /// `max_redirects` attempt budget.
+    pub fn hedge(mut self, delay: Duration) -> Self {
+        self.hedge = Some(delay);
+        self
+    }
+
+    /// Uses `id` as this request's ID (sent in the client's request ID header, and included
+    /// in logs and errors) instead of a generated one.
+    pub fn request_id(mut self, id: impl Into<String>) -> Self {
+        self.request_id = Some(id.into());
+        self
+    }
+
+    /// Sends the query to a supervisor of partition `index`, saving the supervisor-side hop
+    /// to the partition that holds the data.
+    ///
+    /// Only takes effect when the conductor lists `Supervisor-Locations` per partition or
+    /// per task group and they're cached; otherwise the query is routed as usual. Replaces hedging, since
+    /// both attempts would go to the same partition. Applies to `select`, `select_one`
+    /// and their `_opt`, `_raw` and `_with_meta` variants.
+    pub fn partition_index(mut self, index: u32) -> Self {
+        self.partition = Some(index);
+        self
+    }
+
+    /// Trims each result down to the listed fields before it is deserialized, for wide
+    /// values where the caller needs only a few fields and the path can't change.
+    ///
+    /// Plain names pick top-level keys; names starting with `/` are JSON pointers
+    /// (e.g. `"/profile/avatar_url"`) whose value is kept at the same nested position.
+    /// Results that aren't JSON objects are returned unchanged. The full value is still
+    /// transferred; this saves deserialization work, not bandwidth (see `project_on_server`).
+    pub fn project(mut self, fields: &[&str]) -> Self {
+        self.projection = Some(Projection::new(fields, false));
+        self
+    }
+
+    /// Like `project`, but the server does the picking, so only the listed fields are
+    /// transferred: the path is sent with a `multiPath` navigator appended, one key path
+    /// per field (`["multiPath", ["name"], ["profile", "avatar_url"]]`), and each result
+    /// is rebuilt from the values it selects.
+    ///
+    /// Only for paths that select maps. A field whose value is null is left out, like a
+    /// missing one, and a result with none of the fields is null (so `select_one` fails
+    /// with `ClientError::NotFound`). `select_one` runs as a `select`. The stale variants
+    /// project client-side, and `select_raw` and `select_stream` don't project.
+    pub fn project_on_server(mut self, fields: &[&str]) -> Self {
+        self.projection = Some(Projection::new(fields, true));
+        self
+    }
+
+    /// Captures the query without executing it, as a `select`. The result owns its data and
+    /// can be executed later, repeatedly, or in batches. Projection, hedging, the request
+    /// ID and the partition index are kept (a fixed request ID then applies to every
+    /// execution).
+    pub fn prepare(self) -> PreparedQuery {
+        PreparedQuery::new(self, false)
+    }
+
+    /// Like `prepare`, but executes via `selectOne`.
+    pub fn prepare_one(self) -> PreparedQuery {
+        PreparedQuery::new(self, true)
+    }
+}
+
+impl<C> fmt::Display for PStateQueryBuilder<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_pretty(f, &CanonicalPath(&self.path))
+    }
+}
 
+impl<'a> PStateQueryBuilder<'a> {
     // --- Execution Methods ---
 
     /// Executes the query using the constructed path via the `select` endpoint.
     /// Expects a list of results.
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), rama_client::ClientError> {
+    /// # let cluster = rama_client::doc_harness::profiles();
+    /// # let client = cluster.client();
+    /// let ages: Vec<u32> = client.pstate_query("ProfilesModule", "$$profiles").map_vals().key("age").select().await?;
+    /// assert_eq!(ages, [30, 25]);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
     pub async fn select<R: DeserializeOwned>(self) -> Result<Vec<R>, ClientError> {
-        let path_suffix = format!("pstate/{}/select", self.pstate);
-        // The body for PState queries is the JSON array representing the path
-        self.client
-            .send_request(&self.module, &path_suffix, &self.path)
-            .await
+        self.record("select");
+        let Some(projection) = &self.projection else {
+            return self.send("select").await;
+        };
+        let (values, _) = self.select_projected(projection).await?;
+        values
+            .into_iter()
+            .map(|v| Ok(serde_json::from_value(v)?))
+            .collect()
+    }
+
+    /// Like `select`, but decodes results one at a time as the response body arrives, so
+    /// memory stays bounded by the largest single result rather than the whole list.
+    ///
+    /// Redirects are followed before anything is streamed. Request errors, and any error
+    /// mid-stream, are yielded once and end the stream. Hedging and projection don't apply.
+    pub fn select_stream<R: DeserializeOwned + 'a>(self) -> impl Stream<Item = Result<R, ClientError>> + 'a {
+        self.record("select");
+        let request = async move {
+            params::check_bound(&self.path)?;
+            self.client.check_path_size(&self.path)?;
+            let path_suffix = format!("pstate/{}/select", self.pstate);
+            let body = CanonicalPath(&self.path);
+            let request = self.client.send_streaming_request(&self.module, &path_suffix, &body);
+            request_id::scope(self.request_id.clone(), request).await
+        };
+        stream::once(request).flat_map(|response| match response {
+            Ok(response) => crate::json_stream::array_elements(response).left_stream(),
+            Err(e) => stream::once(async { Err(e) }).right_stream(),
+        })
     }
 
     /// Executes the query using the constructed path via the `selectOne` endpoint.
-    /// Expects a single result. Errors if 0 or >1 results are found by the server.
+    /// Expects a single result. Errors if 0 or >1 results are found by the server; a
+    /// `null` answer (how some server versions report no result) is
+    /// `ClientError::NotFound`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), rama_client::ClientError> {
+    /// # let cluster = rama_client::doc_harness::profiles();
+    /// # let client = cluster.client();
+    /// #[derive(serde::Deserialize)]
+    /// struct Profile {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// let bob: Profile = client.pstate_query("ProfilesModule", "$$profiles").key("bob").select_one().await?;
+    /// assert_eq!((bob.name.as_str(), bob.age), ("Bob", 25));
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
     pub async fn select_one<R: DeserializeOwned>(self) -> Result<R, ClientError> {
-        let path_suffix = format!("pstate/{}/selectOne", self.pstate);
-        // The body is the same path array
-        self.client
-            .send_request(&self.module, &path_suffix, &self.path)
-            .await
+        self.record("selectOne");
+        let (value, _) = self.select_one_value().await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Like `select`, also returning how the request was served (attempts, redirects,
+    /// whether a cached supervisor was used, ...).
+    pub async fn select_with_meta<R: DeserializeOwned>(self) -> Result<(Vec<R>, RequestMeta), ClientError> {
+        self.record("select");
+        let (values, meta): (Vec<Value>, _) = match &self.projection {
+            Some(projection) => self.select_projected(projection).await?,
+            None => self.send_with_meta("select").await?,
+        };
+        let values = values.into_iter().map(serde_json::from_value).collect::<Result<_, _>>()?;
+        Ok((values, meta))
+    }
+
+    /// Like `select_one`, also returning how the request was served.
+    pub async fn select_one_with_meta<R: DeserializeOwned>(self) -> Result<(R, RequestMeta), ClientError> {
+        self.record("selectOne");
+        let (value, meta) = self.select_one_value().await?;
+        Ok((serde_json::from_value(value)?, meta))
+    }
+
+    /// Like `select`, but returns the response body undecoded, for forwarding verbatim.
+    /// The body is only checked to be valid JSON; no `Value` tree is built. Projection
+    /// doesn't apply.
+    pub async fn select_raw(self) -> Result<Box<RawValue>, ClientError> {
+        self.record("select");
+        self.send("select").await
+    }
+
+    /// Like `select_one`, but returns the result undecoded (see `select_raw`). A `null`
+    /// answer is still `ClientError::NotFound`.
+    pub async fn select_one_raw(self) -> Result<Box<RawValue>, ClientError> {
+        self.record("selectOne");
+        let raw: Box<RawValue> = self.send("selectOne").await?;
+        // Guard: Nothing selected
+        if raw.get() == "null" {
+            return Err(not_found(&self.module, &self.pstate, &self.path));
+        }
+        Ok(raw)
+    }
+
+    /// Pages through the sorted map this path navigates to, `page_size` entries at a time.
+    /// See `PStatePager`.
+    pub fn paginate<K: PageKey, R: DeserializeOwned>(self, page_size: u32) -> PStatePager<'a, K, R> {
+        PStatePager::new(self.client, self.module, self.pstate, self.path, self.hedge, page_size)
+    }
+
+    /// Like `select_one`, but `Ok(None)` when the path selects nothing, and
+    /// `ClientError::MultipleResults` when it selects more than one value.
+    ///
+    /// Runs as a `select` so the result count is known exactly rather than inferred from
+    /// a `selectOne` error response. Nothing means no values, or a single `null` (what a
+    /// `key` navigator selects for a missing entry, and what `select_one` reports as
+    /// `ClientError::NotFound`), so `R` needn't be an `Option` to decode it.
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), rama_client::ClientError> {
+    /// # let cluster = rama_client::doc_harness::profiles();
+    /// # let client = cluster.client();
+    /// let carol: Option<serde_json::Value> = client.pstate_query("ProfilesModule", "$$profiles").key("carol").select_one_opt().await?;
+    /// assert_eq!(carol, None);
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn select_one_opt<R: DeserializeOwned>(self) -> Result<Option<R>, ClientError> {
+        let mut values: Vec<Value> = self.select().await?;
+        // Guard: More than one result
+        if values.len() > 1 {
+            return Err(ClientError::MultipleResults(values.len()));
+        }
+        match values.pop() {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => Ok(Some(serde_json::from_value(value)?)),
+        }
+    }
+
+    /// Like `select`, but may return the last known good result (flagged `stale`) if the
+    /// client has a `ServeStale` policy and the request fails with a retryable error.
+    pub async fn select_or_stale<R: DeserializeOwned>(self) -> Result<WithMeta<Vec<R>>, ClientError> {
+        self.record("select");
+        let result = self.send_or_stale("select").await?;
+        match &self.projection {
+            Some(projection) => decode_meta(result.map(|v| projection.apply_each(v))),
+            None => decode_meta(result),
+        }
+    }
+
+    /// Like `select_one`, with the same stale fallback as `select_or_stale`.
+    pub async fn select_one_or_stale<R: DeserializeOwned>(self) -> Result<WithMeta<R>, ClientError> {
+        self.record("selectOne");
+        let result = self.send_or_stale("selectOne").await?;
+        match &self.projection {
+            Some(projection) => decode_meta(result.map(|v| projection.apply(v))),
+            None => decode_meta(result),
+        }
+    }
+
+    // A `select` with `projection` applied to each result (see `Projection::apply_all`).
+    async fn select_projected(&self, projection: &Projection) -> Result<(Vec<Value>, RequestMeta), ClientError> {
+        let (values, meta) = self.send_path_with_meta("select", &projection.wire_path(&self.path)).await?;
+        Ok((projection.apply_all(values), meta))
+    }
+
+    // The non-null result of a `selectOne`, projected. A server-side projection needs a
+    // `select`, so its result count is checked here instead.
+    async fn select_one_value(&self) -> Result<(Value, RequestMeta), ClientError> {
+        let (value, meta) = match &self.projection {
+            Some(projection) if projection.on_server => {
+                let (values, meta) = self.select_projected(projection).await?;
+                (single(values)?, meta)
+            }
+            Some(projection) => {
+                let (value, meta) = self.send_with_meta("selectOne").await?;
+                (projection.apply(value), meta)
+            }
+            None => self.send_with_meta("selectOne").await?,
+        };
+        Ok((not_null(value, &self.module, &self.pstate, &self.path)?, meta))
+    }
+
+    // Sends the path (the body for PState queries) to a PState endpoint, under this
+    // query's request ID if one was set.
+    async fn send<R: DeserializeOwned>(&self, operation: &str) -> Result<R, ClientError> {
+        self.send_with_meta(operation).await.map(|(value, _)| value)
+    }
+
+    // Like `send`, also returning the request's metadata.
+    async fn send_with_meta<R: DeserializeOwned>(&self, operation: &str) -> Result<(R, RequestMeta), ClientError> {
+        self.send_path_with_meta(operation, &self.path).await
+    }
+
+    // Like `send_with_meta`, sending `path` (the builder's path, possibly rewritten).
+    async fn send_path_with_meta<R: DeserializeOwned>(&self, operation: &str, path: &[Value]) -> Result<(R, RequestMeta), ClientError> {
+        params::check_bound(path)?;
+        self.client.check_path_size(path)?;
+        let path_suffix = format!("pstate/{}/{}", self.pstate, operation);
+        let client = self.client;
+        let request = async {
+            match self.partition {
+                Some(partition) => client.send_partition_request_meta(&self.module, &path_suffix, &CanonicalPath(path), partition, self.hedge).await,
+                None => client.send_idempotent_request_meta(&self.module, &path_suffix, &CanonicalPath(path), self.hedge).await,
+            }
+        };
+        request_id::scope(self.request_id.clone(), request).await
+    }
+
+    // Like `send`, with the stale fallback.
+    async fn send_or_stale(&self, operation: &str) -> Result<WithMeta<Value>, ClientError> {
+        params::check_bound(&self.path)?;
+        self.client.check_path_size(&self.path)?;
+        let path_suffix = format!("pstate/{}/{}", self.pstate, operation);
+        let body = CanonicalPath(&self.path);
+        let request = self.client.send_read_or_stale(&self.module, &path_suffix, &body, self.hedge);
+        request_id::scope(self.request_id.clone(), request).await
+    }
+
+    // Adds this query to the client's call inventory, if recording.
+    fn record(&self, operation: &str) {
+        self.client.record_call(&self.module, &self.pstate, operation, Some(&self.path));
+    }
+}
+
+// --- Prepared Query ---
+
+/// A PState query captured by `PStateQueryBuilder::prepare`, independent of any client borrow.
+///
+/// A query built with `key_param` placeholders is a template: `bind` fills them in. Its
+/// request body is serialized once, at `prepare`, with gaps for the placeholders, so
+/// executing a bound copy only serializes the bound values. Copies share the template,
+/// so `clone` and `bind` are cheap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedQuery {
+    shape: Arc<QueryShape>,
+    // Values for the template's parameters, indexed like `BodyTemplate::params`
+    bound: Vec<Option<Value>>,
+}
+
+// What every copy of a prepared query shares.
+#[derive(Debug, PartialEq)]
+struct QueryShape {
+    module: String,
+    pstate: String,
+    path: Vec<Value>,
+    wire_path: Vec<Value>, // As sent: `path`, plus a server-side projection's `multiPath`
+    one: bool, // selectOne instead of select
+    template: Option<BodyTemplate>, // Of `wire_path`; None without placeholders
+    // Options carried over from the builder
+    projection: Option<Projection>,
+    hedge: Option<Duration>,
+    request_id: Option<String>,
+    partition: Option<u32>,
+}
+
+impl PreparedQuery {
+    fn new<C>(builder: PStateQueryBuilder<'_, C>, one: bool) -> Self {
+        let PStateQueryBuilder { module, pstate, path, hedge, projection, request_id, partition, .. } = builder;
+        let wire_path = projection.as_ref().map_or_else(|| path.clone(), |projection| projection.wire_path(&path).into_owned());
+        let template = BodyTemplate::new(&wire_path);
+        let bound = vec![None; template.as_ref().map_or(0, |t| t.params.len())];
+        let shape = QueryShape { module, pstate, path, wire_path, one, template, projection, hedge, request_id, partition };
+        Self { shape: Arc::new(shape), bound }
+    }
+
+    pub fn module(&self) -> &str {
+        &self.shape.module
+    }
+
+    pub fn pstate(&self) -> &str {
+        &self.shape.pstate
+    }
+
+    /// The path as prepared, placeholders included.
+    pub fn path(&self) -> &[Value] {
+        &self.shape.path
+    }
+
+    /// Names of the query's `key_param` placeholders, in path order.
+    pub fn params(&self) -> &[String] {
+        self.shape.template.as_ref().map_or(&[], |t| &t.params)
     }
+
+    /// Fills every placeholder named `name` with `value`, encoded per `ToRamaValue`.
+    /// Binding a name again replaces its value. Executing fails with
+    /// `ClientError::UnboundQueryParameter` while any placeholder is unbound.
+    ///
+    /// Fails with `ClientError::UnknownQueryParameter` if the query has no placeholder
+    /// named `name`.
+    pub fn bind(mut self, name: &str, value: impl ToRamaValue) -> Result<Self, ClientError> {
+        // Guard: No such placeholder
+        let Some(index) = self.params().iter().position(|param| param == name) else {
+            return Err(ClientError::UnknownQueryParameter {
+                pstate: self.shape.pstate.clone(),
+                name: name.to_string(),
+                params: self.params().to_vec(),
+            });
+        };
+        self.bound[index] = Some(value.to_rama_value());
+        Ok(self)
+    }
+
+    /// Executes the query with `client`. Returns a list for `prepare`, a single value for
+    /// `prepare_one`, deserialized into `R`.
+    pub async fn execute<R: DeserializeOwned>(&self, client: &Client) -> Result<R, ClientError> {
+        let shape = &*self.shape;
+        let operation = if shape.one { "selectOne" } else { "select" };
+        client.record_call(&shape.module, &shape.pstate, operation, Some(&shape.path));
+        let body = match &shape.template {
+            Some(template) => template.render(&self.bound)?,
+            None => Bytes::from(serde_json::to_vec(&CanonicalPath(&shape.wire_path))?),
+        };
+        client.check_serialized_path_size(&shape.wire_path, body.len())?;
+
+        // Guard: Nothing to do between the response and `R`
+        let Some(projection) = &shape.projection else {
+            let path_suffix = format!("pstate/{}/{}", shape.pstate, operation);
+            if !shape.one {
+                return self.send(client, &path_suffix, &body).await;
+            }
+            let value = self.send(client, &path_suffix, &body).await?;
+            return Ok(serde_json::from_value(not_null(value, &shape.module, &shape.pstate, &self.bound_path())?)?);
+        };
+
+        // A server-side projection needs a `select`, even for `prepare_one`
+        let operation = if projection.on_server { "select" } else { operation };
+        let path_suffix = format!("pstate/{}/{}", shape.pstate, operation);
+        let value: Value = self.send(client, &path_suffix, &body).await?;
+        let value = match (shape.one, projection.on_server) {
+            (false, _) => Value::Array(projection.apply_all(serde_json::from_value(value)?)),
+            (true, true) => single(projection.apply_all(serde_json::from_value(value)?))?,
+            (true, false) => projection.apply(value),
+        };
+        if !shape.one {
+            return Ok(serde_json::from_value(value)?);
+        }
+        Ok(serde_json::from_value(not_null(value, &shape.module, &shape.pstate, &self.bound_path())?)?)
+    }
+
+    // Sends the rendered body, routed per the query's hedge and partition options and
+    // under its request ID, if it has one.
+    async fn send<R: DeserializeOwned>(&self, client: &Client, path_suffix: &str, body: &Bytes) -> Result<R, ClientError> {
+        let shape = &*self.shape;
+        let request = async {
+            match shape.partition {
+                Some(partition) => client.send_partition_bytes_meta(&shape.module, path_suffix, body, partition, shape.hedge).await,
+                None => client.send_idempotent_bytes_meta(&shape.module, path_suffix, body, shape.hedge).await,
+            }
+        };
+        request_id::scope(shape.request_id.clone(), request).await.map(|(value, _)| value)
+    }
+
+    // The path with bound values in place of placeholders, for errors.
+    fn bound_path(&self) -> Vec<Value> {
+        self.shape
+            .path
+            .iter()
+            .map(|nav| {
+                let bound = param_name(nav).and_then(|name| {
+                    let index = self.params().iter().position(|param| param == name)?;
+                    self.bound[index].clone()
+                });
+                bound.unwrap_or_else(|| nav.clone())
+            })
+            .collect()
+    }
+}
+
+// The one result of a `select`, as `selectOne` would answer it: `null` for none,
+// `ClientError::MultipleResults` for more.
+fn single(mut values: Vec<Value>) -> Result<Value, ClientError> {
+    // Guard: More than one result
+    if values.len() > 1 {
+        return Err(ClientError::MultipleResults(values.len()));
+    }
+    Ok(values.pop().unwrap_or(Value::Null))
+}
+
+// Passes a `selectOne` result through, or `ClientError::NotFound` if it is `null`.
+fn not_null(value: Value, module: &str, pstate: &str, path: &[Value]) -> Result<Value, ClientError> {
+    // Guard: Nothing selected
+    if value.is_null() {
+        return Err(not_found(module, pstate, path));
+    }
+    Ok(value)
+}
+
+fn not_found(module: &str, pstate: &str, path: &[Value]) -> ClientError {
+    ClientError::NotFound { module: module.to_string(), pstate: pstate.to_string(), path: Value::Array(path.to_vec()) }
+}
+
+// Deserializes the value of a `WithMeta<Value>` into the caller's type.
+fn decode_meta<R: DeserializeOwned>(result: WithMeta<Value>) -> Result<WithMeta<R>, ClientError> {
+    let WithMeta { value, stale, warning } = result;
+    Ok(WithMeta { value: serde_json::from_value(value)?, stale, warning })
 }
 
 
+
 // --- Depot Append Builder ---
 
 /// Represents the acknowledgment levels for depot appends.
@@ -185,24 +1071,148 @@ struct DepotAppendBody<T: Serialize> {
     ack_level: Option<AckLevel>,
 }
 
-/// Builds a Depot append request.
-#[derive(Debug)]
-pub struct DepotAppendBuilder<'a, T: Serialize> {
-    client: &'a Client,
-    module: String,
-    depot: String,
+/// A transformation applied to a record, as JSON, before it is appended.
+pub type DepotTransform = Arc<dyn Fn(Value) -> Result<Value, ClientError> + Send + Sync>;
+
+// Ordered transform chain; wrapped for a Debug impl (closures aren't Debug).
+#[derive(Clone, Default)]
+struct Transforms(Vec<DepotTransform>);
+
+impl Transforms {
+    // Runs every transform in order, tagging a failure with the depot and transform index.
+    fn apply(&self, depot: &str, mut data: Value) -> Result<Value, ClientError> {
+        for (index, transform) in self.0.iter().enumerate() {
+            data = transform(data).map_err(|e| ClientError::TransformFailed {
+                depot: depot.to_string(),
+                index,
+                source: Box::new(e),
+            })?;
+        }
+        Ok(data)
+    }
+}
+
+impl fmt::Debug for Transforms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transforms({})", self.0.len())
+    }
+}
+
+/// A depot of a module with per-depot settings applied to every append made through it.
+///
+/// Created with `Client::depot`. Cheap to clone.
+#[derive(Debug)]
+pub struct DepotHandle<'a, C = Client> {
+    client: &'a C,
+    module: String,
+    depot: String,
+    transforms: Transforms,
+}
+
+impl<C> Clone for DepotHandle<'_, C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client,
+            module: self.module.clone(),
+            depot: self.depot.clone(),
+            transforms: self.transforms.clone(),
+        }
+    }
+}
+
+impl<'a, C> DepotHandle<'a, C> {
+    pub(crate) fn new(client: &'a C, module: &str, depot: &str) -> Self {
+        Self { client, module: module.to_string(), depot: depot_name(depot), transforms: Transforms::default() }
+    }
+
+    /// Adds a transformation applied to each record after serialization, e.g. to upgrade
+    /// old event schemas at the edge. Transforms run in the order they were added; an error
+    /// fails the append with `ClientError::TransformFailed`.
+    pub fn with_transform(mut self, transform: DepotTransform) -> Self {
+        self.transforms.0.push(transform);
+        self
+    }
+
+    pub(crate) fn client(&self) -> &'a C {
+        self.client
+    }
+
+    pub(crate) fn module_name(&self) -> &str {
+        &self.module
+    }
+
+    pub(crate) fn depot_name(&self) -> &str {
+        &self.depot
+    }
+
+    pub(crate) fn transform_list(&self) -> &[DepotTransform] {
+        &self.transforms.0
+    }
+
+    /// Starts building an append of `data` to this depot.
+    pub fn append<T: Serialize>(&self, data: T) -> DepotAppendBuilder<'a, T, C> {
+        let mut builder = DepotAppendBuilder::new(self.client, &self.module, &self.depot, data);
+        builder.transforms = self.transforms.clone();
+        builder
+    }
+}
+
+/// Builds a Depot append request.
+///
+/// Like `PStateQueryBuilder`, generic over the executing client type.
+///
+/// ```
+/// # #[cfg(feature = "test-util")]
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), rama_client::ClientError> {
+/// # let cluster = rama_client::doc_harness::profiles();
+/// # let client = cluster.client();
+/// use rama_client::builder::AckLevel;
+/// use rama_client::AckReturn;
+/// use serde_json::json;
+///
+/// // The default ack level waits for the streaming topologies and returns their acks
+/// let ack: AckReturn = client.depot_append("ProfilesModule", "*profileEdits", json!({"id": "alice", "age": 31})).append().await?;
+/// assert_eq!(ack.get_as::<bool>("profiles")?, Some(true));
+///
+/// // `AppendAck` only waits for the depot, so there are no topology acks
+/// let ack: AckReturn = client
+///     .depot_append("ProfilesModule", "*profileEdits", json!({"id": "bob", "age": 26}))
+///     .ack_level(AckLevel::AppendAck)
+///     .append()
+///     .await?;
+/// assert!(ack.raw().is_empty());
+/// assert_eq!(cluster.appended("ProfilesModule", "*profileEdits").len(), 2);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "test-util"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct DepotAppendBuilder<'a, T: Serialize, C = Client> {
+    client: &'a C,
+    module: String,
+    depot: String,
     data: T, // Data is required
     ack_level: Option<AckLevel>, // Defaults to server default ("ack") if None
+    transforms: Transforms, // From the `DepotHandle`, if any
+    request_id: Option<String>, // Overrides the generated request ID
+    idempotency_key: Option<Value>, // Injected into the data at `idempotency_key_pointer`
+    idempotency_key_pointer: String,
 }
 
-impl<'a, T: Serialize> DepotAppendBuilder<'a, T> {
-     pub(crate) fn new(client: &'a Client, module: &str, depot: &str, data: T) -> Self {
+impl<'a, T: Serialize, C> DepotAppendBuilder<'a, T, C> {
+     pub(crate) fn new(client: &'a C, module: &str, depot: &str, data: T) -> Self {
         Self {
             client,
             module: module.to_string(),
-            depot: depot.to_string(),
+            depot: depot_name(depot),
             data,
             ack_level: None,
+            transforms: Transforms::default(),
+            request_id: None,
+            idempotency_key: None,
+            idempotency_key_pointer: idempotency::DEFAULT_POINTER.to_string(),
         }
     }
 
@@ -213,19 +1223,917 @@ impl<'a, T: Serialize> DepotAppendBuilder<'a, T> {
         self
     }
 
+    /// Uses `id` as this request's ID instead of a generated one.
+    /// See `PStateQueryBuilder::request_id`.
+    pub fn request_id(mut self, id: impl Into<String>) -> Self {
+        self.request_id = Some(id.into());
+        self
+    }
+
+    /// Sets `key` as a field of the record, at `idempotency_key_pointer`
+    /// (`/idempotencyKey` by default), so a module can recognize a retried append.
+    ///
+    /// The body is built once per append, so retries under the client's `RetryPolicy`
+    /// resend the same key. To retry by hand (e.g. after a timeout), append again with
+    /// the same explicit key. That is all the client guarantees: discarding duplicates is
+    /// up to the module's topology, which must read the field and remember the keys it
+    /// has seen. Depots whose topologies don't will still append a retried record twice.
+    pub fn idempotency_key(mut self, key: impl Into<Value>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Sets a random UUID (as a string) as the idempotency key. See `idempotency_key`.
+    /// The key is chosen now, so `body_json` and the append see the same one.
+    #[cfg(feature = "uuid")]
+    pub fn auto_idempotency_key(self) -> Self {
+        self.idempotency_key(uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Where `idempotency_key` puts the key, as a JSON pointer into the record after
+    /// transforms (e.g. `/dedupeId`, or `/meta/dedupeId` for a nested field). Missing
+    /// objects along the way are created. The record must be a JSON object; appending
+    /// fails with `ClientError::InvalidIdempotencyKeyPointer` if the pointer leads
+    /// through anything else.
+    pub fn idempotency_key_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.idempotency_key_pointer = pointer.into();
+        self
+    }
+
+    /// The full request body (`data` after any transforms and with any idempotency key,
+    /// plus `ackLevel`) exactly as it will be sent. Doesn't consume the builder. `Display`
+    /// prints the same JSON, pretty-printed.
+    pub fn body_json(&self) -> Result<Value, ClientError> {
+        let mut data = self.transforms.apply(&self.depot, serde_json::to_value(&self.data)?)?;
+        if let Some(key) = &self.idempotency_key {
+            idempotency::inject(&mut data, &self.idempotency_key_pointer, key.clone())?;
+        }
+        Ok(serde_json::to_value(DepotAppendBody { data, ack_level: self.ack_level })?)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn client(&self) -> &'a C {
+        self.client
+    }
+
+    // Moves the append onto another client type.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn with_client<D>(self, client: &'a D) -> DepotAppendBuilder<'a, T, D> {
+        DepotAppendBuilder {
+            client,
+            module: self.module,
+            depot: self.depot,
+            data: self.data,
+            ack_level: self.ack_level,
+            transforms: self.transforms,
+            request_id: self.request_id,
+            idempotency_key: self.idempotency_key,
+            idempotency_key_pointer: self.idempotency_key_pointer,
+        }
+    }
+}
+
+impl<T: Serialize, C> fmt::Display for DepotAppendBuilder<'_, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.body_json() {
+            Ok(body) => write_pretty(f, &body),
+            Err(e) => write!(f, "<invalid append body: {}>", e),
+        }
+    }
+}
+
+impl<T: Serialize> DepotAppendBuilder<'_, T> {
     /// Executes the depot append request.
     ///
     /// The type `R` depends on the `ackLevel`:
-    /// - `AckLevel::Ack`: `HashMap<String, Value>` (topology name -> ack return value)
+    /// - `AckLevel::Ack`: `AckReturn`, or `HashMap<String, Value>` (topology name -> ack return value)
     /// - `AckLevel::AppendAck` or `AckLevel::None`: `serde_json::Value::Object` (empty map `{}`)
+    ///
+    /// Fails with `ClientError::TopologyFailure` if a streaming topology threw while
+    /// processing the record, whether the server reports it with an error status or in
+    /// the ack.
     pub async fn append<R: DeserializeOwned>(self) -> Result<R, ClientError> {
-        let body = DepotAppendBody {
-            data: self.data,
-            ack_level: self.ack_level,
-        };
+        self.client.record_call(&self.module, &self.depot, "append", None);
+        let body = self.body_json()?;
         let path_suffix = format!("depot/{}/append", self.depot);
+        let request = self.client.send_append(&self.module, &path_suffix, &body);
+        request_id::scope(self.request_id, request).await
+    }
+
+    /// Appends, then polls `query` until `visible` shows the write has been applied.
+    /// Returns the append's ack and the first query result accepted by `visible`.
+    ///
+    /// See `VisibilityPolling`.
+    pub async fn append_and_await<A, R, F>(
+        self,
+        query: &PreparedQuery,
+        visible: F,
+        polling: VisibilityPolling,
+    ) -> Result<(A, R), ClientError>
+    where
+        A: DeserializeOwned,
+        R: DeserializeOwned,
+        F: Fn(&R) -> bool,
+    {
+        let client = self.client;
+        let ack = self.append().await?;
+        let result = client.await_visible(query, visible, polling).await?;
+        Ok((ack, result))
+    }
+}
+
+
+// Writes `value` as pretty-printed JSON, for the builders' `Display` impls.
+fn write_pretty(f: &mut fmt::Formatter<'_>, value: &impl Serialize) -> fmt::Result {
+    let pretty = serde_json::to_string_pretty(value).map_err(|_| fmt::Error)?;
+    f.write_str(&pretty)
+}
+
+// --- Query Invoke Builder ---
+
+// Safety cap on pages fetched by `QueryInvokeBuilder::paginate` unless overridden.
+const DEFAULT_MAX_PAGES: usize = 1000;
+
+/// Builds an invocation of a query topology.
+///
+/// Add arguments in order with `arg`/`args`, then call `invoke`.
+///
+/// Like `PStateQueryBuilder`, generic over the executing client type.
+#[derive(Debug)]
+pub struct QueryInvokeBuilder<'a, C = Client> {
+    client: &'a C,
+    module: String,
+    query: String,
+    args: Vec<Value>,
+    hedge: Option<Duration>, // Overrides the client's default hedge delay
+    max_pages: usize, // Only used by `paginate`
+    request_id: Option<String>, // Overrides the generated request ID
+}
+
+impl<'a, C> QueryInvokeBuilder<'a, C> {
+    pub(crate) fn new(client: &'a C, module: &str, query: &str) -> Self {
+        Self {
+            client,
+            module: module.to_string(),
+            query: query.to_string(),
+            args: Vec::new(),
+            hedge: None,
+            max_pages: DEFAULT_MAX_PAGES,
+            request_id: None,
+        }
+    }
+
+    /// Appends one argument to the invocation.
+    pub fn arg(mut self, value: impl Into<Value>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    /// Appends several arguments to the invocation, in order.
+    pub fn args(mut self, values: impl IntoIterator<Item = impl Into<Value>>) -> Self {
+        self.args.extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    /// Hedges this invoke across two cached supervisors. See `PStateQueryBuilder::hedge`.
+    /// Query topologies are read-only, so both attempts reaching the server is harmless.
+    pub fn hedge(mut self, delay: Duration) -> Self {
+        self.hedge = Some(delay);
+        self
+    }
+
+    /// Caps the number of invocations `paginate` makes. If the cap is reached while the
+    /// result still has a next token, the stream ends with `ClientError::PaginationLimit`.
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Uses `id` as this request's ID instead of a generated one.
+    /// See `PStateQueryBuilder::request_id`.
+    pub fn request_id(mut self, id: impl Into<String>) -> Self {
+        self.request_id = Some(id.into());
+        self
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn client(&self) -> &'a C {
         self.client
-            .send_request(&self.module, &path_suffix, &body)
-            .await
+    }
+
+    // Moves the invoke onto another client type.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn with_client<D>(self, client: &'a D) -> QueryInvokeBuilder<'a, D> {
+        QueryInvokeBuilder {
+            client,
+            module: self.module,
+            query: self.query,
+            args: self.args,
+            hedge: self.hedge,
+            max_pages: self.max_pages,
+            request_id: self.request_id,
+        }
+    }
+}
+
+impl<'a> QueryInvokeBuilder<'a> {
+    /// Invokes the query topology with the arguments added so far.
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-util")]
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), rama_client::ClientError> {
+    /// # let cluster = rama_client::doc_harness::profiles();
+    /// # let client = cluster.client();
+    /// let profile: serde_json::Value = client.query_invoke("ProfilesModule", "getProfile").arg("alice").invoke().await?;
+    /// assert_eq!(profile["tags"], serde_json::json!(["admin", "beta"]));
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    pub async fn invoke<R: DeserializeOwned>(self) -> Result<R, ClientError> {
+        self.invoke_ref().await
+    }
+
+    /// Like `invoke`, also returning how the request was served.
+    pub async fn invoke_with_meta<R: DeserializeOwned>(self) -> Result<(R, RequestMeta), ClientError> {
+        self.client.record_call(&self.module, &self.query, "invoke", None);
+        let path_suffix = format!("query/{}/invoke", self.query);
+        let request = self.client.send_idempotent_request_meta(&self.module, &path_suffix, &self.args, self.hedge);
+        request_id::scope(self.request_id.clone(), request).await
+    }
+
+    // Invokes without consuming the builder, so pagination can re-invoke it.
+    async fn invoke_ref<R: DeserializeOwned>(&self) -> Result<R, ClientError> {
+        self.client.record_call(&self.module, &self.query, "invoke", None);
+        let path_suffix = format!("query/{}/invoke", self.query);
+        // The body for query invokes is the JSON array of arguments
+        let request = self.client.send_idempotent_request(&self.module, &path_suffix, &self.args, self.hedge);
+        request_id::scope(self.request_id.clone(), request).await
+    }
+
+    /// Pages through a cursor-style query result by re-invoking the query.
+    ///
+    /// After each invocation, `extract` splits the result into its items and the next
+    /// token (None when exhausted). The token is handed to `inject` together with the
+    /// arguments for the next invocation. The stream yields every item of every page
+    /// in order. An error ends the stream after it is yielded.
+    pub fn paginate<E, I>(self, extract: E, inject: I) -> impl Stream<Item = Result<Value, ClientError>> + 'a
+    where
+        E: Fn(&Value) -> (Vec<Value>, Option<Value>) + 'a,
+        I: Fn(&mut Vec<Value>, Value) + 'a,
+    {
+        let state = Some((self, extract, inject, 0usize));
+        stream::unfold(state, |state| async move {
+            let (mut builder, extract, inject, pages) = state?;
+
+            // Guard: Safety cap reached with pages remaining
+            if pages >= builder.max_pages {
+                warn!("Pagination of query '{}' in module '{}' stopped after {} pages", builder.query, builder.module, pages);
+                return Some((Err(ClientError::PaginationLimit(pages)), None));
+            }
+
+            let page: Value = match builder.invoke_ref().await {
+                Ok(page) => page,
+                Err(e) => return Some((Err(e), None)),
+            };
+            let (items, next) = extract(&page);
+
+            // Guard: Last page
+            let Some(token) = next else {
+                return Some((Ok(items), None));
+            };
+            inject(&mut builder.args, token);
+            Some((Ok(items), Some((builder, extract, inject, pages + 1))))
+        })
+        .flat_map(|page| {
+            let items: Vec<Result<Value, ClientError>> = match page {
+                Ok(items) => items.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            stream::iter(items)
+        })
+    }
+
+    /// Like `paginate`, deserializing each item into `R`.
+    pub fn paginate_as<R, E, I>(self, extract: E, inject: I) -> impl Stream<Item = Result<R, ClientError>> + 'a
+    where
+        R: DeserializeOwned + 'a,
+        E: Fn(&Value) -> (Vec<Value>, Option<Value>) + 'a,
+        I: Fn(&mut Vec<Value>, Value) + 'a,
+    {
+        self.paginate(extract, inject)
+            .map(|item| item.and_then(|value| serde_json::from_value(value).map_err(ClientError::from)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_rama_keyword, decode_rama_symbol, rama_keyword, rama_keyword_ns, rama_symbol, Keyword, ToRamaValue};
+    use crate::testing::{FakeCluster, Reply, Scripted};
+    use crate::{Client, ClientError, RamaValue};
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn profiles() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({
+            "alice": {"age": 30, "tags": ["a", "b"]},
+            "bob": null,
+            "carol": 7,
+        }));
+        cluster
+    }
+
+    #[test]
+    fn longs_decode_exactly_in_both_forms() {
+        for n in [i64::MAX, i64::MIN, 0, -1] {
+            assert_eq!(super::decode_rama_long(&json!(n)), Some(n));
+            assert_eq!(super::decode_rama_long(&super::rama_long(n)), Some(n));
+        }
+        assert_eq!(super::decode_rama_long(&json!(u64::MAX)), None);
+        assert_eq!(super::decode_rama_long(&json!(1.5)), None);
+        assert_eq!(super::decode_rama_long(&json!("9223372036854775807")), None);
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Counter {
+        #[serde(deserialize_with = "super::deserialize_rama_long")]
+        n: i64,
+    }
+
+    #[tokio::test]
+    async fn extreme_longs_survive_select() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        // Raw JSON numbers beyond 2^53, and the same values as Rama Longs
+        let body = format!(r##"[{max}, {min}, {{"n": {max}}}, {{"n": "#__L{min}"}}]"##, max = i64::MAX, min = i64::MIN);
+        script.script("supervisor-1:1984", [Reply::Status(reqwest::StatusCode::OK, vec![], body)]);
+        let client = script.client_builder().build().unwrap();
+        let values: Vec<Value> = client.pstate_query("counters", "$$counters").all().select().await.unwrap();
+        assert_eq!(values[..2], [json!(i64::MAX), json!(i64::MIN)]);
+
+        let (numbers, records): (Vec<Value>, Vec<Value>) = values.into_iter().partition(Value::is_number);
+        let numbers: Vec<i64> = numbers.iter().map(|n| super::decode_rama_long(n).unwrap()).collect();
+        assert_eq!(numbers, [i64::MAX, i64::MIN]);
+        let records: Vec<i64> = records.into_iter().map(|r| serde_json::from_value::<Counter>(r).unwrap().n).collect();
+        assert_eq!(records, [i64::MAX, i64::MIN]);
+    }
+
+    #[tokio::test]
+    async fn extreme_longs_survive_append() {
+        let cluster = FakeCluster::new();
+        cluster.depot("counters", "*edits").ack_return("counters", "*edits", "max", i64::MAX).ack_return("counters", "*edits", "min", super::rama_long(i64::MIN));
+        let client = cluster.client();
+
+        let record = json!({"max": i64::MAX, "min": i64::MIN, "long": super::rama_long(i64::MAX)});
+        let ack: crate::AckReturn = client.depot_append("counters", "*edits", &record).append().await.unwrap();
+        assert_eq!(cluster.appended("counters", "*edits"), [record]);
+        let body = cluster.requests().last().unwrap().body.to_string();
+        assert!(body.contains("9223372036854775807") && body.contains("-9223372036854775808"), "{}", body);
+        assert_eq!(ack.get_as::<i64>("max").unwrap(), Some(i64::MAX));
+        assert_eq!(ack.get_as::<i64>("min").unwrap(), Some(i64::MIN));
+    }
+
+    #[tokio::test]
+    async fn path_json_shows_the_path_without_consuming_the_builder() {
+        let cluster = profiles();
+        let client = cluster.client();
+        let query = client.pstate_query("profiles", "$$profiles").key("alice");
+        assert_eq!(query.path_json(), json!(["alice"]));
+
+        // Still chainable afterwards
+        let query = query.nav(json!({"b": 1, "a": 2})).all();
+        assert_eq!(query.path_json(), json!(["alice", {"b": 1, "a": 2}, ["all"]]));
+        assert_eq!(query.to_string(), "[\n  \"alice\",\n  {\n    \"a\": 2,\n    \"b\": 1\n  },\n  [\n    \"all\"\n  ]\n]");
+
+        let query = client.pstate_query("profiles", "$$profiles").key("alice").key("tags");
+        let sent = query.path_json();
+        query.select::<Value>().await.unwrap();
+        assert_eq!(cluster.requests().last().unwrap().body, sent);
+    }
+
+    #[tokio::test]
+    async fn body_json_shows_the_append_body_as_sent() {
+        let cluster = FakeCluster::new();
+        cluster.depot("profiles", "*edits");
+        let client = cluster.client();
+        let append = client.depot_append("profiles", "*edits", json!({"id": "alice"})).idempotency_key("k1");
+        let body = append.body_json().unwrap();
+        assert_eq!(body["data"]["id"], "alice");
+        assert_ne!(body["data"], json!({"id": "alice"}), "the idempotency key is part of the body");
+        assert_eq!(serde_json::from_str::<Value>(&append.to_string()).unwrap(), body);
+
+        append.append::<Value>().await.unwrap();
+        assert_eq!(cluster.requests().last().unwrap().body, body);
+    }
+
+    #[test]
+    fn names_get_their_prefix_once() {
+        assert_eq!(super::pstate_name("profiles"), "$$profiles");
+        assert_eq!(super::pstate_name("$$profiles"), "$$profiles");
+        // Only a full `$$` counts as the prefix
+        assert_eq!(super::pstate_name("$profiles"), "$$$profiles");
+        assert_eq!(super::depot_name("registerDepot"), "*registerDepot");
+        assert_eq!(super::depot_name("*registerDepot"), "*registerDepot");
+        assert_eq!(super::depot_name("**d"), "**d");
+    }
+
+    #[tokio::test]
+    async fn unprefixed_names_reach_the_same_objects() {
+        let cluster = profiles();
+        cluster.depot("profiles", "*edits");
+        let client = cluster.client();
+
+        for pstate in ["profiles", "$$profiles"] {
+            let age: u32 = client.pstate_query("profiles", pstate).key("alice").key("age").select_one().await.unwrap();
+            assert_eq!(age, 30);
+        }
+        for depot in ["edits", "*edits"] {
+            client.depot_append("profiles", depot, json!(1)).append::<Value>().await.unwrap();
+        }
+        let paths: Vec<String> = cluster.requests().iter().map(|r| r.url.path().to_string()).collect();
+        // Including the conductor's first answer, which redirects to the supervisor
+        assert_eq!(paths.iter().filter(|p| *p == "/rest/profiles/pstate/$$profiles/selectOne").count(), 3, "{:?}", paths);
+        assert_eq!(paths.iter().filter(|p| *p == "/rest/profiles/depot/*edits/append").count(), 2, "{:?}", paths);
+    }
+
+    // The table in the `ToRamaValue` docs.
+    #[test]
+    fn native_values_encode_per_the_mapping_table() {
+        let table: [(&dyn ToRamaValue, Value); 14] = [
+            (&-3i8, json!("#__B-3")),
+            (&300i16, json!("#__S300")),
+            (&7i32, json!(7)),
+            (&7i64, json!("#__L7")),
+            (&i64::MIN, json!("#__L-9223372036854775808")),
+            (&0.5f32, json!("#__F0.5")),
+            (&0.5f64, json!(0.5)),
+            (&'x', json!("#__Cx")),
+            (&"alice", json!("alice")),
+            (&"alice".to_string(), json!("alice")),
+            (&true, json!(true)),
+            (&Keyword("user/id".into()), json!("#__Kuser/id")),
+            (&json!({"a": [1]}), json!({"a": [1]})),
+            (&json!("#__L7"), json!("#__L7")),
+        ];
+        for (value, expected) in table {
+            assert_eq!(value.to_rama_value(), expected);
+        }
+    }
+
+    #[test]
+    fn navigators_use_the_native_encoding() {
+        let client = Client::new("http://conductor:1984").unwrap();
+        let query = client
+            .pstate_query("m", "$$p")
+            .key(5i64)
+            .nav(5i32)
+            .must([1i64, 2i64])
+            .sorted_map_range(1i64, 'z')
+            .sorted_map_range_from(Keyword("k".into()), 10);
+        assert_eq!(
+            query.path_json(),
+            json!(["#__L5", 5, ["must", "#__L1", "#__L2"], ["sortedMapRange", "#__L1", "#__Cz"], ["sortedMapRangeFrom", "#__Kk", 10]])
+        );
+    }
+
+    #[test]
+    fn must_key_differs_from_key_only_in_the_navigator() {
+        let client = Client::new("http://conductor:1984").unwrap();
+        let path = |query: super::PStateQueryBuilder<'_>| query.path_json();
+        for (key, encoded) in [(json!("a"), json!("a")), (json!(7), json!(7)), (super::rama_long(7), json!("#__L7"))] {
+            let by_key = path(client.pstate_query("m", "$$p").key(&key));
+            let by_must = path(client.pstate_query("m", "$$p").must_key(&key));
+            assert_eq!((by_key, by_must), (json!([encoded.clone()]), json!([["must", encoded]])));
+        }
+        assert_eq!(path(client.pstate_query("m", "$$p").key(7i64)), json!(["#__L7"]));
+        assert_eq!(path(client.pstate_query("m", "$$p").must_key(7i64)), json!([["must", "#__L7"]]));
+        assert_eq!(path(client.pstate_query("m", "$$p").must(["a", "b"])), json!([["must", "a", "b"]]));
+    }
+
+    #[tokio::test]
+    async fn must_selects_nothing_where_key_selects_null() {
+        let client = profiles().client();
+        let by_key: Vec<Value> = client.pstate_query("profiles", "$$profiles").key("nobody").key("age").select().await.unwrap();
+        assert_eq!(by_key, [Value::Null]);
+        let by_must: Vec<Value> = client.pstate_query("profiles", "$$profiles").must_key("nobody").key("age").select().await.unwrap();
+        assert_eq!(by_must, Vec::<Value>::new());
+        let present: Vec<u32> = client.pstate_query("profiles", "$$profiles").must_key("alice").key("age").select().await.unwrap();
+        assert_eq!(present, [30]);
+    }
+
+    #[tokio::test]
+    async fn select_one_opt_maps_null_to_none() {
+        let client = profiles().client();
+        let missing: Option<Value> = client.pstate_query("profiles", "$$profiles").key("nobody").select_one_opt().await.unwrap();
+        assert_eq!(missing, None);
+        let null: Option<u32> = client.pstate_query("profiles", "$$profiles").key("bob").select_one_opt().await.unwrap();
+        assert_eq!(null, None);
+        let must: Option<u32> = client.pstate_query("profiles", "$$profiles").must_key("nobody").select_one_opt().await.unwrap();
+        assert_eq!(must, None);
+    }
+
+    #[tokio::test]
+    async fn select_one_opt_decodes_scalars_and_objects() {
+        let client = profiles().client();
+        let scalar: Option<u32> = client.pstate_query("profiles", "$$profiles").key("carol").select_one_opt().await.unwrap();
+        assert_eq!(scalar, Some(7));
+        let object: Option<Value> = client.pstate_query("profiles", "$$profiles").key("alice").select_one_opt().await.unwrap();
+        assert_eq!(object, Some(json!({"age": 30, "tags": ["a", "b"]})));
+    }
+
+    #[tokio::test]
+    async fn select_one_opt_rejects_multiple_results() {
+        let client = profiles().client();
+        let result = client.pstate_query("profiles", "$$profiles").key("alice").key("tags").all().select_one_opt::<String>().await;
+        assert!(matches!(result, Err(ClientError::MultipleResults(2))), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn select_one_reports_null_as_not_found() {
+        let client = profiles().client();
+        let result = client.pstate_query("profiles", "$$profiles").key("bob").select_one::<Value>().await;
+        assert!(matches!(result, Err(ClientError::NotFound { .. })), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn raw_selects_pass_the_body_through_verbatim() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        // Whitespace, key order, duplicate keys and numbers beyond f64 all survive
+        let body = r##"[ {"z": 1,  "a": 12345678901234567890123, "a": 1.50} , "#__Kname"]"##;
+        let verbatim = |body: &str| Reply::Status(reqwest::StatusCode::OK, vec![], body.to_string());
+        script.script("supervisor-1:1984", [verbatim(body), verbatim(r#"{"b" : [1, 2]}"#)]);
+        let client = script.client_builder().build().unwrap();
+
+        let raw = client.pstate_query("profiles", "$$profiles").key("alice").select_raw().await.unwrap();
+        assert_eq!(raw.get(), body);
+        let raw = client.pstate_query("profiles", "$$profiles").key("alice").select_one_raw().await.unwrap();
+        assert_eq!(raw.get(), r#"{"b" : [1, 2]}"#);
+    }
+
+    #[tokio::test]
+    async fn select_one_raw_reports_null_as_not_found() {
+        let client = profiles().client();
+        let result = client.pstate_query("profiles", "$$profiles").key("bob").select_one_raw().await;
+        assert!(matches!(result, Err(ClientError::NotFound { .. })), "{result:?}");
+        // Still a value for `select_raw`
+        let raw = client.pstate_query("profiles", "$$profiles").key("bob").select_raw().await.unwrap();
+        assert_eq!(raw.get(), "[null]");
+    }
+
+    #[tokio::test]
+    async fn prepared_queries_send_the_same_body_as_the_builder() {
+        let cluster = profiles();
+        let client = cluster.client();
+        let prepared = client.pstate_query("profiles", "$$profiles").key_param("user").key("age").prepare();
+        assert_eq!(prepared.params(), ["user"]);
+
+        let ages: Vec<u32> = prepared.clone().bind("user", "alice").unwrap().execute(&client).await.unwrap();
+        assert_eq!(ages, [30]);
+        let direct: Vec<u32> = client.pstate_query("profiles", "$$profiles").key("alice").key("age").select().await.unwrap();
+        assert_eq!(direct, ages);
+
+        let bodies: Vec<Value> = cluster.requests().into_iter().filter(|r| r.path_suffix.ends_with("/select")).map(|r| r.body).collect();
+        assert_eq!(bodies[0], bodies[bodies.len() - 1]);
+        assert_eq!(bodies[0], json!(["alice", "age"]));
+    }
+
+    #[tokio::test]
+    async fn binding_an_unknown_parameter_is_an_error() {
+        let client = profiles().client();
+        let prepared = client.pstate_query("profiles", "$$profiles").key_param("user").prepare();
+        let err = prepared.bind("usr", "alice").unwrap_err();
+        assert!(matches!(&err, ClientError::UnknownQueryParameter { name, params, .. } if name == "usr" && params == &["user"]), "{err:?}");
+        assert_eq!(err.code(), crate::codes::QUERY_UNKNOWN_PARAMETER);
+    }
+
+    #[tokio::test]
+    async fn unbound_parameters_fail_before_sending() {
+        let cluster = profiles();
+        let client = cluster.client();
+        let prepared = client.pstate_query("profiles", "$$profiles").key_param("user").key_param("field").prepare();
+        let err = prepared.bind("user", "alice").unwrap().execute::<Vec<Value>>(&client).await.unwrap_err();
+        assert!(matches!(&err, ClientError::UnboundQueryParameter(name) if name == "field"), "{err:?}");
+
+        let err = client.pstate_query("profiles", "$$profiles").key_param("user").select::<Value>().await.unwrap_err();
+        assert!(matches!(&err, ClientError::UnboundQueryParameter(name) if name == "user"), "{err:?}");
+        assert!(cluster.requests().is_empty());
+    }
+
+    fn wide() -> FakeCluster {
+        let cluster = FakeCluster::new().with_supervisors(&["fake-supervisor-1:1984", "fake-supervisor-2:1984"]);
+        cluster.pstate("profiles", "$$wide", json!({
+            "alice": {"name": "Alice", "bio": "long text", "profile": {"avatar_url": "a.png", "theme": "dark"}},
+            "bob": {"name": "Bob", "bio": "more text", "profile": {"theme": "light"}},
+        }));
+        cluster
+    }
+
+    fn last_body(cluster: &FakeCluster) -> Value {
+        cluster.requests().last().unwrap().body.clone()
+    }
+
+    #[tokio::test]
+    async fn client_side_projection_trims_results() {
+        let cluster = wide();
+        let client = cluster.client();
+        let query = client.pstate_query("profiles", "$$wide").map_vals().project(&["name", "/profile/avatar_url"]);
+        let mut results: Vec<Value> = query.select().await.unwrap();
+        results.sort_by_key(|v| v["name"].to_string());
+        assert_eq!(results, [json!({"name": "Alice", "profile": {"avatar_url": "a.png"}}), json!({"name": "Bob"})]);
+        assert_eq!(last_body(&cluster), json!([["mapVals"]]));
+    }
+
+    #[tokio::test]
+    async fn server_side_projection_sends_a_multi_path() {
+        let cluster = wide();
+        let client = cluster.client();
+        let query = client.pstate_query("profiles", "$$wide").key("alice").project_on_server(&["name", "/profile/avatar_url"]);
+        let one: Value = query.clone().select_one().await.unwrap();
+        assert_eq!(one, json!({"name": "Alice", "profile": {"avatar_url": "a.png"}}));
+        let request = cluster.requests().pop().unwrap();
+        assert_eq!(request.path_suffix, "pstate/$$wide/select");
+        assert_eq!(serde_json::to_string(&request.body).unwrap(), r#"["alice",["multiPath",["name"],["profile","avatar_url"]]]"#);
+
+        let all: Vec<Value> = client.pstate_query("profiles", "$$wide").map_vals().project_on_server(&["name"]).select().await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let missing = client.pstate_query("profiles", "$$wide").key("carol").project_on_server(&["name"]).select_one::<Value>().await;
+        assert!(matches!(missing, Err(ClientError::NotFound { .. })), "{missing:?}");
+        let missing: Option<Value> = client.pstate_query("profiles", "$$wide").key("carol").project_on_server(&["name"]).select_one_opt().await.unwrap();
+        assert_eq!(missing, None);
+    }
+
+    #[tokio::test]
+    async fn prepared_queries_keep_projection_and_request_id() {
+        let cluster = wide();
+        let client = cluster.client();
+        let prepared = client
+            .pstate_query("profiles", "$$wide")
+            .key_param("user")
+            .project_on_server(&["name"])
+            .request_id("req-291")
+            .prepare_one();
+        let alice: Value = prepared.clone().bind("user", "alice").unwrap().execute(&client).await.unwrap();
+        assert_eq!(alice, json!({"name": "Alice"}));
+        let request = cluster.requests().pop().unwrap();
+        assert_eq!(request.body, json!(["alice", ["multiPath", ["name"]]]));
+        assert_eq!(request.headers["x-request-id"], "req-291");
+
+        let trimmed: Vec<Value> = client.pstate_query("profiles", "$$wide").map_vals().project(&["bio"]).prepare().execute(&client).await.unwrap();
+        assert_eq!(trimmed.len(), 2);
+        assert!(trimmed.iter().all(|v| v.as_object().unwrap().keys().eq(["bio"])));
+    }
+
+    #[tokio::test]
+    async fn prepared_queries_keep_partition_and_hedge() {
+        let cluster = wide();
+        let client = cluster.client();
+        let cached = crate::CachedSupervisors {
+            supervisors: vec!["fake-supervisor-1:1984".to_string(), "fake-supervisor-2:1984".to_string()],
+            partitions: Some(vec![vec!["fake-supervisor-1:1984".to_string()], vec!["fake-supervisor-2:1984".to_string()]]),
+            learned_at_ms: crate::rt::SystemTime::now().duration_since(crate::rt::UNIX_EPOCH).unwrap().as_millis() as u64,
+        };
+        client.import_supervisor_cache(crate::SupervisorCacheSnapshot { modules: [("profiles".to_string(), cached)].into() });
+
+        let prepared = client.pstate_query("profiles", "$$wide").key("bob").key("name").partition_index(1).prepare();
+        for _ in 0..5 {
+            assert_eq!(prepared.execute::<Vec<String>>(&client).await.unwrap(), ["Bob"]);
+        }
+        assert!(cluster.requests().iter().all(|r| r.url.host_str() == Some("fake-supervisor-2")));
+
+        cluster.latency("*", std::time::Duration::from_millis(100));
+        let hedged = client.pstate_query("profiles", "$$wide").key("bob").key("name").hedge(std::time::Duration::from_millis(10)).prepare();
+        let before = cluster.requests().len();
+        assert_eq!(hedged.execute::<Vec<String>>(&client).await.unwrap(), ["Bob"]);
+        assert_eq!(cluster.requests().len() - before, 2);
+    }
+
+    #[test]
+    fn keywords_and_symbols_encode_with_their_prefixes() {
+        assert_eq!(rama_keyword("id"), json!("#__Kid"));
+        assert_eq!(rama_keyword("user/id"), json!("#__Kuser/id"));
+        assert_eq!(rama_keyword_ns("user", "id"), json!("#__Kuser/id"));
+        assert_eq!(rama_keyword("/"), json!("#__K/"));
+        assert_eq!(rama_symbol("inc"), json!("#__Yinc"));
+        assert_eq!(rama_symbol("clojure.core/inc"), json!("#__Yclojure.core/inc"));
+        assert_eq!(decode_rama_symbol(&rama_symbol("clojure.core/inc")), Some("clojure.core/inc"));
+        assert_eq!(decode_rama_symbol(&json!("#__Kid")), None);
+    }
+
+    #[test]
+    fn namespaced_keywords_round_trip() {
+        for encoded in [rama_keyword("user/id"), rama_keyword_ns("user", "id"), Keyword("user/id".into()).to_rama_value()] {
+            assert_eq!(decode_rama_keyword(&encoded), Some("user/id"));
+            assert_eq!(RamaValue::decode(&encoded), RamaValue::Keyword(Keyword("user/id".into())));
+            assert_eq!(RamaValue::decode(&encoded).into_json(), json!("user/id"));
+        }
+        let map = RamaValue::decode(&json!({"#__Kuser/id": "#__Kuser/name"}));
+        assert_eq!(map.into_json(), json!({"user/id": "user/name"}));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Rama keyword ':id': leave off the leading ':'")]
+    fn keyword_rejects_leading_colon() {
+        rama_keyword(":id");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Rama keyword 'user/': empty namespace or name")]
+    fn keyword_rejects_empty_name() {
+        rama_keyword("user/");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Rama keyword 'user/a/b': the name can't contain '/'")]
+    fn keyword_ns_rejects_slash_in_name() {
+        rama_keyword_ns("user", "a/b");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Rama symbol 'my inc': symbols can't contain whitespace or commas")]
+    fn symbol_rejects_whitespace() {
+        rama_symbol("my inc");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid Rama symbol '': empty namespace or name")]
+    fn symbol_rejects_empty() {
+        rama_symbol("");
+    }
+
+    #[test]
+    fn doubles_encode_as_plain_numbers_and_round_trip() {
+        assert_eq!(super::rama_double(1.0).to_string(), "1.0");
+        assert_eq!(super::rama_double(0.1).to_string(), "0.1");
+        assert_eq!(super::rama_double(-2.5e-8).to_string(), "-2.5e-8");
+        assert_eq!(super::rama_double(f64::NAN), Value::Null);
+        for n in [1.0, 0.1, -2.5e-8, f64::MAX] {
+            assert_eq!(super::decode_rama_double(&super::rama_double(n)), Some(n));
+        }
+        assert_eq!(super::decode_rama_double(&json!(3)), Some(3.0));
+        assert_eq!(super::decode_rama_double(&json!("#__F1.5")), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn instants_encode_as_epoch_millis_longs_and_round_trip() {
+        let instant = chrono::DateTime::from_timestamp_millis(1_700_000_000_123).unwrap();
+        assert_eq!(super::rama_instant(instant), json!("#__L1700000000123"));
+        assert_eq!(super::decode_rama_instant(&super::rama_instant(instant)), Some(instant));
+        assert_eq!(super::decode_rama_instant(&json!(1_700_000_000_123i64)), Some(instant));
+        let before_epoch = chrono::DateTime::from_timestamp_millis(-1).unwrap();
+        assert_eq!(super::rama_instant(before_epoch), json!("#__L-1"));
+        assert_eq!(super::decode_rama_instant(&json!("2023-11-14T22:13:20Z")), None);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids_encode_hyphenated_and_round_trip() {
+        let uuid = uuid::Uuid::parse_str("67E55044-10B1-426F-9247-BB680E5FE0C8").unwrap();
+        assert_eq!(super::rama_uuid(uuid), json!("67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert_eq!(super::decode_rama_uuid(&super::rama_uuid(uuid)), Some(uuid));
+        assert_eq!(RamaValue::decode(&super::rama_uuid(uuid)), RamaValue::String("67e55044-10b1-426f-9247-bb680e5fe0c8".into()));
+        assert_eq!(super::decode_rama_uuid(&json!("not-a-uuid")), None);
+        assert_eq!(super::decode_rama_uuid(&json!(7)), None);
+    }
+
+    // --- Query Pagination ---
+
+    // A `listTags` query answering `{"items": [...], "next": token}` three times, the last
+    // time without a token.
+    fn three_pages() -> (Arc<Scripted>, Client) {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        script.script("supervisor-1:1984", [
+            Reply::ok(json!({"items": [1, 2], "next": "t1"})),
+            Reply::ok(json!({"items": [3], "next": "t2"})),
+            Reply::ok(json!({"items": [4, 5], "next": null})),
+        ]);
+        let client = script.client_builder().build().unwrap();
+        (script, client)
+    }
+
+    fn extract(page: &Value) -> (Vec<Value>, Option<Value>) {
+        let items = page["items"].as_array().cloned().unwrap_or_default();
+        (items, Some(page["next"].clone()).filter(|next| !next.is_null()))
+    }
+
+    // Replaces the token argument after the first, fixed one
+    fn inject(args: &mut Vec<Value>, token: Value) {
+        args.truncate(1);
+        args.push(token);
+    }
+
+    #[tokio::test]
+    async fn paginates_until_the_last_page() {
+        let (script, client) = three_pages();
+        let items: Vec<Result<Value, ClientError>> = client.query_invoke("tags", "listTags").arg("alice").paginate(extract, inject).collect().await;
+        let items: Vec<Value> = items.into_iter().map(Result::unwrap).collect();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+
+        let bodies: Vec<Value> = script.bodies().iter().map(|body| serde_json::from_slice(body).unwrap()).collect();
+        assert_eq!(bodies, [json!(["alice"]), json!(["alice"]), json!(["alice", "t1"]), json!(["alice", "t2"])]);
+    }
+
+    #[tokio::test]
+    async fn paginates_into_typed_items() {
+        let (_, client) = three_pages();
+        let items: Vec<Result<u32, ClientError>> = client.query_invoke("tags", "listTags").arg("alice").paginate_as(extract, inject).collect().await;
+        assert_eq!(items.into_iter().map(Result::unwrap).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn pagination_stops_at_max_pages() {
+        let (script, client) = three_pages();
+        let items: Vec<Result<Value, ClientError>> = client.query_invoke("tags", "listTags").arg("alice").max_pages(2).paginate(extract, inject).collect().await;
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[..3].iter().map(|item| item.as_ref().unwrap().clone()).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(matches!(&items[3], Err(ClientError::PaginationLimit(2))), "{:?}", items[3]);
+        assert_eq!(script.hosts().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn pagination_ends_after_an_error() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        script.script("supervisor-1:1984", [
+            Reply::ok(json!({"items": [1], "next": "t1"})),
+            Reply::Status(reqwest::StatusCode::BAD_REQUEST, Vec::new(), String::new()),
+        ]);
+        let client = script.client_builder().build().unwrap();
+
+        let items: Vec<Result<Value, ClientError>> = client.query_invoke("tags", "listTags").paginate(extract, inject).collect().await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(*items[0].as_ref().unwrap(), 1);
+        assert!(matches!(items[1].as_ref().unwrap_err().kind(), ClientError::UnexpectedStatus(status, _) if status.as_u16() == 400));
+        assert_eq!(script.hosts().len(), 3);
+    }
+
+    // --- Depot Transforms ---
+
+    // Appends `step` to the record's `steps`.
+    fn step(step: &'static str) -> super::DepotTransform {
+        Arc::new(move |mut record: Value| {
+            let Some(steps) = record["steps"].as_array_mut() else {
+                return Err(ClientError::Json(serde::de::Error::custom("record has no steps")));
+            };
+            steps.push(json!(step));
+            Ok(record)
+        })
+    }
+
+    fn edits() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.depot("profiles", "*edits");
+        cluster
+    }
+
+    #[tokio::test]
+    async fn transforms_run_in_order() {
+        let cluster = edits();
+        let client = cluster.client();
+        let depot = client.depot("profiles", "*edits").with_transform(step("v2")).with_transform(step("v3"));
+
+        depot.append(json!({"id": "alice", "steps": ["v1"]})).append::<Value>().await.unwrap();
+        assert_eq!(cluster.appended("profiles", "*edits"), [json!({"id": "alice", "steps": ["v1", "v2", "v3"]})]);
+    }
+
+    #[tokio::test]
+    async fn transforms_apply_to_bulk_and_appender_appends() {
+        let cluster = edits();
+        let client = cluster.client();
+        let depot = client.depot("profiles", "*edits").with_transform(step("v2")).with_transform(step("v3"));
+
+        let report = depot.append_bulk(vec![json!({"steps": []}), json!({"steps": ["v1"]})], super::AckLevel::AppendAck, 1).await.unwrap();
+        assert!(report.all_succeeded());
+        let appender = depot.appender(crate::AppenderOptions::default());
+        appender.send(json!({"steps": []})).await.unwrap();
+        appender.close().await.unwrap();
+
+        assert_eq!(
+            cluster.appended("profiles", "*edits"),
+            [json!({"steps": ["v2", "v3"]}), json!({"steps": ["v1", "v2", "v3"]}), json!({"steps": ["v2", "v3"]})]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_failing_transform_fails_the_append_with_its_index() {
+        let cluster = edits();
+        let client = cluster.client();
+        let depot = client.depot("profiles", "*edits").with_transform(step("v2")).with_transform(step("v3"));
+
+        // No `steps` array: the first transform fails
+        let err = depot.append(json!({"id": "alice"})).append::<Value>().await.unwrap_err();
+        let ClientError::TransformFailed { depot, index, source } = err.kind() else {
+            panic!("expected TransformFailed, got {:?}", err);
+        };
+        assert_eq!((depot.as_str(), *index), ("*edits", 0));
+        assert_eq!(source.to_string(), "JSON serialization/deserialization failed: record has no steps");
+        assert!(cluster.appended("profiles", "*edits").is_empty());
     }
 }
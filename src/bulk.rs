@@ -0,0 +1,76 @@
+use crate::builder::{AckLevel, DepotHandle};
+use crate::logging::{info, warn};
+use crate::{Client, ClientError};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Outcome of a bulk depot append, by index into the submitted items.
+#[derive(Debug, Default)]
+pub struct BulkAppendReport {
+    /// `(index, ack)` for each item appended, in index order.
+    pub succeeded: Vec<(usize, Value)>,
+    /// `(index, error)` for each item that failed, in index order.
+    pub failed: Vec<(usize, ClientError)>,
+}
+
+impl BulkAppendReport {
+    /// True if every item was appended.
+    pub fn all_succeeded(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Indexes of the failed items, e.g. to retry just those.
+    pub fn failed_indexes(&self) -> Vec<usize> {
+        self.failed.iter().map(|(index, _)| *index).collect()
+    }
+}
+
+impl Client {
+    /// Appends every item to a depot with at most `concurrency` requests in flight.
+    ///
+    /// Items are appended independently: a failure is recorded in the report and the
+    /// rest carry on, so callers can retry only what failed.
+    pub async fn depot_append_bulk<T: Serialize>(
+        &self,
+        module: &str,
+        depot: &str,
+        items: Vec<T>,
+        ack_level: AckLevel,
+        concurrency: usize,
+    ) -> Result<BulkAppendReport, ClientError> {
+        self.depot(module, depot).append_bulk(items, ack_level, concurrency).await
+    }
+}
+
+impl DepotHandle<'_> {
+    /// Like `Client::depot_append_bulk`, applying this handle's transforms to each item.
+    pub async fn append_bulk<T: Serialize>(
+        &self,
+        items: Vec<T>,
+        ack_level: AckLevel,
+        concurrency: usize,
+    ) -> Result<BulkAppendReport, ClientError> {
+        let total = items.len();
+        let appends = items.into_iter().enumerate().map(|(index, item)| async move {
+            (index, self.append(item).ack_level(ack_level).append::<Value>().await)
+        });
+        let mut results: Vec<(usize, Result<Value, ClientError>)> =
+            stream::iter(appends).buffer_unordered(concurrency.max(1)).collect().await;
+        results.sort_by_key(|(index, _)| *index);
+
+        let mut report = BulkAppendReport::default();
+        for (index, result) in results {
+            match result {
+                Ok(ack) => report.succeeded.push((index, ack)),
+                Err(e) => report.failed.push((index, e)),
+            }
+        }
+        if report.all_succeeded() {
+            info!("Bulk append of {} items to depot '{}' succeeded", total, self.depot_name());
+        } else {
+            warn!("Bulk append to depot '{}': {} of {} items failed", self.depot_name(), report.failed.len(), total);
+        }
+        Ok(report)
+    }
+}
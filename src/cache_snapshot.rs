@@ -0,0 +1,174 @@
+use crate::logging::debug;
+use crate::rt::{Instant, SystemTime, UNIX_EPOCH};
+use crate::supervisor::{CacheEntry, SupervisorLocations};
+use crate::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// A copy of a client's supervisor cache that can be saved and loaded into another client,
+/// e.g. by a short-lived CLI that would otherwise pay the conductor redirect on every run.
+///
+/// Create one with `Client::export_supervisor_cache` and restore it with
+/// `Client::import_supervisor_cache`. The format is plain serde, so any serde format works.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupervisorCacheSnapshot {
+    /// Cached supervisors, by module name.
+    pub modules: BTreeMap<String, CachedSupervisors>,
+}
+
+/// The supervisors cached for one module in a `SupervisorCacheSnapshot`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedSupervisors {
+    /// Supervisor locations (`host:port`), as announced in `Supervisor-Locations`.
+    pub supervisors: Vec<String>,
+    /// Supervisor locations per partition (index = partition), if the conductor announced
+    /// them that way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<Vec<Vec<String>>>,
+    /// When the locations were learned, in milliseconds since the Unix epoch.
+    pub learned_at_ms: u64,
+}
+
+// Wall-clock time now, in milliseconds since the Unix epoch.
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+impl Client {
+    /// Returns a copy of the supervisor cache, including entries older than the TTL.
+    pub fn export_supervisor_cache(&self) -> SupervisorCacheSnapshot {
+        let now = now_ms();
+        let cache = self.supervisor_cache.lock().unwrap();
+        let modules = cache
+            .iter()
+            .map(|(module, entry)| {
+                let age = entry.cached_at.elapsed().as_millis() as u64;
+                let cached = CachedSupervisors {
+                    supervisors: entry.supervisors.clone(),
+                    partitions: entry.locations.partitions(),
+                    learned_at_ms: now.saturating_sub(age),
+                };
+                (module.clone(), cached)
+            })
+            .collect();
+        SupervisorCacheSnapshot { modules }
+    }
+
+    /// Loads the entries of `snapshot` into the supervisor cache and returns how many were
+    /// loaded.
+    ///
+    /// Entries keep their age, so with `supervisor_cache_ttl` configured, ones that have
+    /// expired are skipped. An entry this client learned more recently than the snapshot's
+    /// is kept.
+    pub fn import_supervisor_cache(&self, snapshot: SupervisorCacheSnapshot) -> usize {
+        let now = now_ms();
+        let mut imported = 0;
+        for (module, cached) in snapshot.modules {
+            // Timestamps in the future (clock changes) count as just learned.
+            let age = Duration::from_millis(now.saturating_sub(cached.learned_at_ms));
+
+            // Guard: Expired
+            if self.supervisor_cache_ttl.is_some_and(|ttl| age > ttl) {
+                debug!("Not importing supervisors for module '{}': learned {:?} ago", module, age);
+                continue;
+            }
+            let cached_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+
+            // Guard: Newer entry already cached
+            let newer = self.supervisor_cache.lock().unwrap().get(&module).is_some_and(|entry| entry.cached_at > cached_at);
+            if newer {
+                continue;
+            }
+            let locations = match cached.partitions {
+                Some(partitions) => SupervisorLocations::Partitioned(partitions),
+                None => SupervisorLocations::List(cached.supervisors),
+            };
+            self.store_cache_entry(&module, CacheEntry::learned_at(locations, cached_at));
+            imported += 1;
+        }
+        debug!("Imported {} supervisor cache entries", imported);
+        imported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{now_ms, CachedSupervisors, SupervisorCacheSnapshot};
+    use crate::testing::{Reply, Scripted};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    const SUPERVISOR_1: &str = "supervisor-1:1984";
+    const SUPERVISOR_2: &str = "supervisor-2:1984";
+
+    fn snapshot(learned_at_ms: u64) -> SupervisorCacheSnapshot {
+        let list = CachedSupervisors { supervisors: vec![SUPERVISOR_1.into()], partitions: None, learned_at_ms };
+        let partitioned = CachedSupervisors {
+            supervisors: vec![SUPERVISOR_1.into(), SUPERVISOR_2.into()],
+            partitions: Some(vec![vec![SUPERVISOR_1.into()], vec![SUPERVISOR_2.into()]]),
+            learned_at_ms,
+        };
+        SupervisorCacheSnapshot { modules: [("profiles".into(), list), ("accounts".into(), partitioned)].into() }
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_serde() {
+        let snapshot = snapshot(1_700_000_000_000);
+        let text = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(text, json!({"modules": {
+            "accounts": {
+                "supervisors": [SUPERVISOR_1, SUPERVISOR_2],
+                "partitions": [[SUPERVISOR_1], [SUPERVISOR_2]],
+                "learned_at_ms": 1_700_000_000_000u64,
+            },
+            "profiles": {"supervisors": [SUPERVISOR_1], "learned_at_ms": 1_700_000_000_000u64},
+        }}));
+        assert_eq!(serde_json::from_value::<SupervisorCacheSnapshot>(text).unwrap(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn export_then_import_skips_the_conductor() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR_1)]);
+        script.script(SUPERVISOR_1, [Reply::ok([30])]);
+        let first = script.client_builder().build().unwrap();
+        first.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        let saved = serde_json::to_string(&first.export_supervisor_cache()).unwrap();
+
+        // A new process: a fresh client with the saved cache
+        let second = script.client_builder().build().unwrap();
+        assert_eq!(second.import_supervisor_cache(serde_json::from_str(&saved).unwrap()), 1);
+        second.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        assert_eq!(script.hosts(), [Scripted::CONDUCTOR, SUPERVISOR_1, SUPERVISOR_1]);
+        // Learned at the same time, give or take the rounding of each export to whole ms
+        let (restored, original) = (&second.export_supervisor_cache().modules["profiles"], &first.export_supervisor_cache().modules["profiles"]);
+        assert_eq!(restored.supervisors, original.supervisors);
+        assert!(restored.learned_at_ms.abs_diff(original.learned_at_ms) <= 2, "{:?} {:?}", restored, original);
+    }
+
+    #[test]
+    fn expired_entries_are_not_imported() {
+        let script = Scripted::new();
+        let client = script.client_builder().supervisor_cache_ttl(Duration::from_secs(60)).build().unwrap();
+        assert_eq!(client.import_supervisor_cache(snapshot(now_ms() - 120_000)), 0);
+        assert!(client.export_supervisor_cache().modules.is_empty());
+        assert_eq!(client.import_supervisor_cache(snapshot(now_ms() - 30_000)), 2);
+
+        // Without a TTL, any age is fine; the age is kept
+        let client = script.client_builder().build().unwrap();
+        assert_eq!(client.import_supervisor_cache(snapshot(now_ms() - 3_600_000)), 2);
+        let learned_at_ms = client.export_supervisor_cache().modules["profiles"].learned_at_ms;
+        assert!(now_ms() - learned_at_ms >= 3_600_000);
+    }
+
+    #[test]
+    fn newer_entries_win_over_imported_ones() {
+        let client = Scripted::new().client_builder().build().unwrap();
+        client.import_supervisor_cache(snapshot(now_ms()));
+        let mut older = snapshot(now_ms() - 60_000);
+        older.modules.get_mut("profiles").unwrap().supervisors = vec![SUPERVISOR_2.into()];
+        assert_eq!(client.import_supervisor_cache(older), 0);
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, [SUPERVISOR_1]);
+    }
+}
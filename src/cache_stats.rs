@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Supervisor cache counters for one module, from `Client::cache_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests sent straight to a cached supervisor.
+    pub hits: u64,
+    /// Requests sent to the conductor because nothing usable was cached.
+    pub misses: u64,
+    /// Entries found out of date: too old for the cache TTL (the request goes to the
+    /// conductor), naming a supervisor that redirected elsewhere, or naming one that
+    /// answered 404 (see `ClientBuilder::retry_not_found_via_conductor`). Either way the
+    /// redirect that follows refreshes the entry.
+    pub stale_refreshes: u64,
+}
+
+/// One supervisor cache event, as counted in `CacheStats` and reported to
+/// `ClientMetrics::on_cache_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheEvent {
+    Hit,
+    Miss,
+    StaleRefresh,
+}
+
+#[derive(Debug, Default)]
+struct AtomicCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stale_refreshes: AtomicU64,
+}
+
+impl AtomicCacheStats {
+    fn record(&self, event: CacheEvent) {
+        let counter = match event {
+            CacheEvent::Hit => &self.hits,
+            CacheEvent::Miss => &self.misses,
+            CacheEvent::StaleRefresh => &self.stale_refreshes,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            stale_refreshes: self.stale_refreshes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Per-module cache counters, shared by all clones of a client. Like the latency
+// histograms, the map is only write-locked the first time a module is seen.
+#[derive(Debug, Default)]
+pub(crate) struct CacheStatsTracker {
+    modules: RwLock<HashMap<String, AtomicCacheStats>>,
+}
+
+impl CacheStatsTracker {
+    pub(crate) fn record(&self, module: &str, event: CacheEvent) {
+        if let Some(stats) = self.modules.read().unwrap().get(module) {
+            stats.record(event);
+            return;
+        }
+        self.modules.write().unwrap().entry(module.to_string()).or_default().record(event);
+    }
+
+    pub(crate) fn snapshot(&self) -> HashMap<String, CacheStats> {
+        self.modules
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(module, stats)| (module.clone(), stats.snapshot()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeCluster;
+    use crate::{Client, ClientMetrics, RequestOutcome};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<(String, CacheEvent)>>);
+
+    impl ClientMetrics for Events {
+        fn on_request(&self, _module: &str, _outcome: &RequestOutcome) {}
+
+        fn on_cache_event(&self, module: &str, event: CacheEvent) {
+            self.0.lock().unwrap().push((module.to_string(), event));
+        }
+    }
+
+    fn stats(hits: u64, misses: u64, stale_refreshes: u64) -> CacheStats {
+        CacheStats { hits, misses, stale_refreshes }
+    }
+
+    async fn select_alice(client: &Client) {
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<u32>().await.unwrap();
+    }
+
+    #[test]
+    fn counts_each_event_per_module() {
+        let tracker = CacheStatsTracker::default();
+        for event in [CacheEvent::Miss, CacheEvent::Hit, CacheEvent::Hit, CacheEvent::StaleRefresh] {
+            tracker.record("profiles", event);
+        }
+        tracker.record("orders", CacheEvent::Miss);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["profiles"], stats(2, 1, 1));
+        assert_eq!(snapshot["orders"], stats(0, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn miss_then_hit_then_stale_refresh_after_the_ttl() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let events = Arc::new(Events::default());
+        let client = cluster.client_builder().supervisor_cache_ttl(Duration::from_millis(50)).metrics(events.clone()).build().unwrap();
+        assert!(client.cache_stats().is_empty());
+
+        select_alice(&client).await;
+        assert_eq!(client.cache_stats()["profiles"], stats(0, 1, 0));
+        // Clones share the counters
+        let clone = client.clone();
+        select_alice(&clone).await;
+        assert_eq!(client.cache_stats()["profiles"], stats(1, 1, 0));
+
+        crate::rt::sleep(Duration::from_millis(80)).await;
+        select_alice(&client).await;
+        assert_eq!(clone.cache_stats()["profiles"], stats(1, 1, 1));
+
+                let profiles = |event| ("profiles".to_string(), event);
+        assert_eq!(*events.0.lock().unwrap(), [profiles(CacheEvent::Miss), profiles(CacheEvent::Hit), profiles(CacheEvent::StaleRefresh)]);
+    }
+}
@@ -0,0 +1,103 @@
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use serde_json::Value;
+
+// Serializes navigators with every object's keys in sorted order, however the objects were
+// built. `serde_json::Map` is only sorted while no crate in the build enables serde_json's
+// `preserve_order` feature; with it, a map converted from a `HashMap` keeps that map's
+// arbitrary order, and identical paths would be sent as different bytes.
+pub(crate) struct CanonicalPath<'a>(pub(crate) &'a [Value]);
+
+impl Serialize for CanonicalPath<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(Canonical))
+    }
+}
+
+// One value of a canonical path.
+pub(crate) struct Canonical<'a>(pub(crate) &'a Value);
+
+impl Serialize for Canonical<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Object(map) => {
+                let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+                entries.sort_unstable_by_key(|(key, _)| *key);
+                let mut object = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    object.serialize_entry(key, &Canonical(value))?;
+                }
+                object.end()
+            }
+            Value::Array(items) => serializer.collect_seq(items.iter().map(Canonical)),
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+// The canonical JSON text of a path.
+pub(crate) fn path_to_string(navigators: &[Value]) -> String {
+    serde_json::to_string(&CanonicalPath(navigators)).expect("JSON values always serialize")
+}
+
+// The canonical JSON text of one value.
+pub(crate) fn to_string(value: &Value) -> String {
+    serde_json::to_string(&Canonical(value)).expect("JSON values always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{path_to_string, to_string};
+    use crate::testing::{Reply, Scripted};
+    use crate::Path;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    // The same entries, inserted in opposite orders.
+    fn maps() -> (HashMap<&'static str, Value>, HashMap<&'static str, Value>) {
+        let entries = [("zeta", json!(1)), ("alpha", json!({"y": 2, "x": [{"q": 1, "p": 2}]})), ("mid", json!(null))];
+        (entries.clone().into_iter().collect(), entries.into_iter().rev().collect())
+    }
+
+    #[test]
+    fn sorts_keys_at_every_level() {
+        let value = json!({"b": {"d": 1, "c": 2}, "a": [{"z": 1, "y": 2}]});
+        assert_eq!(to_string(&value), r#"{"a":[{"y":2,"z":1}],"b":{"c":2,"d":1}}"#);
+        assert_eq!(path_to_string(&[json!("k"), value]), r#"["k",{"a":[{"y":2,"z":1}],"b":{"c":2,"d":1}}]"#);
+    }
+
+    #[test]
+    fn differently_ordered_inputs_give_identical_text() {
+        let (forward, backward) = maps();
+        let forward = Path::new().key("alice").nav(serde_json::to_value(forward).unwrap()).all();
+        let backward = Path::new().key("alice").nav(serde_json::to_value(backward).unwrap()).all();
+        assert_eq!(forward.to_canonical_json(), backward.to_canonical_json());
+        assert_eq!(
+            forward.to_canonical_json(),
+            r#"["alice",{"alpha":{"x":[{"p":2,"q":1}],"y":2},"mid":null,"zeta":1},["all"]]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn differently_ordered_inputs_send_identical_bodies() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        script.script("supervisor-1:1984", [Reply::ok([1])]);
+        let client = script.client_builder().build().unwrap();
+        let (forward, backward) = maps();
+
+        let mut expected = None;
+        for map in [forward, backward] {
+            let query = client.pstate_query("profiles", "$$profiles").key("alice").nav(serde_json::to_value(map).unwrap());
+            expected = Some(query.clone().into_path().to_canonical_json());
+            query.clone().select::<Value>().await.unwrap();
+            query.prepare().execute::<Value>(&client).await.unwrap();
+        }
+        let bodies = script.bodies();
+        assert_eq!(bodies.len(), 5);
+        for body in &bodies[1..] {
+            assert_eq!(body, &bodies[0]);
+        }
+        assert_eq!(bodies[0], expected.unwrap().as_bytes());
+    }
+}
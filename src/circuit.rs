@@ -0,0 +1,97 @@
+use crate::logging::{info, warn};
+use crate::rt::Instant;
+use crate::ClientError;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Per-module circuit breaker settings. See `ClientBuilder::circuit_breaker`.
+///
+/// After `failure_threshold` consecutive requests to a module fail with a retryable error
+/// (after their own retries), the module's circuit opens: requests fail immediately with
+/// `ClientError::CircuitOpen` for `open_for`. Then one probe request is let through
+/// (half-open); its success closes the circuit and its failure opens it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    pub failure_threshold: u32,
+    pub open_for: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    /// 5 consecutive failures, open for 30 seconds.
+    fn default() -> Self {
+        Self { failure_threshold: 5, open_for: Duration::from_secs(30) }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    // A probe is in flight. If it never reports back (e.g. its future was dropped),
+    // another probe is allowed after `open_for`.
+    HalfOpen { probe_started: Instant },
+}
+
+// Circuit state per module, shared by all clones of a client.
+#[derive(Debug)]
+pub(crate) struct CircuitBreakers {
+    policy: CircuitBreakerPolicy,
+    modules: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreakers {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self { policy, modules: Mutex::new(HashMap::new()) }
+    }
+
+    // Checks whether a request to `module` may be sent; it may be the half-open probe.
+    pub(crate) fn check(&self, module: &str) -> Result<(), ClientError> {
+        let mut modules = self.modules.lock().unwrap();
+        let Some(circuit) = modules.get_mut(module) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let wait_until = match *circuit {
+            Circuit::Closed { .. } => return Ok(()),
+            Circuit::Open { until } => until,
+            Circuit::HalfOpen { probe_started } => probe_started + self.policy.open_for,
+        };
+
+        // Guard: Still open, or the probe is still out
+        if now < wait_until {
+            return Err(ClientError::CircuitOpen { module: module.to_string(), retry_after: wait_until - now });
+        }
+        info!("Circuit for module '{}' half-open; sending a probe request", module);
+        *circuit = Circuit::HalfOpen { probe_started: now };
+        Ok(())
+    }
+
+    // Records the outcome of a request `check` let through.
+    pub(crate) fn record(&self, module: &str, result: &Result<impl Sized, ClientError>) {
+        // Only failures that suggest the module is unavailable count.
+        let failed = matches!(result, Err(e) if e.is_retryable());
+        let mut modules = self.modules.lock().unwrap();
+
+        // Guard: Success; close the circuit
+        if !failed {
+            if let Some(Circuit::HalfOpen { .. } | Circuit::Open { .. }) = modules.remove(module) {
+                info!("Circuit for module '{}' closed", module);
+            }
+            return;
+        }
+
+        let circuit = modules.entry(module.to_string()).or_insert(Circuit::Closed { failures: 0 });
+        let open = match *circuit {
+            Circuit::Closed { failures } if failures + 1 < self.policy.failure_threshold => {
+                *circuit = Circuit::Closed { failures: failures + 1 };
+                false
+            }
+            _ => true,
+        };
+        if open {
+            warn!("Circuit for module '{}' opened for {:?}", module, self.policy.open_for);
+            *circuit = Circuit::Open { until: Instant::now() + self.policy.open_for };
+        }
+    }
+}
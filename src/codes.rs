@@ -0,0 +1,273 @@
+//! Stable error codes for `ClientError`, for alerting rules and log matching.
+//!
+//! Codes are a compatibility surface: once published, a code keeps its meaning and is
+//! never reused. `ClientError::code` returns one of these; `ALL` lists every code.
+
+// --- Transport ---
+/// The HTTP request timed out.
+pub const TRANSPORT_TIMEOUT: &str = "RAMA-TRANSPORT-TIMEOUT";
+/// Connecting to the conductor or a supervisor failed.
+pub const TRANSPORT_CONNECT: &str = "RAMA-TRANSPORT-CONNECT";
+/// A response body couldn't be decoded.
+pub const TRANSPORT_DECODE: &str = "RAMA-TRANSPORT-DECODE";
+/// Any other HTTP-level failure.
+pub const TRANSPORT_OTHER: &str = "RAMA-TRANSPORT-OTHER";
+
+// --- Server responses ---
+/// The server answered with a 5xx status.
+pub const SERVER_5XX: &str = "RAMA-SERVER-5XX";
+/// The server answered 429 Too Many Requests.
+pub const SERVER_THROTTLED: &str = "RAMA-SERVER-THROTTLED";
+/// The server answered 401 or 403.
+pub const SERVER_AUTH: &str = "RAMA-SERVER-AUTH";
+/// The server answered 404 (`ModuleNotFound`, `ObjectNotFound`).
+pub const SERVER_NOT_FOUND: &str = "RAMA-SERVER-NOTFOUND";
+/// The server answered with any other unexpected status.
+pub const SERVER_4XX: &str = "RAMA-SERVER-4XX";
+
+// --- Routing ---
+pub const ROUTING_NO_SUPERVISOR: &str = "RAMA-ROUTING-NOSUPERVISOR";
+pub const ROUTING_MISSING_LOCATION: &str = "RAMA-ROUTING-NOLOCATION";
+pub const ROUTING_MISSING_SUPERVISOR_LOCATIONS: &str = "RAMA-ROUTING-NOSUPERVISORLOCATIONS";
+pub const ROUTING_INVALID_SUPERVISOR_LOCATIONS: &str = "RAMA-ROUTING-BADSUPERVISORLOCATIONS";
+pub const ROUTING_MAX_REDIRECTS: &str = "RAMA-ROUTING-MAXREDIRECTS";
+pub const ROUTING_LOOP: &str = "RAMA-ROUTING-LOOP";
+pub const ROUTING_REJECTED: &str = "RAMA-ROUTING-REJECTED";
+pub const ROUTING_SCHEME: &str = "RAMA-ROUTING-SCHEME";
+/// The module's circuit breaker is open; the request wasn't sent.
+pub const ROUTING_CIRCUIT_OPEN: &str = "RAMA-ROUTING-CIRCUITOPEN";
+
+// --- Queries and appends ---
+/// `selectOne` answered `null`: the path selected nothing.
+pub const QUERY_NOT_FOUND: &str = "RAMA-QUERY-NOTFOUND";
+pub const QUERY_MULTIPLE_RESULTS: &str = "RAMA-QUERY-MULTIPLERESULTS";
+pub const QUERY_JOIN_FAN_OUT: &str = "RAMA-QUERY-JOINFANOUT";
+pub const QUERY_JOIN_MISSING: &str = "RAMA-QUERY-JOINMISSING";
+pub const QUERY_PAGINATION_LIMIT: &str = "RAMA-QUERY-PAGINATIONLIMIT";
+/// A prepared query was executed with a `key_param` placeholder left unbound.
+pub const QUERY_UNBOUND_PARAMETER: &str = "RAMA-QUERY-UNBOUNDPARAM";
+/// `PreparedQuery::bind` named a parameter the query doesn't have.
+pub const QUERY_UNKNOWN_PARAMETER: &str = "RAMA-QUERY-UNKNOWNPARAM";
+/// A path exceeded the client's navigator or size limit; it wasn't sent.
+pub const QUERY_PATH_TOO_LARGE: &str = "RAMA-QUERY-PATHTOOLARGE";
+pub const APPEND_TRANSFORM: &str = "RAMA-APPEND-TRANSFORM";
+pub const APPEND_CLOSED: &str = "RAMA-APPEND-CLOSED";
+/// A `DepotAppender` had appends fail before it was closed.
+pub const APPEND_DEFERRED_FAILURES: &str = "RAMA-APPEND-DEFERRED";
+/// A streaming topology threw while processing an acked append.
+pub const APPEND_TOPOLOGY_FAILURE: &str = "RAMA-APPEND-TOPOLOGY";
+/// An append's idempotency key pointer doesn't lead to a field of an object.
+pub const APPEND_IDEMPOTENCY_KEY: &str = "RAMA-APPEND-IDEMPOTENCYKEY";
+/// A queued append was refused because the client's memory budget is used up.
+pub const APPEND_BUDGET_EXCEEDED: &str = "RAMA-APPEND-BUDGET";
+
+// --- Client-side ---
+/// Serializing a request or deserializing a result failed.
+pub const CLIENT_JSON: &str = "RAMA-CLIENT-JSON";
+/// A MessagePack result couldn't be deserialized.
+pub const CLIENT_MSGPACK: &str = "RAMA-CLIENT-MSGPACK";
+pub const CLIENT_URL: &str = "RAMA-CLIENT-URL";
+pub const CLIENT_HEADER: &str = "RAMA-CLIENT-HEADER";
+/// A module or object name can't be represented in a URL.
+pub const CLIENT_NAME: &str = "RAMA-CLIENT-NAME";
+/// A client-side deadline (preflight, visibility polling) passed.
+pub const CLIENT_TIMEOUT: &str = "RAMA-CLIENT-TIMEOUT";
+/// The blocking client's runtime couldn't start.
+pub const CLIENT_RUNTIME: &str = "RAMA-CLIENT-RUNTIME";
+
+/// Every code `ClientError::code` can return, regardless of enabled features.
+pub const ALL: &[&str] = &[
+    TRANSPORT_TIMEOUT,
+    TRANSPORT_CONNECT,
+    TRANSPORT_DECODE,
+    TRANSPORT_OTHER,
+    SERVER_5XX,
+    SERVER_THROTTLED,
+    SERVER_AUTH,
+    SERVER_NOT_FOUND,
+    SERVER_4XX,
+    ROUTING_NO_SUPERVISOR,
+    ROUTING_MISSING_LOCATION,
+    ROUTING_MISSING_SUPERVISOR_LOCATIONS,
+    ROUTING_INVALID_SUPERVISOR_LOCATIONS,
+    ROUTING_MAX_REDIRECTS,
+    ROUTING_LOOP,
+    ROUTING_REJECTED,
+    ROUTING_SCHEME,
+    ROUTING_CIRCUIT_OPEN,
+    QUERY_NOT_FOUND,
+    QUERY_MULTIPLE_RESULTS,
+    QUERY_JOIN_FAN_OUT,
+    QUERY_JOIN_MISSING,
+    QUERY_PAGINATION_LIMIT,
+    QUERY_UNBOUND_PARAMETER,
+    QUERY_UNKNOWN_PARAMETER,
+    QUERY_PATH_TOO_LARGE,
+    APPEND_TRANSFORM,
+    APPEND_CLOSED,
+    APPEND_DEFERRED_FAILURES,
+    APPEND_TOPOLOGY_FAILURE,
+    APPEND_IDEMPOTENCY_KEY,
+    APPEND_BUDGET_EXCEEDED,
+    CLIENT_JSON,
+    CLIENT_MSGPACK,
+    CLIENT_URL,
+    CLIENT_HEADER,
+    CLIENT_NAME,
+    CLIENT_TIMEOUT,
+    CLIENT_RUNTIME,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::ALL;
+    use crate::{ClientError, PathLimits};
+    use reqwest::StatusCode;
+    use serde::de::Error as _;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    // Alerting rules downstream match on these strings: changing one is a breaking change.
+    #[test]
+    fn codes_are_pinned() {
+        let pinned = [
+            "RAMA-TRANSPORT-TIMEOUT",
+            "RAMA-TRANSPORT-CONNECT",
+            "RAMA-TRANSPORT-DECODE",
+            "RAMA-TRANSPORT-OTHER",
+            "RAMA-SERVER-5XX",
+            "RAMA-SERVER-THROTTLED",
+            "RAMA-SERVER-AUTH",
+            "RAMA-SERVER-NOTFOUND",
+            "RAMA-SERVER-4XX",
+            "RAMA-ROUTING-NOSUPERVISOR",
+            "RAMA-ROUTING-NOLOCATION",
+            "RAMA-ROUTING-NOSUPERVISORLOCATIONS",
+            "RAMA-ROUTING-BADSUPERVISORLOCATIONS",
+            "RAMA-ROUTING-MAXREDIRECTS",
+            "RAMA-ROUTING-LOOP",
+            "RAMA-ROUTING-REJECTED",
+            "RAMA-ROUTING-SCHEME",
+            "RAMA-ROUTING-CIRCUITOPEN",
+            "RAMA-QUERY-NOTFOUND",
+            "RAMA-QUERY-MULTIPLERESULTS",
+            "RAMA-QUERY-JOINFANOUT",
+            "RAMA-QUERY-JOINMISSING",
+            "RAMA-QUERY-PAGINATIONLIMIT",
+            "RAMA-QUERY-UNBOUNDPARAM",
+            "RAMA-QUERY-UNKNOWNPARAM",
+            "RAMA-QUERY-PATHTOOLARGE",
+            "RAMA-APPEND-TRANSFORM",
+            "RAMA-APPEND-CLOSED",
+            "RAMA-APPEND-DEFERRED",
+            "RAMA-APPEND-TOPOLOGY",
+            "RAMA-APPEND-IDEMPOTENCYKEY",
+            "RAMA-APPEND-BUDGET",
+            "RAMA-CLIENT-JSON",
+            "RAMA-CLIENT-MSGPACK",
+            "RAMA-CLIENT-URL",
+            "RAMA-CLIENT-HEADER",
+            "RAMA-CLIENT-NAME",
+            "RAMA-CLIENT-TIMEOUT",
+            "RAMA-CLIENT-RUNTIME",
+        ];
+        assert_eq!(ALL, pinned);
+        assert_eq!(ClientError::all_codes(), pinned);
+        assert_eq!(ALL.iter().collect::<HashSet<_>>().len(), ALL.len(), "duplicate code");
+    }
+
+    fn json_error() -> serde_json::Error {
+        serde_json::Error::custom("bad")
+    }
+
+    fn status(status: StatusCode) -> ClientError {
+        ClientError::UnexpectedStatus(status, "http://conductor:1984".into())
+    }
+
+    // One error of each kind that can be built without a live connection, with its code.
+    // `ClientError::code` has no wildcard arm, so a new variant fails to compile until it
+    // is assigned a code; this pins which code each kind maps to.
+    fn examples() -> Vec<(ClientError, &'static str)> {
+        let boxed = || Box::new(ClientError::MaxRedirectsExceeded);
+        vec![
+            (ClientError::Transport("refused".into()), "RAMA-TRANSPORT-OTHER"),
+            (status(StatusCode::BAD_GATEWAY), "RAMA-SERVER-5XX"),
+            (status(StatusCode::TOO_MANY_REQUESTS), "RAMA-SERVER-THROTTLED"),
+            (status(StatusCode::UNAUTHORIZED), "RAMA-SERVER-AUTH"),
+            (status(StatusCode::FORBIDDEN), "RAMA-SERVER-AUTH"),
+            (status(StatusCode::NOT_FOUND), "RAMA-SERVER-NOTFOUND"),
+            (status(StatusCode::CONFLICT), "RAMA-SERVER-4XX"),
+            (ClientError::ModuleNotFound { module: "m".into(), body: String::new() }, "RAMA-SERVER-NOTFOUND"),
+            (
+                ClientError::ObjectNotFound { module: "m".into(), object: "$$p".into(), body: String::new() },
+                "RAMA-SERVER-NOTFOUND",
+            ),
+            (ClientError::CompressedBodyRejected(StatusCode::UNSUPPORTED_MEDIA_TYPE, "u".into()), "RAMA-SERVER-4XX"),
+            (ClientError::NoSupervisor("m".into()), "RAMA-ROUTING-NOSUPERVISOR"),
+            (ClientError::MissingLocationHeader, "RAMA-ROUTING-NOLOCATION"),
+            (ClientError::MissingSupervisorLocationsHeader, "RAMA-ROUTING-NOSUPERVISORLOCATIONS"),
+            (ClientError::InvalidSupervisorLocations(json_error()), "RAMA-ROUTING-BADSUPERVISORLOCATIONS"),
+            (ClientError::MaxRedirectsExceeded, "RAMA-ROUTING-MAXREDIRECTS"),
+            (ClientError::RedirectLoop { urls: vec![] }, "RAMA-ROUTING-LOOP"),
+            (ClientError::RedirectRejected { location: "l".into() }, "RAMA-ROUTING-REJECTED"),
+            (ClientError::RedirectNotFollowed { location: "l".into(), supervisors: vec![] }, "RAMA-ROUTING-REJECTED"),
+            (ClientError::SchemeChange("http".into(), "https"), "RAMA-ROUTING-SCHEME"),
+            (ClientError::CircuitOpen { module: "m".into(), retry_after: Duration::ZERO }, "RAMA-ROUTING-CIRCUITOPEN"),
+            (ClientError::NotFound { module: "m".into(), pstate: "$$p".into(), path: serde_json::Value::Null }, "RAMA-QUERY-NOTFOUND"),
+            (ClientError::MultipleResults(2), "RAMA-QUERY-MULTIPLERESULTS"),
+            (ClientError::JoinFanOutExceeded { size: 2, max: 1 }, "RAMA-QUERY-JOINFANOUT"),
+            (ClientError::MissingJoinValue("k".into()), "RAMA-QUERY-JOINMISSING"),
+            (ClientError::PaginationLimit(3), "RAMA-QUERY-PAGINATIONLIMIT"),
+            (ClientError::UnboundQueryParameter("id".into()), "RAMA-QUERY-UNBOUNDPARAM"),
+            (
+                ClientError::UnknownQueryParameter { pstate: "$$p".into(), name: "id".into(), params: vec![] },
+                "RAMA-QUERY-UNKNOWNPARAM",
+            ),
+            (
+                ClientError::PathTooLarge { navigators: 2, bytes: 2, limits: PathLimits::default() },
+                "RAMA-QUERY-PATHTOOLARGE",
+            ),
+            (ClientError::TransformFailed { depot: "*d".into(), index: 0, source: boxed() }, "RAMA-APPEND-TRANSFORM"),
+            (ClientError::AppenderClosed("*d".into()), "RAMA-APPEND-CLOSED"),
+            (ClientError::AppendsFailed { depot: "*d".into(), failed: 1, first: boxed() }, "RAMA-APPEND-DEFERRED"),
+            (
+                ClientError::TopologyFailure { topology: "t".into(), message: "m".into(), details: serde_json::Value::Null },
+                "RAMA-APPEND-TOPOLOGY",
+            ),
+            (ClientError::InvalidIdempotencyKeyPointer("/x".into()), "RAMA-APPEND-IDEMPOTENCYKEY"),
+            (ClientError::BudgetExceeded { requested: 2, used: 1, limit: 2 }, "RAMA-APPEND-BUDGET"),
+            (ClientError::Json(json_error()), "RAMA-CLIENT-JSON"),
+            (ClientError::Url(url::ParseError::EmptyHost), "RAMA-CLIENT-URL"),
+            (ClientError::MissingScheme("c".into()), "RAMA-CLIENT-URL"),
+            (ClientError::MissingHost("http://".into()), "RAMA-CLIENT-URL"),
+            (ClientError::InvalidHeader("x".into()), "RAMA-CLIENT-HEADER"),
+            (ClientError::InvalidName("a/b".into()), "RAMA-CLIENT-NAME"),
+            (ClientError::Timeout(Duration::from_secs(1)), "RAMA-CLIENT-TIMEOUT"),
+        ]
+    }
+
+    #[test]
+    fn each_kind_maps_to_its_code() {
+        for (err, code) in examples() {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!(ALL.contains(&code));
+        }
+    }
+
+    #[test]
+    fn wrappers_report_the_code_of_what_they_wrap() {
+        for (err, code) in examples() {
+            let unreachable = ClientError::Unreachable { url: "u".into(), reason: "r", source: Box::new(err) };
+            let wrapped = ClientError::WithRequestId { request_id: "id".into(), source: Box::new(unreachable) };
+            assert_eq!(wrapped.code(), code);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_refused_connection_is_a_connect_error() {
+        // Nothing listens on port 1
+        let client = crate::Client::new("http://127.0.0.1:1").unwrap();
+        let err = client.pstate_query("profiles", "$$profiles").key("alice").select::<serde_json::Value>().await.unwrap_err();
+        assert_eq!(err.code(), "RAMA-TRANSPORT-CONNECT", "{:?}", err);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::ClientError;
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+// Gzips a request body for `Content-Encoding: gzip`.
+pub(crate) fn gzip(body: &[u8]) -> Result<Bytes, ClientError> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(body.len() / 4), Compression::default());
+    // Writing to a Vec can't fail, but the encoder's API is fallible.
+    let compressed = encoder
+        .write_all(body)
+        .and_then(|()| encoder.finish())
+        .map_err(|e| ClientError::Transport(Box::new(e)))?;
+    Ok(Bytes::from(compressed))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "tokio"))]
+mod tests {
+    use super::gzip;
+    use crate::testing::MockServer;
+    use crate::{Client, ClientError};
+    use flate2::read::GzDecoder;
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use std::io::Read;
+
+    fn gunzip(body: &[u8]) -> Vec<u8> {
+        let mut decoded = Vec::new();
+        GzDecoder::new(body).read_to_end(&mut decoded).unwrap();
+        decoded
+    }
+
+    // Answers with `body`, gzipped when the request accepts gzip.
+    async fn serving(body: Value) -> MockServer {
+        let body = serde_json::to_vec(&body).unwrap();
+        MockServer::start(move |request| match request.header("accept-encoding").is_some_and(|accepted| accepted.contains("gzip")) {
+            true => (200, vec![("content-encoding", "gzip".to_string()), ("content-type", "application/json".to_string())], gzip(&body).unwrap().to_vec()),
+            false => (200, vec![("content-type", "application/json".to_string())], body.clone()),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn gzipped_results_are_decoded() {
+        let ids: Vec<u32> = (0..5_000).collect();
+        let server = serving(json!(ids)).await;
+        let client = Client::new(&server.url).unwrap();
+
+        let selected: Vec<u32> = client.pstate_query("profiles", "$$ids").all().select().await.unwrap();
+        assert_eq!(selected, ids);
+        let streamed: Vec<u32> = client.pstate_query("profiles", "$$ids").all().select_stream::<u32>().map(Result::unwrap).collect().await;
+        assert_eq!(streamed, ids);
+        assert!(server.requests().iter().all(|r| r.header("accept-encoding").is_some_and(|a| a.contains("gzip") && a.contains("br"))));
+    }
+
+    #[tokio::test]
+    async fn compression_can_be_turned_off() {
+        let server = serving(json!([1, 2])).await;
+        let client = Client::builder(&server.url).accept_compressed(false).build().unwrap();
+        let selected: Vec<u32> = client.pstate_query("profiles", "$$ids").all().select().await.unwrap();
+        assert_eq!(selected, [1, 2]);
+        assert_eq!(server.requests()[0].header("accept-encoding"), None);
+    }
+
+    #[tokio::test]
+    async fn a_corrupt_gzip_body_is_a_decode_error() {
+        let server = MockServer::start(|_| (200, vec![("content-encoding", "gzip".to_string())], b"not gzip at all".to_vec())).await;
+        let client = Client::new(&server.url).unwrap();
+        let err = client.pstate_query("profiles", "$$ids").all().select::<Value>().await.unwrap_err();
+        assert_eq!(err.code(), "RAMA-TRANSPORT-DECODE", "{:?}", err);
+    }
+
+    // Accepts appends, answering 415 to compressed ones if `reject_gzip`.
+    async fn depot(reject_gzip: bool) -> MockServer {
+        MockServer::start(move |request| match (request.header("content-encoding"), reject_gzip) {
+            (Some(_), true) => (415, Vec::new(), b"unsupported encoding".to_vec()),
+            _ => (200, Vec::new(), b"{}".to_vec()),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn request_bodies_over_the_threshold_are_gzipped() {
+        let server = depot(false).await;
+        let client = Client::builder(&server.url).compress_request_bodies(1024).build().unwrap();
+        let small = json!({"id": "alice"});
+        let large = json!({"id": "bob", "bio": "x".repeat(4096)});
+        for record in [&small, &large] {
+            client.depot_append("profiles", "*edits", record).append::<Value>().await.unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("content-encoding"), None);
+        let sent: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(sent["data"], small);
+
+        assert_eq!(requests[1].header("content-encoding"), Some("gzip"));
+        assert!(requests[1].body.len() < 1024);
+        let sent: Value = serde_json::from_slice(&gunzip(&requests[1].body)).unwrap();
+        assert_eq!(sent["data"], large);
+    }
+
+    #[tokio::test]
+    async fn request_compression_is_off_by_default() {
+        let server = depot(true).await;
+        let client = Client::new(&server.url).unwrap();
+        client.depot_append("profiles", "*edits", json!({"bio": "x".repeat(1 << 20)})).append::<Value>().await.unwrap();
+        assert_eq!(server.requests()[0].header("content-encoding"), None);
+    }
+
+    #[tokio::test]
+    async fn a_rejected_compressed_body_suggests_disabling_compression() {
+        let server = depot(true).await;
+        let client = Client::builder(&server.url).compress_request_bodies(16).build().unwrap();
+        let err = client.depot_append("profiles", "*edits", json!({"bio": "x".repeat(64)})).append::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::CompressedBodyRejected(status, _) if status.as_u16() == 415), "{:?}", err);
+        assert!(err.to_string().contains("compress_request_bodies"), "{}", err);
+    }
+}
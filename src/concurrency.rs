@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// Optional cap on concurrent logical requests, plus the in-flight count for monitoring.
+// Shared by a client and its clones.
+#[derive(Debug, Default)]
+pub(crate) struct ConcurrencyLimiter {
+    // None = unlimited
+    semaphore: Option<Semaphore>,
+    in_flight: AtomicUsize,
+}
+
+impl ConcurrencyLimiter {
+    pub(crate) fn new(max_in_flight: Option<usize>) -> Self {
+        Self { semaphore: max_in_flight.map(|max| Semaphore::new(max.max(1))), in_flight: AtomicUsize::new(0) }
+    }
+
+    // Waits (FIFO) until the request may start. The returned guard is the request's slot:
+    // it is released when dropped, however the request ends.
+    pub(crate) async fn acquire(&self) -> InFlightGuard<'_> {
+        let permit = match &self.semaphore {
+            Some(semaphore) => match semaphore.try_acquire() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    crate::logging::debug!("Concurrency limit reached; queueing request");
+                    // The semaphore is never closed, so this only fails if it were.
+                    semaphore.acquire().await.ok()
+                }
+            },
+            None => None,
+        };
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard { _permit: permit, in_flight: &self.in_flight }
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+}
+
+pub(crate) struct InFlightGuard<'a> {
+    _permit: Option<SemaphorePermit<'a>>,
+    in_flight: &'a AtomicUsize,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use url::Url;
+
+// The conductor URLs a client can use, in failover order, and which one is in use.
+// Shared by all clones, so a failover is remembered instead of re-probing the dead
+// conductor on every request.
+#[derive(Debug)]
+pub(crate) struct Conductors {
+    // Never empty; the first is the builder's base URL
+    urls: Vec<Url>,
+    active: AtomicUsize,
+}
+
+impl Conductors {
+    pub(crate) fn new(urls: Vec<Url>) -> Self {
+        Self { urls, active: AtomicUsize::new(0) }
+    }
+
+    // The conductor currently in use.
+    pub(crate) fn active(&self) -> &Url {
+        &self.urls[self.active.load(Ordering::Relaxed)]
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    // Whether `url` points at one of the conductors.
+    pub(crate) fn contains(&self, url: &Url) -> bool {
+        self.urls.iter().any(|conductor| conductor.origin() == url.origin())
+    }
+
+    // Switches away from `failed` (if it is still the active conductor) to the next one in
+    // order, and returns the conductor now in use. None if there is no other conductor.
+    pub(crate) fn fail_over(&self, failed: &Url) -> Option<&Url> {
+        // Guard: Nothing to fail over to
+        if self.urls.len() < 2 {
+            return None;
+        }
+        let current = self.active.load(Ordering::Relaxed);
+        if self.urls[current].origin() == failed.origin() {
+            // A concurrent request may have failed over already; either way, use its choice.
+            let _ = self.active.compare_exchange(current, (current + 1) % self.urls.len(), Ordering::Relaxed, Ordering::Relaxed);
+        }
+        Some(self.active())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Conductors;
+    use url::Url;
+
+    fn conductors(urls: &[&str]) -> Conductors {
+        Conductors::new(urls.iter().map(|url| Url::parse(url).unwrap()).collect())
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn fails_over_in_order_and_wraps_around() {
+        let conductors = conductors(&["http://primary:1984/", "http://standby:1984/"]);
+        assert_eq!(conductors.active().as_str(), "http://primary:1984/");
+        // Any path on the conductor counts as the conductor
+        assert!(conductors.contains(&url("http://standby:1984/rest/profiles/pstate/$$p/select")));
+        assert!(!conductors.contains(&url("http://supervisor-1:1984/")));
+
+        let next = conductors.fail_over(&url("http://primary:1984/rest/profiles/depot/*d/append")).unwrap();
+        assert_eq!(next.as_str(), "http://standby:1984/");
+        let next = conductors.fail_over(&url("http://standby:1984/")).unwrap();
+        assert_eq!(next.as_str(), "http://primary:1984/");
+    }
+
+    #[test]
+    fn a_stale_failure_doesnt_fail_over_again() {
+        let conductors = conductors(&["http://primary:1984/", "http://standby:1984/", "http://third:1984/"]);
+        // Two requests fail against the primary; only the first moves the client on
+        conductors.fail_over(&url("http://primary:1984/"));
+        let next = conductors.fail_over(&url("http://primary:1984/")).unwrap();
+        assert_eq!(next.as_str(), "http://standby:1984/");
+    }
+
+    #[test]
+    fn a_single_conductor_has_nothing_to_fail_over_to() {
+        let conductors = conductors(&["http://primary:1984/"]);
+        assert_eq!(conductors.fail_over(&url("http://primary:1984/")), None);
+        assert_eq!(conductors.active().as_str(), "http://primary:1984/");
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    mod failover {
+        use crate::testing::MockServer;
+        use crate::{Client, ClientBuilder};
+
+        // A local address nothing listens on, so connections are refused
+        async fn refusing_url() -> String {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        }
+
+        async fn select_alice(client: &Client) -> Result<Vec<u32>, crate::ClientError> {
+            client.pstate_query("profiles", "$$profiles").key("alice").select().await
+        }
+
+        #[tokio::test]
+        async fn a_refusing_conductor_fails_over_to_the_standby_and_stays_there() {
+            let primary = refusing_url().await;
+            let standby = MockServer::start(|_| (200, Vec::new(), b"[30]".to_vec())).await;
+            let client = ClientBuilder::new(&primary).fallback_conductors([standby.url.clone()]).build().unwrap();
+
+            assert_eq!(select_alice(&client).await.unwrap(), [30]);
+            assert_eq!(client.base_url().as_str(), format!("{}/", standby.url));
+            // Clones share the switch, so neither probes the primary again
+            let clone = client.clone();
+            assert_eq!(select_alice(&clone).await.unwrap(), [30]);
+            assert_eq!(standby.requests().len(), 2);
+        }
+
+        #[tokio::test]
+        async fn fails_when_every_conductor_refuses() {
+            let (primary, standby) = (refusing_url().await, refusing_url().await);
+            let client = ClientBuilder::new(&primary).fallback_conductors([standby]).build().unwrap();
+            let err = select_alice(&client).await.unwrap_err();
+            assert_eq!(err.code(), "RAMA-TRANSPORT-CONNECT", "{:?}", err);
+        }
+
+        #[tokio::test]
+        async fn a_refusing_supervisor_doesnt_switch_conductors() {
+            let supervisor = refusing_url().await;
+            let supervisor_host = supervisor.trim_start_matches("http://").to_string();
+            let conductor = MockServer::start(move |_| {
+                let location = format!("http://{}/rest/profiles/pstate/$$profiles/select", supervisor_host);
+                (308, vec![("location", location), ("supervisor-locations", format!("[\"{}\"]", supervisor_host))], Vec::new())
+            })
+            .await;
+            let standby = MockServer::start(|_| (200, Vec::new(), b"[30]".to_vec())).await;
+            let client = ClientBuilder::new(&conductor.url).fallback_conductors([standby.url.clone()]).build().unwrap();
+
+            let err = select_alice(&client).await.unwrap_err();
+            assert_eq!(err.code(), "RAMA-TRANSPORT-CONNECT", "{:?}", err);
+            assert_eq!(client.base_url().as_str(), format!("{}/", conductor.url));
+            assert!(standby.requests().is_empty());
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::budget::{self, BudgetTracker, Subsystem};
+use crate::builder::AckLevel;
+use crate::logging::{debug, warn};
+use crate::{rt, Client, ClientError};
+use futures::future::join_all;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+// Detached appends sent at once by the worker.
+const MAX_IN_FLIGHT: usize = 16;
+
+/// Counts of detached appends that were lost, from `Client::detached_failures`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DetachedFailures {
+    /// Appends the server rejected or that couldn't be sent.
+    pub failed: u64,
+    /// Records dropped unsent because the queue was full.
+    pub dropped: u64,
+}
+
+// A queued append: module, depot, record and its size as accounted in the budget.
+type Job = (String, String, Value, usize);
+
+#[derive(Debug, Default)]
+struct Queue {
+    jobs: VecDeque<Job>,
+    // Jobs taken by the worker and not finished yet
+    in_flight: usize,
+    // Whether a worker task is draining the queue
+    draining: bool,
+}
+
+// Queue of detached appends, shared by all clones of a client. A worker task is spawned
+// when records are queued and exits once the queue is empty, so an idle client holds no
+// task (and no clone of itself).
+#[derive(Debug)]
+pub(crate) struct DetachedAppends {
+    capacity: usize,
+    // Queued records count towards the client's memory budget
+    budget: Arc<BudgetTracker>,
+    queue: Mutex<Queue>,
+    // Woken whenever the queue drains completely
+    idle: Notify,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl DetachedAppends {
+    pub(crate) fn new(capacity: usize, budget: Arc<BudgetTracker>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            budget,
+            queue: Mutex::default(),
+            idle: Notify::new(),
+            failed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    // Queues a job, dropping the oldest if full. Returns whether a worker must be spawned.
+    fn push(&self, job: Job) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.jobs.len() >= self.capacity {
+            if let Some((module, depot, _, size)) = queue.jobs.pop_front() {
+                self.budget.remove(Subsystem::AppendQueue, size);
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!("Detached append queue full ({} records); dropped the oldest record for depot '{}' of module '{}' ({} dropped so far)", self.capacity, depot, module, dropped);
+            }
+        }
+        queue.jobs.push_back(job);
+        !std::mem::replace(&mut queue.draining, true)
+    }
+
+    // Takes the next jobs to send, finishing the previous batch. Returns none (and marks
+    // the queue idle) once everything has been sent.
+    fn next_batch(&self, finished: usize) -> Vec<Job> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.in_flight -= finished;
+        let count = queue.jobs.len().min(MAX_IN_FLIGHT);
+        let batch: Vec<Job> = queue.jobs.drain(..count).collect();
+        self.budget.remove(Subsystem::AppendQueue, batch.iter().map(|job| job.3).sum());
+        queue.in_flight += batch.len();
+        if batch.is_empty() {
+            queue.draining = false;
+            self.idle.notify_waiters();
+        }
+        batch
+    }
+
+    fn is_idle(&self) -> bool {
+        let queue = self.queue.lock().unwrap();
+        queue.jobs.is_empty() && queue.in_flight == 0
+    }
+}
+
+impl Client {
+    /// Queues an append to be sent in the background and returns immediately, for
+    /// telemetry-style records that needn't be awaited. Appends use `AckLevel::None`.
+    ///
+    /// Failures are logged and counted in `detached_failures`; nothing is retried. If
+    /// `ClientBuilder::detached_queue_capacity` records are already queued, the oldest is
+    /// dropped (with a warning) to make room. Call `flush` before shutting down, or queued
+    /// records may never be sent. With the `tokio` feature, must be called inside a Tokio
+    /// runtime.
+    ///
+    /// Fails if `data` can't be serialized, or with `ClientError::BudgetExceeded` if the
+    /// client's `MemoryBudget` rejects appends and the record doesn't fit.
+    pub fn depot_append_detached<T: Serialize>(&self, module: &str, depot: &str, data: T) -> Result<(), ClientError> {
+        let data = serde_json::to_value(data)?;
+        let size = budget::value_size(&data);
+        self.detached.budget.reserve_append(size)?;
+        let job = (module.to_string(), depot.to_string(), data, size);
+        if self.detached.push(job) {
+            let client = self.clone();
+            drop(rt::spawn(async move { client.drain_detached().await }));
+        }
+        Ok(())
+    }
+
+    /// Waits until every detached append queued so far (see `depot_append_detached`) has
+    /// been sent, or fails with `ClientError::Timeout` after `timeout`. Use at shutdown.
+    pub async fn flush(&self, timeout: Duration) -> Result<(), ClientError> {
+        let drained = async {
+            loop {
+                let notified = self.detached.idle.notified();
+                if self.detached.is_idle() {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        rt::timeout(timeout, drained).await.map_err(|_| ClientError::Timeout(timeout))
+    }
+
+    /// Detached appends that failed or were dropped since the client was built, across
+    /// all clones.
+    pub fn detached_failures(&self) -> DetachedFailures {
+        DetachedFailures {
+            failed: self.detached.failed.load(Ordering::Relaxed),
+            dropped: self.detached.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    // The worker: sends queued appends, MAX_IN_FLIGHT at a time, until the queue is empty.
+    async fn drain_detached(&self) {
+        let mut finished = 0;
+        loop {
+            let batch = self.detached.next_batch(finished);
+            // Guard: Queue drained
+            if batch.is_empty() {
+                debug!("Detached append queue drained");
+                return;
+            }
+            finished = batch.len();
+            let appends = batch.into_iter().map(|(module, depot, data, _)| async move {
+                let result = self.depot_append(&module, &depot, data).ack_level(AckLevel::None).append::<Value>().await;
+                if let Err(e) = result {
+                    self.detached.failed.fetch_add(1, Ordering::Relaxed);
+                    warn!("[{}] Detached append to depot '{}' of module '{}' failed: {}", e.code(), depot, module, e);
+                }
+            });
+            join_all(appends).await;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::DetachedFailures;
+    use crate::testing::FakeCluster;
+    use crate::ClientError;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.depot("telemetry", "*events");
+        cluster
+    }
+
+    fn sorted(mut records: Vec<Value>) -> Vec<Value> {
+        records.sort_by_key(|record| record["n"].as_u64());
+        records
+    }
+
+    #[tokio::test]
+    async fn flush_drains_every_queued_append() {
+        let cluster = cluster();
+        cluster.latency("*", Duration::from_millis(20));
+        let client = cluster.client();
+        // Nothing queued yet
+        client.flush(Duration::from_millis(10)).await.unwrap();
+
+        // Queued from a clone, flushed from the original
+        let clone = client.clone();
+        for n in 0..40 {
+            clone.depot_append_detached("telemetry", "*events", json!({"n": n})).unwrap();
+        }
+        assert!(cluster.appended("telemetry", "*events").is_empty());
+        client.flush(Duration::from_secs(5)).await.unwrap();
+
+        let expected: Vec<Value> = (0..40).map(|n| json!({"n": n})).collect();
+        assert_eq!(sorted(cluster.appended("telemetry", "*events")), expected);
+        assert_eq!(client.detached_failures(), DetachedFailures::default());
+        // Every append was sent with `AckLevel::None`
+        assert!(cluster.requests().iter().all(|request| request.body["ackLevel"] == "none"));
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_drops_the_oldest_records() {
+        let cluster = cluster();
+        let client = cluster.client_builder().detached_queue_capacity(5).build().unwrap();
+        // The worker can't run before this task yields, so the queue fills up
+        for n in 0..12 {
+            client.depot_append_detached("telemetry", "*events", json!({"n": n})).unwrap();
+        }
+        client.flush(Duration::from_secs(5)).await.unwrap();
+
+        let expected: Vec<Value> = (7..12).map(|n| json!({"n": n})).collect();
+        assert_eq!(sorted(cluster.appended("telemetry", "*events")), expected);
+        assert_eq!(client.detached_failures(), DetachedFailures { failed: 0, dropped: 7 });
+    }
+
+    #[tokio::test]
+    async fn failed_appends_are_counted_across_clones() {
+        let cluster = cluster();
+        let client = cluster.client();
+        client.depot_append_detached("telemetry", "*missing", json!({"n": 1})).unwrap();
+        client.depot_append_detached("telemetry", "*events", json!({"n": 2})).unwrap();
+        client.depot_append_detached("unknown", "*events", json!({"n": 3})).unwrap();
+        client.flush(Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(client.clone().detached_failures(), DetachedFailures { failed: 2, dropped: 0 });
+        assert_eq!(cluster.appended("telemetry", "*events"), [json!({"n": 2})]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_gives_up_after_its_timeout() {
+        let cluster = cluster();
+        cluster.latency("*", Duration::from_secs(10));
+        let client = cluster.client();
+        client.depot_append_detached("telemetry", "*events", json!({"n": 1})).unwrap();
+
+        let err = client.flush(Duration::from_secs(1)).await.unwrap_err();
+        assert!(matches!(err, ClientError::Timeout(timeout) if timeout == Duration::from_secs(1)), "{:?}", err);
+        // The append is still pending, and a longer flush waits for it
+        client.flush(Duration::from_secs(60)).await.unwrap();
+        assert_eq!(cluster.appended("telemetry", "*events"), [json!({"n": 1})]);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::logging::{debug, warn};
+use crate::redact::Redacted;
+use crate::{parse_supervisor_header, Client, ClientError, RoutingMode};
+use futures::future::join_all;
+use reqwest::StatusCode;
+
+impl Client {
+    /// Learns which supervisors serve `module` and caches them, so the next request goes
+    /// straight to a supervisor instead of being redirected by the conductor.
+    ///
+    /// Sends a GET for the module's REST root to the conductor, whose 308 carries the
+    /// supervisors; the 308 isn't an error, whatever `max_redirects` is. Returns the
+    /// supervisors (`host:port`), or none if the conductor serves the module itself.
+    /// With `RoutingMode::ConductorOnly` nothing is cached.
+    pub async fn discover_supervisors(&self, module: &str) -> Result<Vec<String>, ClientError> {
+        let url = self.build_url(module, "")?;
+        let response = self.get(url.clone()).await?;
+        let status = response.status;
+        debug!("Discovery probe for module '{}' answered {}", module, status);
+        match status {
+            StatusCode::PERMANENT_REDIRECT => {
+                let locations = parse_supervisor_header(&response.headers, &url)?;
+                let supervisors = locations.supervisors();
+                if self.routing_mode == RoutingMode::Smart {
+                    self.store_supervisors(module, locations);
+                }
+                Ok(supervisors)
+            }
+            StatusCode::NOT_FOUND => {
+                let body = response.text().await.unwrap_or_default();
+                Err(ClientError::ModuleNotFound { module: module.to_string(), body })
+            }
+            s if s == StatusCode::UNAUTHORIZED || s == StatusCode::FORBIDDEN || s.is_server_error() => {
+                Err(ClientError::UnexpectedStatus(s, Redacted(&url).to_string()))
+            }
+            // No redirect: the conductor answers for the module itself
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Discovers the supervisors of several modules concurrently (see
+    /// `discover_supervisors`), e.g. at startup. Returns one result per module, in order;
+    /// failures are also logged.
+    pub async fn warm_up(&self, modules: &[&str]) -> Vec<Result<Vec<String>, ClientError>> {
+        join_all(modules.iter().map(|module| async move {
+            let result = self.discover_supervisors(module).await;
+            if let Err(e) = &result {
+                warn!("[{}] Warm-up discovery for module '{}' failed: {}", e.code(), module, e);
+            }
+            result
+        }))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{Reply, Scripted};
+    use crate::ClientError;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    fn not_found() -> Reply {
+        Reply::Status(StatusCode::NOT_FOUND, vec![], "no such module".into())
+    }
+
+    #[tokio::test]
+    async fn discovery_caches_the_redirect_even_without_following_redirects() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::Redirect("supervisor-1:1984", vec!["supervisor-1:1984", "supervisor-2:1984"])]);
+        for supervisor in ["supervisor-1:1984", "supervisor-2:1984"] {
+            script.script(supervisor, [Reply::ok(json!([30]))]);
+        }
+        let client = script.client_builder().max_redirects(0).build().unwrap();
+
+        let supervisors = client.discover_supervisors("profiles").await.unwrap();
+        assert_eq!(supervisors, ["supervisor-1:1984", "supervisor-2:1984"]);
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, supervisors);
+
+        // The next request goes straight to a supervisor
+        let ages: Vec<u32> = client.pstate_query("profiles", "$$profiles").key("alice").select().await.unwrap();
+        assert_eq!(ages, [30]);
+        let hosts = script.hosts();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0], Scripted::CONDUCTOR);
+        assert!(supervisors.contains(&hosts[1]), "{:?}", hosts);
+    }
+
+    #[tokio::test]
+    async fn unknown_modules_are_not_found() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [not_found()]);
+        let client = script.client_builder().build().unwrap();
+
+        let err = client.discover_supervisors("missing").await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::ModuleNotFound { module, body } if module == "missing" && body == "no such module"), "{:?}", err);
+        assert!(client.export_supervisor_cache().modules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn conductor_only_routing_caches_nothing() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        let client = script.client_builder().routing_mode(crate::RoutingMode::ConductorOnly { redirects: crate::ConductorRedirects::Reject }).build().unwrap();
+
+        assert_eq!(client.discover_supervisors("profiles").await.unwrap(), ["supervisor-1:1984"]);
+        assert!(client.export_supervisor_cache().modules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn warm_up_returns_one_result_per_module_in_order() {
+        let script = Scripted::new();
+        // Answered in the order the probes are sent, which is the order of the modules
+        script.script(Scripted::CONDUCTOR, [
+            Reply::redirect("supervisor-1:1984"),
+            not_found(),
+            Reply::ok(json!({})),
+            Reply::Status(StatusCode::SERVICE_UNAVAILABLE, vec![], String::new()),
+        ]);
+        let client = script.client_builder().build().unwrap();
+
+        let results = client.warm_up(&["profiles", "missing", "local", "down"]).await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap(), &["supervisor-1:1984"]);
+        assert!(matches!(results[1].as_ref().unwrap_err().kind(), ClientError::ModuleNotFound { module, .. } if module == "missing"), "{:?}", results[1]);
+        // The conductor serves `local` itself
+        assert!(results[2].as_ref().unwrap().is_empty());
+        assert!(matches!(results[3].as_ref().unwrap_err().kind(), ClientError::UnexpectedStatus(StatusCode::SERVICE_UNAVAILABLE, _)), "{:?}", results[3]);
+        assert_eq!(client.export_supervisor_cache().modules.keys().collect::<Vec<_>>(), ["profiles"]);
+    }
+}
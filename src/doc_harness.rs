@@ -0,0 +1,30 @@
+//! The fixture the crate's doc examples run against, so `cargo test --doc --features
+//! test-util` drives real request/response cycles (including the conductor's 308 to a
+//! supervisor) instead of needing a running cluster.
+//!
+//! `profiles` returns a `FakeCluster` holding one module, `ProfilesModule`, with:
+//! - the PState `$$profiles`: user ID -> profile (`name`, `age`, `tags`), for `alice` and `bob`;
+//! - the depot `*profileEdits`, whose `profiles` streaming topology acks each append with
+//!   `true`;
+//! - the query topology `getProfile`, which always answers with alice's profile.
+
+use crate::testing::FakeCluster;
+use serde_json::json;
+
+/// Name of the fixture's module.
+pub const MODULE: &str = "ProfilesModule";
+
+/// A fake cluster holding the fixture module, with one supervisor.
+pub fn profiles() -> FakeCluster {
+    let alice = json!({"name": "Alice", "age": 30, "tags": ["admin", "beta"]});
+    let cluster = FakeCluster::new();
+    cluster
+        .pstate(MODULE, "$$profiles", json!({
+            "alice": alice,
+            "bob": {"name": "Bob", "age": 25, "tags": []},
+        }))
+        .depot(MODULE, "*profileEdits")
+        .ack_return(MODULE, "*profileEdits", "profiles", true)
+        .respond(MODULE, "query/getProfile/invoke", alice);
+    cluster
+}
@@ -0,0 +1,106 @@
+use crate::latency::host_key;
+use crate::logging::{debug, warn};
+use crate::rt::Instant;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+/// When a supervisor is avoided. See `ClientBuilder::supervisor_health`.
+///
+/// After `failure_threshold` consecutive failed requests (transport errors or 5xx) a
+/// supervisor is skipped when picking from the cache. Once `cooldown` has passed since its
+/// last failure it is tried again; a success clears its record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for HealthPolicy {
+    /// 5 consecutive failures, 30 second cool-down.
+    fn default() -> Self {
+        Self { failure_threshold: 5, cooldown: Duration::from_secs(30) }
+    }
+}
+
+/// One supervisor's health, as returned by `Client::supervisor_health`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SupervisorHealth {
+    /// `host:port`
+    pub supervisor: String,
+    pub consecutive_failures: u32,
+    /// Time since the last failure.
+    pub since_last_failure: Duration,
+    /// False once `failure_threshold` is reached; the supervisor is then skipped until its
+    /// cool-down passes.
+    pub healthy: bool,
+}
+
+// Recent failures of one host. Hosts without failures have no entry.
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    consecutive: u32,
+    last: Instant,
+}
+
+// Per-supervisor failure counts, shared by all clones of a client.
+// Keyed by `host:port`, like `LatencyTracker`.
+#[derive(Debug, Default)]
+pub(crate) struct HealthTracker {
+    policy: HealthPolicy,
+    hosts: Mutex<HashMap<String, Failures>>,
+}
+
+impl HealthTracker {
+    pub(crate) fn new(policy: HealthPolicy) -> Self {
+        Self { policy, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    pub(crate) fn record_success(&self, url: &Url) {
+        if self.hosts.lock().unwrap().remove(&host_key(url)).is_some() {
+            debug!("Supervisor {} succeeded; clearing its failures", host_key(url));
+        }
+    }
+
+    pub(crate) fn record_failure(&self, url: &Url) {
+        let key = host_key(url);
+        let mut hosts = self.hosts.lock().unwrap();
+        let failures = hosts.entry(key.clone()).or_insert(Failures { consecutive: 0, last: Instant::now() });
+        failures.consecutive += 1;
+        failures.last = Instant::now();
+        if failures.consecutive == self.policy.failure_threshold {
+            warn!("Supervisor {} failed {} times in a row; avoiding it for {:?}", key, failures.consecutive, self.policy.cooldown);
+        }
+    }
+
+    // Whether requests may go to `host_port`: it's healthy, or its cool-down has passed.
+    pub(crate) fn is_available(&self, host_port: &str) -> bool {
+        match self.hosts.lock().unwrap().get(host_port) {
+            Some(failures) => self.healthy(failures) || failures.last.elapsed() >= self.policy.cooldown,
+            None => true,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<SupervisorHealth> {
+        let mut health: Vec<SupervisorHealth> = self
+            .hosts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(host, failures)| SupervisorHealth {
+                supervisor: host.clone(),
+                consecutive_failures: failures.consecutive,
+                since_last_failure: failures.last.elapsed(),
+                healthy: self.healthy(failures),
+            })
+            .collect();
+        health.sort_by(|a, b| a.supervisor.cmp(&b.supervisor));
+        health
+    }
+
+    fn healthy(&self, failures: &Failures) -> bool {
+        failures.consecutive < self.policy.failure_threshold
+    }
+}
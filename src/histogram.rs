@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets. A final bucket holds everything slower.
+pub const LATENCY_BUCKETS: [Duration; 6] = [
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(25),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(2),
+];
+
+/// Latencies of one module's logical requests (redirects and retries included), from
+/// `Client::latency_snapshot`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// Requests per bucket: `counts[i]` took at most `LATENCY_BUCKETS[i]` (and more than
+    /// the bucket before); the last entry counts requests slower than every bound.
+    pub counts: [u64; LATENCY_BUCKETS.len() + 1],
+    /// Total time of all requests counted.
+    pub sum: Duration,
+}
+
+impl Histogram {
+    /// Number of requests counted.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Each bucket's upper bound (None for the last, unbounded one) and count.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS.iter().copied().map(Some).chain([None]).zip(self.counts.iter().copied())
+    }
+}
+
+// One module's counters. Updated with relaxed atomics: a snapshot taken mid-update may be
+// off by the request being recorded, which is fine for statistics.
+#[derive(Debug, Default)]
+struct AtomicHistogram {
+    counts: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    sum_nanos: AtomicU64,
+}
+
+impl AtomicHistogram {
+    fn record(&self, elapsed: Duration) {
+        let bucket = LATENCY_BUCKETS.iter().position(|bound| elapsed <= *bound).unwrap_or(LATENCY_BUCKETS.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_nanos.fetch_add(u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Histogram {
+        Histogram {
+            counts: std::array::from_fn(|i| self.counts[i].load(Ordering::Relaxed)),
+            sum: Duration::from_nanos(self.sum_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+// Per-module latency histograms, shared by all clones of a client.
+// The map is only write-locked the first time a module is seen; every other request
+// takes the read lock and bumps atomics.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyHistograms {
+    modules: RwLock<HashMap<String, AtomicHistogram>>,
+}
+
+impl LatencyHistograms {
+    pub(crate) fn record(&self, module: &str, elapsed: Duration) {
+        if let Some(histogram) = self.modules.read().unwrap().get(module) {
+            histogram.record(elapsed);
+            return;
+        }
+        self.modules.write().unwrap().entry(module.to_string()).or_default().record(elapsed);
+    }
+
+    pub(crate) fn snapshot(&self) -> HashMap<String, Histogram> {
+        self.modules
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(module, histogram)| (module.clone(), histogram.snapshot()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeCluster;
+    use serde_json::json;
+
+    #[test]
+    fn bounds_are_inclusive_and_the_last_bucket_is_unbounded() {
+        let histograms = LatencyHistograms::default();
+        for millis in [0, 1, 2, 5, 25, 26, 100, 500, 2000, 2001, 60_000] {
+            histograms.record("profiles", Duration::from_millis(millis));
+        }
+        histograms.record("orders", Duration::from_millis(3));
+
+        let snapshot = histograms.snapshot();
+        assert_eq!(snapshot["profiles"].counts, [2, 2, 1, 2, 1, 1, 2]);
+        assert_eq!(snapshot["profiles"].count(), 11);
+        assert_eq!(snapshot["profiles"].sum, Duration::from_millis(64_660));
+        assert_eq!(snapshot["orders"].counts, [0, 1, 0, 0, 0, 0, 0]);
+        let buckets: Vec<_> = snapshot["orders"].buckets().collect();
+        assert_eq!(buckets[1], (Some(Duration::from_millis(5)), 1));
+        assert_eq!(buckets[6], (None, 0));
+    }
+
+    #[tokio::test]
+    async fn requests_land_in_the_bucket_of_their_delay() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        cluster.pstate("orders", "$$orders", json!({"1": "open"}));
+        let client = cluster.client_builder().record_latency_histograms().build().unwrap();
+        let select = |module: &'static str, pstate: &'static str, key: &'static str| {
+            let client = client.clone();
+            async move { client.pstate_query(module, pstate).key(key).select::<serde_json::Value>().await.unwrap() }
+        };
+
+        for _ in 0..2 {
+            select("orders", "$$orders", "1").await;
+        }
+        // Only the supervisor is slow, so each request waits once, redirected or not
+        cluster.latency("fake-supervisor-1:1984", Duration::from_millis(40));
+        for _ in 0..2 {
+            select("profiles", "$$profiles", "alice").await;
+        }
+        cluster.latency("fake-supervisor-1:1984", Duration::from_millis(200));
+        select("profiles", "$$profiles", "alice").await;
+
+        let snapshot = client.latency_snapshot();
+        assert_eq!(snapshot.len(), 2);
+        // Undelayed requests stay under 25ms; the exact bucket depends on the machine
+        assert_eq!(snapshot["orders"].counts[..3].iter().sum::<u64>(), 2);
+        assert_eq!(snapshot["orders"].count(), 2);
+        assert_eq!(snapshot["profiles"].counts, [0, 0, 0, 2, 1, 0, 0]);
+        assert!(snapshot["profiles"].sum >= Duration::from_millis(280), "{:?}", snapshot["profiles"].sum);
+    }
+
+    #[tokio::test]
+    async fn default_clients_record_nothing() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let client = cluster.client();
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<u32>().await.unwrap();
+        assert!(client.latency_snapshot().is_empty());
+    }
+}
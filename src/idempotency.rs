@@ -0,0 +1,110 @@
+use crate::ClientError;
+use serde_json::{Map, Value};
+
+// Where `DepotAppendBuilder::idempotency_key` puts the key unless told otherwise.
+pub(crate) const DEFAULT_POINTER: &str = "/idempotencyKey";
+
+// Sets `key` at JSON pointer `pointer` in `data`, creating missing objects on the way.
+// Unlike `Value::pointer_mut`, the target needn't exist yet; arrays aren't traversed.
+pub(crate) fn inject(data: &mut Value, pointer: &str, key: Value) -> Result<(), ClientError> {
+    let invalid = || ClientError::InvalidIdempotencyKeyPointer(pointer.to_string());
+    // Guard: Not a pointer to a field (the empty pointer would replace the whole record)
+    let Some(path) = pointer.strip_prefix('/') else {
+        return Err(invalid());
+    };
+    let tokens: Vec<String> = path.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect();
+    let (field, parents) = tokens.split_last().ok_or_else(invalid)?;
+    let mut target = data;
+    for token in parents {
+        let Value::Object(map) = target else {
+            return Err(invalid());
+        };
+        target = map.entry(token.as_str()).or_insert_with(|| Value::Object(Map::new()));
+    }
+    let Value::Object(map) = target else {
+        return Err(invalid());
+    };
+    map.insert(field.clone(), key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inject;
+    use crate::testing::{Reply, Scripted};
+    use crate::ClientError;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn sets_the_key_at_the_pointer() {
+        let table = [
+            ("/idempotencyKey", json!({"n": 1, "idempotencyKey": "k"})),
+            ("/meta/dedupeId", json!({"n": 1, "meta": {"dedupeId": "k"}})),
+            ("/a~1b/c~0d", json!({"n": 1, "a/b": {"c~d": "k"}})),
+            ("/n", json!({"n": "k"})),
+        ];
+        for (pointer, expected) in table {
+            let mut data = json!({"n": 1});
+            inject(&mut data, pointer, json!("k")).unwrap();
+            assert_eq!(data, expected, "{}", pointer);
+        }
+    }
+
+    #[test]
+    fn rejects_pointers_that_dont_lead_to_a_field() {
+        for (data, pointer) in [(json!({"n": 1}), ""), (json!({"n": 1}), "idempotencyKey"), (json!({"n": 1}), "/n/key"), (json!([1]), "/0"), (json!("text"), "/key")] {
+            let mut data = data;
+            let err = inject(&mut data, pointer, json!("k")).unwrap_err();
+            assert!(matches!(&err, ClientError::InvalidIdempotencyKeyPointer(p) if p == pointer), "{:?}", err);
+        }
+    }
+
+    // A conductor that turns the first append away with a 503, then accepts it
+    fn busy_once() -> std::sync::Arc<Scripted> {
+        let script = Scripted::new();
+        let unavailable = Reply::Status(StatusCode::SERVICE_UNAVAILABLE, vec![("retry-after", "0".to_string())], String::new());
+        script.script(Scripted::CONDUCTOR, [unavailable, Reply::ok(json!({}))]);
+        script
+    }
+
+    fn sent(script: &Scripted) -> Vec<Value> {
+        script.bodies().iter().map(|body| serde_json::from_slice(body).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn a_retried_append_resends_the_same_key() {
+        let script = busy_once();
+        let client = script.client_builder().build().unwrap();
+        client
+            .depot_append("profiles", "*edits", json!({"n": 1}))
+            .idempotency_key("edit-7")
+            .idempotency_key_pointer("/meta/dedupeId")
+            .append::<Value>()
+            .await
+            .unwrap();
+
+        let sent = sent(&script);
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], sent[1]);
+        assert_eq!(sent[0]["data"], json!({"n": 1, "meta": {"dedupeId": "edit-7"}}));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[tokio::test]
+    async fn an_auto_key_is_chosen_once_per_append() {
+        let script = busy_once();
+        let client = script.client_builder().build().unwrap();
+        let append = client.depot_append("profiles", "*edits", json!({"n": 1})).auto_idempotency_key();
+        let key = append.body_json().unwrap()["data"]["idempotencyKey"].clone();
+        assert!(uuid::Uuid::parse_str(key.as_str().unwrap()).is_ok(), "{}", key);
+        append.append::<Value>().await.unwrap();
+
+        let sent = sent(&script);
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|body| body["data"]["idempotencyKey"] == key), "{:?}", sent);
+        // Another append gets another key
+        let other = client.depot_append("profiles", "*edits", json!({"n": 1})).auto_idempotency_key().body_json().unwrap();
+        assert_ne!(other["data"]["idempotencyKey"], key);
+    }
+}
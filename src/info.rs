@@ -0,0 +1,125 @@
+use crate::logging::debug;
+use crate::redact::Redacted;
+use crate::{parse_supervisor_locations, Client, ClientError};
+use reqwest::StatusCode;
+use std::collections::BTreeMap;
+use url::Url;
+
+/// What the conductor reports about one module. See `Client::module_info`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ModuleInfo {
+    pub module: String,
+    /// Whether the module is deployed (the conductor didn't answer 404).
+    pub deployed: bool,
+    /// Supervisors (`host:port`) serving the module, if the conductor redirected the probe.
+    pub supervisors: Vec<String>,
+}
+
+/// The cluster as this client's supervisor cache sees it. See `Client::cached_topology`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct CachedTopology {
+    /// The conductor URL in use.
+    pub conductor: Url,
+    /// Modules this client has routed requests to, with their cached supervisors.
+    pub modules: BTreeMap<String, Vec<String>>,
+}
+
+impl Client {
+    /// Asks the conductor whether `module` is deployed, and which supervisors serve it.
+    ///
+    /// The REST API has no metadata endpoints, so this probes the module's REST root with a
+    /// GET, sent to the conductor directly (the supervisor cache is neither used nor
+    /// updated). 404 means not deployed; a 308 carries the supervisors. Auth failures and
+    /// server errors are returned as `ClientError::UnexpectedStatus`.
+    pub async fn module_info(&self, module: &str) -> Result<ModuleInfo, ClientError> {
+        let url = self.build_url(module, "")?;
+        let response = self.get(url.clone()).await?;
+        let status = response.status;
+        debug!("Module probe for '{}' answered {}", module, status);
+
+        // Guard: Credentials rejected or server failing; deployment state unknown
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN || status.is_server_error() {
+            return Err(ClientError::UnexpectedStatus(status, Redacted(&url).to_string()));
+        }
+
+        let supervisors = match status {
+            StatusCode::PERMANENT_REDIRECT => parse_supervisor_locations(&response.headers, &url)?,
+            _ => Vec::new(),
+        };
+        Ok(ModuleInfo { module: module.to_string(), deployed: status != StatusCode::NOT_FOUND, supervisors })
+    }
+
+    /// What the client has learned about the cluster so far, from local state only: no
+    /// request is sent, so this says nothing about whether the cluster is up (see `ping`)
+    /// or what else is deployed (see `module_info`). The REST API has no cluster-wide
+    /// endpoints, so `modules` only lists modules this client (or a clone) has routed to,
+    /// including expired cache entries.
+    pub fn cached_topology(&self) -> CachedTopology {
+        let modules = self
+            .supervisor_cache
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(module, entry)| (module.clone(), entry.supervisors.clone()))
+            .collect();
+        CachedTopology { conductor: self.base_url().clone(), modules }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{FakeCluster, Reply, Scripted};
+    use crate::ClientError;
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new().with_supervisors(&["fake-supervisor-1:1984", "fake-supervisor-2:1984"]);
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        cluster
+    }
+
+    #[tokio::test]
+    async fn module_info_reports_deployment_and_supervisors() {
+        let cluster = cluster();
+        let client = cluster.client();
+
+        let info = client.module_info("profiles").await.unwrap();
+        assert!(info.deployed);
+        assert_eq!(info.supervisors, ["fake-supervisor-1:1984", "fake-supervisor-2:1984"]);
+        let missing = client.module_info("nothing").await.unwrap();
+        assert_eq!((missing.deployed, missing.supervisors.len()), (false, 0));
+
+        // Sent to the conductor only, without filling the cache
+        assert!(cluster.requests().iter().all(|r| r.url.host_str() == Some("fake-conductor")));
+        assert!(client.cached_topology().modules.is_empty());
+    }
+
+    #[tokio::test]
+    async fn module_info_surfaces_auth_and_server_failures() {
+        for status in [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN, StatusCode::SERVICE_UNAVAILABLE] {
+            let script = Scripted::new();
+            script.script(Scripted::CONDUCTOR, [Reply::Status(status, vec![], String::new())]);
+            let client = script.client_builder().build().unwrap();
+            let err = client.module_info("profiles").await.unwrap_err();
+            assert!(matches!(err.kind(), ClientError::UnexpectedStatus(s, _) if s == &status), "{:?}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_topology_lists_routed_modules() {
+        let cluster = cluster();
+        let client = cluster.client();
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+
+        let sent = cluster.requests().len();
+        let topology = client.cached_topology();
+        assert_eq!(topology.conductor.host_str(), Some("fake-conductor"));
+        let modules: Vec<_> = topology.modules.into_iter().collect();
+        assert_eq!(modules, [("profiles".to_string(), vec!["fake-supervisor-1:1984".to_string(), "fake-supervisor-2:1984".to_string()])]);
+        // Local state only
+        assert_eq!(cluster.requests().len(), sent);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::ClientError;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::Arc;
+
+/// Sees (and may modify) every HTTP request a client sends, e.g. to add correlation IDs,
+/// sign requests, or log payloads.
+///
+/// Called for every attempt of a logical request, including redirects and retries, just
+/// before it is sent. Interceptors run in registration order; an error aborts the request.
+pub trait RequestInterceptor: Send + Sync {
+    fn intercept(&self, request: &mut reqwest::Request, ctx: &RequestContext) -> Result<(), ClientError>;
+
+    /// Observes the status and headers of each response. Does nothing by default.
+    fn on_response(&self, _status: StatusCode, _headers: &HeaderMap, _ctx: &RequestContext) {}
+}
+
+/// Which request an interceptor is looking at.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestContext {
+    pub module: String,
+    /// The path under `/rest/<module>/`, e.g. `pstate/$$profiles/select`.
+    pub path_suffix: String,
+    /// 1-based attempt number within the logical request, across redirects and retries.
+    pub attempt: u32,
+    /// ID of the logical request, shared by all of its attempts.
+    pub request_id: String,
+}
+
+// Registered interceptors in order, with a Debug impl for `Client`.
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn RequestInterceptor>>);
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: Arc<dyn RequestInterceptor>) {
+        self.0.push(interceptor);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn intercept(&self, request: &mut reqwest::Request, ctx: &RequestContext) -> Result<(), ClientError> {
+        self.0.iter().try_for_each(|interceptor| interceptor.intercept(request, ctx))
+    }
+
+    pub(crate) fn on_response(&self, status: StatusCode, headers: &HeaderMap, ctx: &RequestContext) {
+        for interceptor in &self.0 {
+            interceptor.on_response(status, headers, ctx);
+        }
+    }
+}
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
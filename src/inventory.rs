@@ -0,0 +1,173 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::budget::{BudgetTracker, Subsystem};
+use crate::logging::{error, info, warn};
+
+// Placeholder for elided literal values in path shapes.
+const ELIDED: &str = "?";
+
+/// One distinct kind of call observed at runtime.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CallRecord {
+    pub module: String,
+    /// PState or depot name.
+    pub object: String,
+    /// e.g. "select", "selectOne", "append".
+    pub operation: String,
+    /// The query path with literal values replaced by `"?"`, as compact JSON.
+    /// Navigator ops and function references are kept. None for calls without a path.
+    pub path_shape: Option<String>,
+}
+
+/// Report of every distinct call a client has made, sorted and de-duplicated.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallInventory {
+    pub calls: Vec<CallRecord>,
+    /// True if new calls stopped being recorded because the memory budget was exceeded.
+    pub truncated: bool,
+}
+
+// Collects call records for a client and all of its clones.
+#[derive(Debug)]
+pub(crate) struct InventoryCollector {
+    calls: Mutex<BTreeSet<CallRecord>>,
+    // Where to write the report when the last client clone is dropped.
+    output: Option<PathBuf>,
+    budget: Arc<BudgetTracker>,
+    // Set once new records start being dropped for exceeding the memory budget.
+    truncated: AtomicBool,
+}
+
+impl InventoryCollector {
+    pub(crate) fn new(output: Option<PathBuf>, budget: Arc<BudgetTracker>) -> Self {
+        Self { calls: Mutex::default(), output, budget, truncated: AtomicBool::new(false) }
+    }
+
+    pub(crate) fn record(&self, module: &str, object: &str, operation: &str, path: Option<&[Value]>) {
+        let record = CallRecord {
+            module: module.to_string(),
+            object: object.to_string(),
+            operation: operation.to_string(),
+            path_shape: path.map(|p| Value::Array(p.iter().map(path_shape).collect()).to_string()),
+        };
+        let mut calls = self.calls.lock().unwrap();
+
+        // Guard: Already recorded
+        if calls.contains(&record) {
+            return;
+        }
+
+        // Guard: Over the memory budget; keep what we have rather than evicting
+        if self.budget.over_budget() {
+            if !self.truncated.swap(true, Ordering::Relaxed) {
+                warn!("Memory budget exceeded; call inventory will not record new entries");
+            }
+            return;
+        }
+
+        self.budget.add(Subsystem::Inventory, record_size(&record));
+        calls.insert(record);
+    }
+
+    pub(crate) fn snapshot(&self) -> CallInventory {
+        CallInventory {
+            calls: self.calls.lock().unwrap().iter().cloned().collect(),
+            truncated: self.truncated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for InventoryCollector {
+    fn drop(&mut self) {
+        // Guard: No output file configured
+        let Some(output) = &self.output else { return };
+
+        let result = serde_json::to_vec_pretty(&self.snapshot())
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| std::fs::write(output, bytes));
+        match result {
+            Ok(()) => info!("Wrote call inventory to {}", output.display()),
+            Err(e) => error!("Failed to write call inventory to {}: {}", output.display(), e),
+        }
+    }
+}
+
+// Replaces literal values in a navigator with placeholders, keeping navigator structure.
+fn path_shape(nav: &Value) -> Value {
+    match nav {
+        // Explicit navigator: keep the op, shape the arguments (which may be sub-paths).
+        Value::Array(items) => match items.split_first() {
+            Some((op @ Value::String(_), args)) => {
+                let mut shaped = vec![op.clone()];
+                shaped.extend(args.iter().map(path_shape));
+                Value::Array(shaped)
+            }
+            _ => Value::Array(items.iter().map(path_shape).collect()),
+        },
+        // Function references are code, not data.
+        Value::String(s) if s.starts_with("#__f") => nav.clone(),
+        _ => Value::String(ELIDED.to_string()),
+    }
+}
+
+fn record_size(record: &CallRecord) -> usize {
+    std::mem::size_of::<CallRecord>()
+        + record.module.len()
+        + record.object.len()
+        + record.operation.len()
+        + record.path_shape.as_ref().map_or(0, String::len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::FakeCluster;
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn records_distinct_calls_with_literals_elided() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": {"tags": ["a"]}, "bob": {"tags": []}}));
+        cluster.depot("profiles", "*edits");
+        let client = cluster.client_builder().record_inventory().build().unwrap();
+        let profiles = || client.pstate_query("profiles", "$$profiles");
+
+        profiles().key("alice").select::<Value>().await.unwrap();
+        profiles().key("bob").select::<Value>().await.unwrap();
+        profiles().key("alice").key("tags").all().select::<Value>().await.unwrap();
+        profiles().key("bob").key("tags").all().select::<Value>().await.unwrap();
+        profiles().key("bob").select_one::<Value>().await.unwrap();
+        // Unsupported by the fake, but recorded all the same
+        let _ = profiles().nav(json!(["filterPred", "#__fOps.IS_EVEN"])).select::<Value>().await;
+        client.depot_append("profiles", "*edits", json!({"id": "alice"})).append::<Value>().await.unwrap();
+        client.depot_append("profiles", "*edits", json!({"id": "bob"})).append::<Value>().await.unwrap();
+
+        let inventory = client.inventory().unwrap();
+        assert!(!inventory.truncated);
+        let call = |object: &str, operation: &str, path_shape: Option<&str>| {
+            json!({"module": "profiles", "object": object, "operation": operation, "path_shape": path_shape})
+        };
+        assert_eq!(
+            serde_json::to_value(&inventory.calls).unwrap(),
+            json!([
+                call("$$profiles", "select", Some(r#"["?","?",["all"]]"#)),
+                call("$$profiles", "select", Some(r#"["?"]"#)),
+                call("$$profiles", "select", Some(r##"[["filterPred","#__fOps.IS_EVEN"]]"##)),
+                call("$$profiles", "selectOne", Some(r#"["?"]"#)),
+                call("*edits", "append", None),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn records_nothing_unless_enabled() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let client = cluster.client();
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        assert!(client.inventory().is_none());
+    }
+}
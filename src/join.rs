@@ -0,0 +1,198 @@
+use crate::builder::{pstate_name, PreparedQuery};
+use crate::canonical::CanonicalPath;
+use crate::logging::{debug, warn};
+use crate::{Client, ClientError};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+// Marks where the join key goes in a `JoinTarget` path template.
+const JOIN_KEY_PLACEHOLDER: &str = "#__joinKey";
+
+/// Placeholder for the join key in a `JoinTarget` path template.
+///
+/// It may appear anywhere in the template, including inside explicit navigators,
+/// e.g. `vec![join_key(), "name".into()]` or `vec![json!(["must", join_key()])]`.
+pub fn join_key() -> Value {
+    Value::String(JOIN_KEY_PLACEHOLDER.to_string())
+}
+
+/// The PState each item of a join is looked up in.
+#[derive(Debug, Clone)]
+pub struct JoinTarget {
+    module: String,
+    pstate: String,
+    template: Vec<Value>,
+}
+
+impl JoinTarget {
+    /// `template` is the lookup path, containing `join_key()` where the key belongs.
+    pub fn new(module: &str, pstate: &str, template: Vec<Value>) -> Self {
+        Self { module: module.to_string(), pstate: pstate_name(pstate), template }
+    }
+
+    // The template with every placeholder replaced by `key`.
+    fn path_for(&self, key: &Value) -> Vec<Value> {
+        self.template.iter().map(|nav| substitute(nav, key)).collect()
+    }
+}
+
+/// What a join does with items whose lookup found nothing (`null`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingPolicy {
+    /// Keep the item, paired with `None`. (Default)
+    #[default]
+    Keep,
+    /// Leave the item out of the result.
+    Drop,
+    /// Fail the join with `ClientError::MissingJoinValue`.
+    Error,
+}
+
+/// Options for `Client::join`.
+#[derive(Debug, Clone)]
+pub struct JoinOptions {
+    /// Maximum lookups in flight at once.
+    pub parallelism: usize,
+    pub missing: MissingPolicy,
+    /// Fails the join with `ClientError::JoinFanOutExceeded` (before any lookup) if the
+    /// first query returns more items than this.
+    pub max_fan_out: usize,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        Self { parallelism: 16, missing: MissingPolicy::default(), max_fan_out: 1000 }
+    }
+}
+
+impl Client {
+    /// Selects a list with `first`, then looks up each item in `second` by the key
+    /// `key_fn` derives from it ("select the follower IDs, then each follower's profile").
+    ///
+    /// `first` must be prepared with `prepare()` (a `select`). Lookups use `selectOne`
+    /// and run concurrently up to `opts.parallelism`; results keep the order of `first`.
+    pub async fn join<A, B>(
+        &self,
+        first: PreparedQuery,
+        key_fn: impl Fn(&A) -> Value,
+        second: JoinTarget,
+        opts: JoinOptions,
+    ) -> Result<Vec<(A, Option<B>)>, ClientError>
+    where
+        A: DeserializeOwned,
+        B: DeserializeOwned,
+    {
+        let items: Vec<A> = first.execute(self).await?;
+
+        // Guard: Fan-out too large
+        if items.len() > opts.max_fan_out {
+            warn!("Join from '{}' returned {} items, over the fan-out cap of {}", first.pstate(), items.len(), opts.max_fan_out);
+            return Err(ClientError::JoinFanOutExceeded { size: items.len(), max: opts.max_fan_out });
+        }
+        debug!("Joining {} items from '{}' against '{}'", items.len(), first.pstate(), second.pstate);
+
+        let second = &second;
+        let path_suffix = format!("pstate/{}/selectOne", second.pstate);
+        let path_suffix = path_suffix.as_str();
+        let lookups = items.into_iter().map(|item| {
+            let key = key_fn(&item);
+            async move {
+                let path = second.path_for(&key);
+                self.check_path_size(&path)?;
+                self.record_call(&second.module, &second.pstate, "selectOne", Some(&path));
+                let value: Option<B> = self.send_idempotent_request(&second.module, path_suffix, &CanonicalPath(&path), None).await?;
+                Ok::<_, ClientError>((item, key, value))
+            }
+        });
+
+        let joined: Vec<(A, Value, Option<B>)> = stream::iter(lookups)
+            .buffered(opts.parallelism.max(1))
+            .try_collect()
+            .await?;
+
+        let mut result = Vec::with_capacity(joined.len());
+        for (item, key, value) in joined {
+            match (value, opts.missing) {
+                (Some(value), _) => result.push((item, Some(value))),
+                (None, MissingPolicy::Keep) => result.push((item, None)),
+                (None, MissingPolicy::Drop) => {}
+                (None, MissingPolicy::Error) => return Err(ClientError::MissingJoinValue(key.to_string())),
+            }
+        }
+        Ok(result)
+    }
+}
+
+// Replaces the join key placeholder anywhere inside `nav`.
+fn substitute(nav: &Value, key: &Value) -> Value {
+    match nav {
+        Value::String(s) if s == JOIN_KEY_PLACEHOLDER => key.clone(),
+        Value::Array(items) => Value::Array(items.iter().map(|item| substitute(item, key)).collect()),
+        _ => nav.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{join_key, JoinOptions, JoinTarget, MissingPolicy};
+    use crate::testing::FakeCluster;
+    use crate::{Client, ClientError};
+    use serde_json::json;
+
+    // Alice's followers, of whom only bob and dave have profiles.
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster
+            .pstate("social", "$$followers", json!({"alice": ["bob", "carol", "dave"]}))
+            .pstate("social", "$$profiles", json!({"bob": {"name": "Bob"}, "dave": {"name": "Dave"}}));
+        cluster
+    }
+
+    async fn followers_names(client: &Client, opts: JoinOptions) -> Result<Vec<(String, Option<String>)>, ClientError> {
+        let first = client.pstate_query("social", "$$followers").key("alice").all().prepare();
+        let second = JoinTarget::new("social", "$$profiles", vec![join_key(), json!("name")]);
+        client.join(first, |id: &String| json!(id), second, opts).await
+    }
+
+    fn missing(missing: MissingPolicy) -> JoinOptions {
+        JoinOptions { missing, ..JoinOptions::default() }
+    }
+
+    #[tokio::test]
+    async fn keeps_drops_or_rejects_missing_values() {
+        let cluster = cluster();
+        let client = cluster.client();
+        let name = |s: &str| Some(s.to_string());
+
+        let kept = followers_names(&client, missing(MissingPolicy::Keep)).await.unwrap();
+        assert_eq!(kept, [("bob".to_string(), name("Bob")), ("carol".to_string(), None), ("dave".to_string(), name("Dave"))]);
+
+        let dropped = followers_names(&client, missing(MissingPolicy::Drop)).await.unwrap();
+        assert_eq!(dropped, [("bob".to_string(), name("Bob")), ("dave".to_string(), name("Dave"))]);
+
+        let err = followers_names(&client, missing(MissingPolicy::Error)).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::MissingJoinValue(key) if key == r#""carol""#), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn looks_up_each_key_in_the_template() {
+        let cluster = cluster();
+        let client = cluster.client();
+        followers_names(&client, JoinOptions { parallelism: 1, ..JoinOptions::default() }).await.unwrap();
+
+        let lookups: Vec<_> = cluster.requests().into_iter().filter(|r| r.path_suffix == "pstate/$$profiles/selectOne").map(|r| r.body).collect();
+        assert_eq!(lookups, [json!(["bob", "name"]), json!(["carol", "name"]), json!(["dave", "name"])]);
+    }
+
+    #[tokio::test]
+    async fn fan_out_cap_fails_before_any_lookup() {
+        let cluster = cluster();
+        let client = cluster.client();
+
+        let err = followers_names(&client, JoinOptions { max_fan_out: 2, ..JoinOptions::default() }).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::JoinFanOutExceeded { size: 3, max: 2 }), "{:?}", err);
+        assert!(cluster.requests().iter().all(|r| !r.path_suffix.contains("$$profiles")));
+        assert!(followers_names(&client, JoinOptions { max_fan_out: 3, ..JoinOptions::default() }).await.is_ok());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{ClientError, TransportResponse};
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::{DeserializeOwned, Error as _};
+use std::collections::VecDeque;
+
+// Incrementally splits a top-level JSON array into the raw bytes of its elements, so a large
+// response can be decoded element by element. Only the element being read is buffered.
+#[derive(Debug, Default)]
+struct ArraySplitter {
+    element: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    started: bool,
+    finished: bool,
+}
+
+impl ArraySplitter {
+    // Consumes a chunk, pushing each completed element onto `out`.
+    fn feed(&mut self, chunk: &[u8], out: &mut VecDeque<Vec<u8>>) -> Result<(), ClientError> {
+        for &b in chunk {
+            // --- Outside the array ---
+            if self.finished || !self.started {
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                // Guard: Anything but a single array
+                if self.finished || b != b'[' {
+                    return Err(malformed("expected a single top-level JSON array"));
+                }
+                self.started = true;
+                self.depth = 1;
+                continue;
+            }
+
+            // --- Inside a string: only track where it ends ---
+            if self.in_string {
+                self.element.push(b);
+                if self.escaped {
+                    self.escaped = false;
+                } else if b == b'\\' {
+                    self.escaped = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => {
+                    self.in_string = true;
+                    self.element.push(b);
+                }
+                b'[' | b'{' => {
+                    self.depth += 1;
+                    self.element.push(b);
+                }
+                b']' | b'}' if self.depth == 1 => {
+                    // Closing bracket of the top-level array
+                    self.finished = true;
+                    self.emit(out, false)?;
+                }
+                b']' | b'}' => {
+                    self.depth -= 1;
+                    self.element.push(b);
+                }
+                b',' if self.depth == 1 => self.emit(out, true)?,
+                b if b.is_ascii_whitespace() && self.element.is_empty() => {}
+                b => self.element.push(b),
+            }
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, out: &mut VecDeque<Vec<u8>>, required: bool) -> Result<(), ClientError> {
+        // Guard: Empty element (e.g. "[]" is fine, "[1,,2]" is not)
+        if self.element.is_empty() {
+            return if required { Err(malformed("empty array element")) } else { Ok(()) };
+        }
+        out.push_back(std::mem::take(&mut self.element));
+        Ok(())
+    }
+}
+
+fn malformed(msg: &str) -> ClientError {
+    ClientError::Json(serde_json::Error::custom(msg))
+}
+
+// Decodes each element of the JSON array in `response`'s body as it arrives.
+// A transport or decode error is yielded once and ends the stream.
+pub(crate) fn array_elements<R: DeserializeOwned>(response: TransportResponse) -> impl Stream<Item = Result<R, ClientError>> {
+    let bytes = response.body;
+    let state = Some((bytes, ArraySplitter::default(), VecDeque::<Vec<u8>>::new()));
+    stream::unfold(state, |state| async move {
+        let (mut bytes, mut splitter, mut ready) = state?;
+        loop {
+            if let Some(element) = ready.pop_front() {
+                let item = serde_json::from_slice::<R>(&element).map_err(ClientError::from);
+                let next = item.is_ok().then_some((bytes, splitter, ready));
+                return Some((item, next));
+            }
+            if splitter.finished {
+                return None;
+            }
+            let chunk: Bytes = match bytes.next().await {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => return Some((Err(e), None)),
+                // Guard: Body ended before the array was closed
+                None => return Some((Err(malformed("response ended inside the JSON array")), None)),
+            };
+            if let Err(e) = splitter.feed(&chunk, &mut ready) {
+                return Some((Err(e), None));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{array_elements, ArraySplitter};
+    use crate::testing::{Reply, Scripted};
+    use crate::{ClientError, TransportResponse};
+    use bytes::Bytes;
+    use futures::stream::{self, StreamExt};
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use std::collections::VecDeque;
+
+    fn split(chunks: &[&[u8]]) -> Result<Vec<String>, ClientError> {
+        let mut splitter = ArraySplitter::default();
+        let mut out = VecDeque::new();
+        for chunk in chunks {
+            splitter.feed(chunk, &mut out)?;
+        }
+        Ok(out.into_iter().map(|element| String::from_utf8(element).unwrap()).collect())
+    }
+
+    #[test]
+    fn splits_nested_values_and_strings() {
+        let body = br#" [1, "a,]\"}", {"b": [2, {"c": "]"}]}, [[]], null] "#;
+        let expected = ["1", r#""a,]\"}""#, r#"{"b": [2, {"c": "]"}]}"#, "[[]]", "null"];
+        assert_eq!(split(&[body]).unwrap(), expected);
+        // Byte by byte, the same elements come out
+        let bytes: Vec<&[u8]> = body.chunks(1).collect();
+        assert_eq!(split(&bytes).unwrap(), expected);
+        assert_eq!(split(&[b"[]"]).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejects_anything_but_one_array() {
+        for body in [&b"{}"[..], b"1", b"[1,,2]", b"[1] [2]"] {
+            assert!(split(&[body]).is_err(), "{}", String::from_utf8_lossy(body));
+        }
+    }
+
+    // `[{"n":0,"pad":"xxx…"}, …]` for `count` elements, one chunk per element.
+    fn large_array(count: usize) -> impl Iterator<Item = Bytes> {
+        let element = |n: usize| format!(r#"{}{{"n":{},"pad":"{}"}}"#, if n == 0 { "[" } else { "," }, n, "x".repeat(1000));
+        (0..count).map(move |n| Bytes::from(element(n))).chain(std::iter::once(Bytes::from_static(b"]")))
+    }
+
+    #[test]
+    fn buffers_one_element_at_a_time() {
+        let mut splitter = ArraySplitter::default();
+        let mut out = VecDeque::new();
+        let (mut elements, mut peak) = (0, 0);
+        for chunk in large_array(20_000) {
+            splitter.feed(&chunk, &mut out).unwrap();
+            peak = peak.max(splitter.element.capacity());
+            elements += out.len();
+            out.clear();
+        }
+        assert_eq!(elements, 20_000);
+        // ~20 MB went through; at most about one element was ever held
+        assert!(peak < 4_096, "{}", peak);
+    }
+
+    #[tokio::test]
+    async fn decodes_elements_from_a_streamed_body() {
+        let body = stream::iter(large_array(20_000).map(Ok));
+        let response = TransportResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: Box::pin(body) };
+        let mut count = 0;
+        let mut elements = std::pin::pin!(array_elements::<Value>(response));
+        while let Some(element) = elements.next().await {
+            assert_eq!(element.unwrap()["n"], count);
+            count += 1;
+        }
+        assert_eq!(count, 20_000);
+    }
+
+    #[tokio::test]
+    async fn a_truncated_body_ends_the_stream_with_an_error() {
+        let body = stream::iter([Ok(Bytes::from_static(b"[1, 2, 3"))]);
+        let response = TransportResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: Box::pin(body) };
+        let results: Vec<Result<u32, ClientError>> = array_elements(response).collect().await;
+        assert_eq!(results.len(), 3);
+        assert_eq!((results[0].as_ref().unwrap(), results[1].as_ref().unwrap()), (&1, &2));
+        assert!(results[2].is_err());
+    }
+
+    #[tokio::test]
+    async fn select_stream_follows_the_redirect_first() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        script.script("supervisor-1:1984", [Reply::ok((0..10_000).collect::<Vec<u32>>())]);
+        let client = script.client_builder().build().unwrap();
+
+        let results: Vec<Result<u32, ClientError>> = client.pstate_query("profiles", "$$ids").all().select_stream().collect().await;
+        let ids: Vec<u32> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(ids, (0..10_000).collect::<Vec<_>>());
+        assert_eq!(script.hosts(), [Scripted::CONDUCTOR, "supervisor-1:1984"]);
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::rt::Instant;
+use std::time::Duration;
+use url::Url;
+
+// Weight of a new sample in the moving average.
+const EWMA_ALPHA: f64 = 0.3;
+// Age over which an estimate halves, so a host that was slow a while ago looks fast again
+// and gets re-probed instead of being starved forever.
+const DECAY_HALF_LIFE: Duration = Duration::from_secs(30);
+
+/// How a cached supervisor is chosen for each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionStrategy {
+    /// Uniformly at random. (Default)
+    #[default]
+    Random,
+    /// Power of two choices: pick two supervisors at random and use the one with the lower
+    /// observed latency (an exponentially weighted moving average per host). Hosts with no
+    /// measurements yet count as fastest, so new supervisors get probed.
+    LatencyWeighted,
+}
+
+// Moving average of one host's request latency.
+#[derive(Debug, Clone, Copy)]
+struct Ewma {
+    millis: f64,
+    updated_at: Instant,
+}
+
+// Per-supervisor latency estimates, shared by all clones of a client.
+// Keyed by the `host:port` authority of the request URL.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    hosts: Mutex<HashMap<String, Ewma>>,
+}
+
+impl LatencyTracker {
+    pub(crate) fn record(&self, url: &Url, elapsed: Duration) {
+        let sample = elapsed.as_secs_f64() * 1000.0;
+        let mut hosts = self.hosts.lock().unwrap();
+        let ewma = hosts
+            .entry(host_key(url))
+            .and_modify(|e| e.millis = EWMA_ALPHA * sample + (1.0 - EWMA_ALPHA) * decayed(e))
+            .or_insert(Ewma { millis: sample, updated_at: Instant::now() });
+        ewma.updated_at = Instant::now();
+    }
+
+    // Current estimate in milliseconds, decayed by age. None for unmeasured hosts.
+    pub(crate) fn estimate(&self, url: &Url) -> Option<f64> {
+        self.hosts.lock().unwrap().get(&host_key(url)).map(decayed)
+    }
+
+    // Returns whichever of the two URLs has the lower estimate (unmeasured counts as zero).
+    pub(crate) fn faster<'u>(&self, a: &'u Url, b: &'u Url) -> &'u Url {
+        if self.estimate(b).unwrap_or(0.0) < self.estimate(a).unwrap_or(0.0) {
+            b
+        } else {
+            a
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<(String, Duration)> {
+        let mut estimates: Vec<(String, Duration)> = self
+            .hosts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(host, e)| (host.clone(), Duration::from_secs_f64(decayed(e) / 1000.0)))
+            .collect();
+        estimates.sort();
+        estimates
+    }
+}
+
+fn decayed(e: &Ewma) -> f64 {
+    let half_lives = e.updated_at.elapsed().as_secs_f64() / DECAY_HALF_LIFE.as_secs_f64();
+    e.millis * 0.5f64.powf(half_lives)
+}
+
+pub(crate) fn host_key(url: &Url) -> String {
+    format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ewma, LatencyTracker, SelectionStrategy, DECAY_HALF_LIFE};
+    use crate::rt::Instant;
+    use crate::testing::FakeCluster;
+    use serde_json::json;
+    use std::time::Duration;
+    use url::Url;
+
+    fn url(host: &str) -> Url {
+        Url::parse(&format!("http://{}:1984/rest", host)).unwrap()
+    }
+
+    // A tracker that measured `millis` for each host, `ago` before now.
+    fn measured(samples: &[(&str, f64, Duration)]) -> LatencyTracker {
+        let tracker = LatencyTracker::default();
+        let mut hosts = tracker.hosts.lock().unwrap();
+        for (host, millis, ago) in samples {
+            hosts.insert(format!("{}:1984", host), Ewma { millis: *millis, updated_at: Instant::now() - *ago });
+        }
+        drop(hosts);
+        tracker
+    }
+
+    #[test]
+    fn moving_average_weighs_new_samples() {
+        let tracker = LatencyTracker::default();
+        tracker.record(&url("a"), Duration::from_millis(100));
+        tracker.record(&url("a"), Duration::from_millis(200));
+        let estimate = tracker.estimate(&url("a")).unwrap();
+        assert!((estimate - 130.0).abs() < 0.1, "{}", estimate);
+        assert_eq!(tracker.estimate(&url("b")), None);
+    }
+
+    #[test]
+    fn unmeasured_hosts_count_as_fastest() {
+        let tracker = measured(&[("slow", 50.0, Duration::ZERO)]);
+        assert_eq!(tracker.faster(&url("slow"), &url("new")), &url("new"));
+        assert_eq!(tracker.faster(&url("new"), &url("slow")), &url("new"));
+    }
+
+    #[test]
+    fn old_measurements_decay_so_a_recovered_host_is_probed_again() {
+        let tracker = measured(&[("slow", 100.0, DECAY_HALF_LIFE * 2), ("fast", 30.0, Duration::ZERO)]);
+        let estimate = tracker.estimate(&url("slow")).unwrap();
+        assert!((estimate - 25.0).abs() < 0.1, "{}", estimate);
+        assert_eq!(tracker.faster(&url("fast"), &url("slow")), &url("slow"));
+    }
+
+    #[tokio::test]
+    async fn traffic_skews_toward_the_faster_supervisor() {
+        let cluster = FakeCluster::new().with_supervisors(&["fake-supervisor-1:1984", "fake-supervisor-2:1984"]);
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        cluster.latency("fake-supervisor-1:1984", Duration::from_millis(30));
+        let client = cluster.client_builder().selection_strategy(SelectionStrategy::LatencyWeighted).build().unwrap();
+
+        for _ in 0..20 {
+            client.pstate_query("profiles", "$$profiles").key("alice").select::<u32>().await.unwrap();
+        }
+        let count = |host: &str| cluster.requests().iter().filter(|r| r.url.host_str() == Some(host)).count();
+        // The slow supervisor is probed while unmeasured, then loses every comparison
+        let slow = count("fake-supervisor-1");
+        assert!((1..=2).contains(&slow), "{}", slow);
+        assert_eq!(slow + count("fake-supervisor-2"), 20);
+        let latencies = client.supervisor_latencies();
+        let estimate = |host: &str| latencies.iter().find(|(h, _)| h == host).map(|(_, latency)| *latency);
+        assert!(estimate("fake-supervisor-1:1984") > estimate("fake-supervisor-2:1984"), "{:?}", latencies);
+    }
+}
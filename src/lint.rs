@@ -0,0 +1,233 @@
+use crate::Path;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::ops::Range;
+
+// --- Lint Types ---
+
+/// The kinds of findings `analyze_path` can report.
+///
+/// Each kind has a stable identifier (see `as_str`) so review tooling can allow-list findings.
+/// New kinds may be added in future versions.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathLintKind {
+    /// A `filterPred` follows `all()` on a collection that may be unbounded.
+    FilterPredAfterAll,
+    /// A `must` navigator lists the same key more than once.
+    DuplicateMustKeys,
+    /// A `sortedMapRangeFrom` navigator has no max count, so it may return the rest of the map.
+    SortedMapRangeFromWithoutMaxCount,
+}
+
+impl PathLintKind {
+    /// Stable identifier for this lint, suitable for allow-lists.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathLintKind::FilterPredAfterAll => "filter-pred-after-all",
+            PathLintKind::DuplicateMustKeys => "duplicate-must-keys",
+            PathLintKind::SortedMapRangeFromWithoutMaxCount => "sorted-map-range-from-without-max-count",
+        }
+    }
+}
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintSeverity {
+    /// Worth knowing about, but often intentional.
+    Info,
+    /// Likely to cause slow queries or surprising results.
+    Warning,
+}
+
+/// A single advisory finding about a query path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathLint {
+    pub kind: PathLintKind,
+    pub severity: LintSeverity,
+    /// Indexes of the navigators in the path that the finding refers to.
+    pub span: Range<usize>,
+    pub message: String,
+    pub suggestion: String,
+}
+
+/// Optional knowledge about the PState being queried, used to suppress findings that don't apply.
+#[derive(Debug, Clone, Default)]
+pub struct PStateSchemaHint {
+    /// Set when every collection in the PState is known to stay small.
+    pub bounded_collections: bool,
+}
+
+// --- Analyzer ---
+
+/// Runs the advisory lints over a query path.
+///
+/// This never rejects a path; it only reports findings, in navigator order.
+pub fn analyze_path(path: &Path, hint: Option<&PStateSchemaHint>) -> Vec<PathLint> {
+    analyze_navigators(path.navigators(), hint)
+}
+
+/// Like `analyze_path`, for navigators that aren't held in a `Path` (e.g. a path read
+/// from a request log).
+pub fn analyze_navigators(path: &[Value], hint: Option<&PStateSchemaHint>) -> Vec<PathLint> {
+    let bounded = hint.is_some_and(|h| h.bounded_collections);
+    let mut lints = Vec::new();
+    let mut last_all: Option<usize> = None;
+
+    for (index, nav) in path.iter().enumerate() {
+        let op = explicit_op(nav);
+
+        if op == Some("all") {
+            last_all = Some(index);
+            continue;
+        }
+
+        // --- filterPred after all() ---
+        if is_filter_pred(nav) {
+            if let (Some(all_index), false) = (last_all, bounded) {
+                lints.push(PathLint {
+                    kind: PathLintKind::FilterPredAfterAll,
+                    severity: LintSeverity::Warning,
+                    span: all_index..index + 1,
+                    message: "filterPred after all() scans every element of a possibly unbounded collection".to_string(),
+                    suggestion: "Narrow the collection first (e.g. a sorted range navigator) or maintain an index PState".to_string(),
+                });
+            }
+            continue;
+        }
+
+        match op {
+            // --- must() with duplicate keys ---
+            Some("must") => {
+                let Value::Array(items) = nav else { continue };
+                let mut seen = HashSet::new();
+                if items[1..].iter().any(|key| !seen.insert(key.to_string())) {
+                    lints.push(PathLint {
+                        kind: PathLintKind::DuplicateMustKeys,
+                        severity: LintSeverity::Info,
+                        span: index..index + 1,
+                        message: "must() lists the same key more than once".to_string(),
+                        suggestion: "Remove the duplicate keys; each key is only navigated once".to_string(),
+                    });
+                }
+            }
+            // --- sortedMapRangeFrom without a max count ---
+            Some("sortedMapRangeFrom") => {
+                let Value::Array(items) = nav else { continue };
+                if items.len() < 3 {
+                    lints.push(PathLint {
+                        kind: PathLintKind::SortedMapRangeFromWithoutMaxCount,
+                        severity: LintSeverity::Warning,
+                        span: index..index + 1,
+                        message: "sortedMapRangeFrom without a max count returns everything after the start key".to_string(),
+                        suggestion: "Pass a max count, e.g. [\"sortedMapRangeFrom\", start, 100]".to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    lints
+}
+
+// Returns the op name of an explicit navigator (`["op", args...]`), if `nav` is one.
+fn explicit_op(nav: &Value) -> Option<&str> {
+    match nav {
+        Value::Array(items) => items.first().and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+// Implicit function references (`#__f...`) act as filterPred, as does the explicit form.
+fn is_filter_pred(nav: &Value) -> bool {
+    match nav {
+        Value::String(s) => s.starts_with("#__f"),
+        _ => explicit_op(nav) == Some("filterPred"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_navigators, analyze_path, LintSeverity, PStateSchemaHint, PathLintKind};
+    use crate::builder::rama_function;
+    use crate::Path;
+    use serde_json::{json, Value};
+
+    fn kinds(path: &[Value]) -> Vec<PathLintKind> {
+        analyze_navigators(path, None).iter().map(|lint| lint.kind).collect()
+    }
+
+    #[test]
+    fn identifiers_are_stable() {
+        assert_eq!(PathLintKind::FilterPredAfterAll.as_str(), "filter-pred-after-all");
+        assert_eq!(PathLintKind::DuplicateMustKeys.as_str(), "duplicate-must-keys");
+        assert_eq!(PathLintKind::SortedMapRangeFromWithoutMaxCount.as_str(), "sorted-map-range-from-without-max-count");
+    }
+
+    #[test]
+    fn filter_pred_after_all_triggers() {
+        let path = Path::new().key("alice").all().key("x").filter_pred_fn("Ops.IS_EVEN");
+        let lints = analyze_path(&path, None);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, PathLintKind::FilterPredAfterAll);
+        assert_eq!(lints[0].severity, LintSeverity::Warning);
+        assert_eq!(lints[0].span, 1..4);
+        assert_eq!(kinds(&[json!(["all"]), json!(["filterPred", rama_function("Ops.IS_EVEN")])]), [PathLintKind::FilterPredAfterAll]);
+    }
+
+    #[test]
+    fn filter_pred_after_all_does_not_trigger() {
+        assert_eq!(kinds(&[rama_function("Ops.IS_EVEN"), json!(["all"])]), []);
+        assert_eq!(kinds(&[json!("alice"), json!(["filterPred", rama_function("Ops.IS_EVEN")])]), []);
+        let bounded = PStateSchemaHint { bounded_collections: true };
+        let path = Path::new().all().filter_pred_fn("Ops.IS_EVEN");
+        assert_eq!(analyze_path(&path, Some(&bounded)), []);
+        assert_eq!(analyze_path(&path, Some(&PStateSchemaHint::default())).len(), 1);
+    }
+
+    #[test]
+    fn duplicate_must_keys_triggers() {
+        let lints = analyze_navigators(&[json!("users"), json!(["must", "a", "b", "a"])], None);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, PathLintKind::DuplicateMustKeys);
+        assert_eq!(lints[0].severity, LintSeverity::Info);
+        assert_eq!(lints[0].span, 1..2);
+        assert_eq!(kinds(&[json!(["must", {"id": 1}, {"id": 1}])]), [PathLintKind::DuplicateMustKeys]);
+    }
+
+    #[test]
+    fn duplicate_must_keys_does_not_trigger() {
+        assert_eq!(kinds(&[json!(["must", "a", "b"])]), []);
+        assert_eq!(kinds(&[json!(["must"])]), []);
+        // A Long and an Integer are different keys
+        assert_eq!(kinds(&[json!(["must", 1, "#__L1"])]), []);
+        // Repeats across separate must navigators navigate different levels
+        assert_eq!(kinds(&[json!(["must", "a"]), json!(["must", "a"])]), []);
+    }
+
+    #[test]
+    fn sorted_map_range_from_without_max_count_triggers() {
+        let lints = analyze_navigators(&[json!(["sortedMapRangeFrom", "#__L10"])], None);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind, PathLintKind::SortedMapRangeFromWithoutMaxCount);
+        assert_eq!(lints[0].severity, LintSeverity::Warning);
+        assert_eq!(lints[0].span, 0..1);
+    }
+
+    #[test]
+    fn sorted_map_range_from_without_max_count_does_not_trigger() {
+        let path = Path::new().sorted_map_range_from(10i64, 5);
+        assert_eq!(analyze_path(&path, None), []);
+        assert_eq!(kinds(&[json!(["sortedMapRange", "#__L1", "#__L10"])]), []);
+    }
+
+    #[test]
+    fn findings_are_in_navigator_order() {
+        let path = [json!(["sortedMapRangeFrom", 1]), json!(["must", "a", "a"]), json!(["all"]), rama_function("f")];
+        assert_eq!(
+            kinds(&path),
+            [PathLintKind::SortedMapRangeFromWithoutMaxCount, PathLintKind::DuplicateMustKeys, PathLintKind::FilterPredAfterAll]
+        );
+    }
+}
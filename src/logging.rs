@@ -0,0 +1,17 @@
+// Logging macros used throughout the crate.
+//
+// By default these are the `log` crate's macros. With the `tracing` feature they are
+// `tracing`'s, so messages become structured events inside the request span.
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use log::{debug, error, info, warn};
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, warn};
+
+// Records a field on the current request span. A no-op without the `tracing` feature.
+macro_rules! record_span {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record($field, $value);
+    };
+}
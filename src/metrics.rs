@@ -0,0 +1,130 @@
+use crate::cache_stats::CacheEvent;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Receives the outcome of every logical request a client makes, e.g. to export
+/// per-module counts and latencies to Prometheus.
+///
+/// Called once per request after its redirect loop finishes, on the request's task,
+/// so implementations should be cheap and non-blocking. Hedged reads report each of
+/// their two requests that finishes; one cancelled because the other succeeded first
+/// isn't reported.
+pub trait ClientMetrics: Send + Sync {
+    fn on_request(&self, module: &str, outcome: &RequestOutcome);
+
+    /// Called for each supervisor cache hit, miss and stale refresh, as counted in
+    /// `Client::cache_stats`. Does nothing by default.
+    fn on_cache_event(&self, _module: &str, _event: CacheEvent) {}
+}
+
+/// What happened during one logical request.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOutcome {
+    /// The path under `/rest/<module>/`, e.g. `pstate/$$profiles/select`.
+    pub path_suffix: String,
+    /// Total time spent, including every redirect.
+    pub duration: Duration,
+    /// Number of HTTP requests sent, across redirects, retries and both requests of a
+    /// hedged read.
+    pub attempts: u32,
+    /// Number of 308 redirects followed.
+    pub redirects: u32,
+    /// Number of times the request was retried under the client's `RetryPolicy`.
+    pub retries: u32,
+    /// Status of the last response received, if any (None on transport errors).
+    pub status: Option<reqwest::StatusCode>,
+    /// Whether any attempt was sent to a supervisor taken from the supervisor cache.
+    pub used_cached_supervisor: bool,
+    /// Whether the request ultimately succeeded.
+    pub success: bool,
+    /// The logical request's ID, as sent in the request ID header.
+    pub request_id: String,
+    /// `ClientError::code` of the failure, if the request failed.
+    pub error_code: Option<&'static str>,
+    /// Whether the result came from the second (hedge) request of a hedged read. A hedged
+    /// read is one logical request, reported once.
+    pub hedged: bool,
+    /// Whether the request took longer than `ClientBuilder::slow_request_threshold`.
+    pub slow: bool,
+}
+
+impl RequestOutcome {
+    pub(crate) fn new(path_suffix: &str) -> Self {
+        Self { path_suffix: path_suffix.to_string(), ..Self::default() }
+    }
+}
+
+/// How a successful request was served, returned by the `*_with_meta` execution methods
+/// (e.g. `PStateQueryBuilder::select_with_meta`).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestMeta {
+    /// The logical request's ID, as sent in the request ID header.
+    pub request_id: String,
+    /// Number of HTTP requests sent, across redirects and retries.
+    pub attempts: u32,
+    /// Number of 308 redirects followed.
+    pub redirects: u32,
+    /// Number of times the request was retried under the client's `RetryPolicy`.
+    pub retries: u32,
+    /// Total time spent, including every redirect and retry.
+    pub duration: Duration,
+    /// Whether the supervisor cache routed the request: it went to a cached supervisor
+    /// and no redirect was needed.
+    pub cache_hit: bool,
+    /// Whether the response came from the second request of a hedged read.
+    pub hedged: bool,
+    /// The URL that answered. Never contains credentials; use `final_path` to leave out
+    /// the host too.
+    pub final_url: Url,
+}
+
+impl RequestMeta {
+    pub(crate) fn new(outcome: &RequestOutcome, final_url: Url) -> Self {
+        Self {
+            request_id: outcome.request_id.clone(),
+            attempts: outcome.attempts,
+            redirects: outcome.redirects,
+            retries: outcome.retries,
+            duration: outcome.duration,
+            cache_hit: outcome.used_cached_supervisor && outcome.redirects == 0,
+            hedged: outcome.hedged,
+            final_url,
+        }
+    }
+
+    /// The path of `final_url`, without scheme, host or port.
+    pub fn final_path(&self) -> &str {
+        self.final_url.path()
+    }
+}
+
+// The registered metrics hook (no-op by default), with a Debug impl for `Client`.
+#[derive(Clone, Default)]
+pub(crate) struct MetricsHook(Option<Arc<dyn ClientMetrics>>);
+
+impl MetricsHook {
+    pub(crate) fn new(metrics: Arc<dyn ClientMetrics>) -> Self {
+        Self(Some(metrics))
+    }
+
+    pub(crate) fn on_request(&self, module: &str, outcome: &RequestOutcome) {
+        if let Some(metrics) = &self.0 {
+            metrics.on_request(module, outcome);
+        }
+    }
+
+    pub(crate) fn on_cache_event(&self, module: &str, event: CacheEvent) {
+        if let Some(metrics) = &self.0 {
+            metrics.on_cache_event(module, event);
+        }
+    }
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "MetricsHook(registered)" } else { "MetricsHook(none)" })
+    }
+}
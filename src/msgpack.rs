@@ -0,0 +1,177 @@
+use crate::logging::error;
+use crate::ClientError;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+// Content types a gateway may label MessagePack with.
+const CONTENT_TYPES: [&str; 3] = ["application/msgpack", "application/x-msgpack", "application/vnd.msgpack"];
+
+// `Accept` for requests whose result may come back as MessagePack. JSON stays acceptable,
+// so servers that can't transcode answer as before.
+pub(crate) fn accept() -> HeaderValue {
+    HeaderValue::from_static("application/msgpack, application/json;q=0.9")
+}
+
+// Whether a response body is MessagePack, per its `Content-Type`.
+pub(crate) fn is_msgpack(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    CONTENT_TYPES.iter().any(|known| essence.eq_ignore_ascii_case(known))
+}
+
+// Deserializes a MessagePack body into `R`. Types only serde_json can build (`RawValue`)
+// are reached through a `Value`; if that fails too, the direct attempt's error is reported.
+pub(crate) fn decode<R: DeserializeOwned>(body: &[u8]) -> Result<R, ClientError> {
+    let result = rmp_serde::from_slice::<R>(body).or_else(|direct| {
+        let value: Option<Value> = rmp_serde::from_slice(body).ok();
+        value.and_then(|value| serde_json::from_value(value).ok()).ok_or(direct)
+    });
+    result.map_err(|e| {
+        let err = ClientError::MsgPack(e);
+        error!("[{}] Failed to deserialize MessagePack OK response: {}", err.code(), err);
+        err
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, is_msgpack};
+    use crate::transport::{HttpTransport, TransportFuture, TransportResponse};
+    use crate::{Client, ClientBuilder, ClientError, RamaValue};
+    use bytes::Bytes;
+    use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+    use reqwest::StatusCode;
+    use serde::Deserialize;
+    use serde_json::value::RawValue;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use url::Url;
+
+    // A gateway answering every select with `data`, as MessagePack when `transcodes` and
+    // the request accepts it, else as JSON. Records each request's `Accept`.
+    struct Gateway {
+        data: Value,
+        transcodes: bool,
+        accepts: Mutex<Vec<Option<String>>>,
+    }
+
+    impl HttpTransport for Gateway {
+        fn post(&self, _url: Url, headers: HeaderMap, _body: Bytes) -> TransportFuture<'_> {
+            let accept = headers.get(ACCEPT).map(|value| value.to_str().unwrap().to_string());
+            let msgpack = self.transcodes && accept.as_deref().is_some_and(|accept| accept.contains("application/msgpack"));
+            self.accepts.lock().unwrap().push(accept);
+            let (content_type, body) = match msgpack {
+                true => ("application/msgpack", rmp_serde::to_vec(&self.data).unwrap()),
+                false => ("application/json", serde_json::to_vec(&self.data).unwrap()),
+            };
+            let headers = HeaderMap::from_iter([(CONTENT_TYPE, HeaderValue::from_static(content_type))]);
+            Box::pin(async move { Ok(TransportResponse::from_bytes(StatusCode::OK, headers, body)) })
+        }
+
+        fn get(&self, _url: Url, _headers: HeaderMap) -> TransportFuture<'_> {
+            Box::pin(async { Ok(TransportResponse::from_bytes(StatusCode::NOT_FOUND, HeaderMap::new(), Bytes::new())) })
+        }
+    }
+
+    fn gateway(data: &Value, transcodes: bool) -> (Client, Arc<Gateway>) {
+        let gateway = Arc::new(Gateway { data: data.clone(), transcodes, accepts: Mutex::default() });
+        let client = ClientBuilder::new("http://gateway:1984").transport(gateway.clone()).build().unwrap();
+        (client, gateway)
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Profile {
+        name: String,
+        age: u32,
+        score: f64,
+        tags: Vec<String>,
+        manager: Option<String>,
+    }
+
+    fn profiles() -> Value {
+        json!([
+            {"name": "alice", "age": 30, "score": 1.5, "tags": ["admin"], "manager": null},
+            {"name": "bob", "age": 41, "score": -2.25, "tags": [], "manager": "alice"},
+        ])
+    }
+
+    #[test]
+    fn recognizes_msgpack_content_types() {
+        for (content_type, expected) in [
+            ("application/msgpack", true),
+            ("application/x-msgpack; charset=binary", true),
+            ("Application/Vnd.MsgPack", true),
+            ("application/json", false),
+            ("text/plain", false),
+        ] {
+            let headers = HeaderMap::from_iter([(CONTENT_TYPE, HeaderValue::from_static(content_type))]);
+            assert_eq!(is_msgpack(&headers), expected, "{}", content_type);
+        }
+        assert!(!is_msgpack(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn raw_values_are_built_through_json() {
+        let body = rmp_serde::to_vec(&json!({"a": [1, "#__L2"]})).unwrap();
+        let raw: Box<RawValue> = decode(&body).unwrap();
+        assert_eq!(raw.get(), r##"{"a":[1,"#__L2"]}"##);
+        // Single-precision floats mark a Java Float
+        let body = rmp_serde::to_vec(&0.5f32).unwrap();
+        assert_eq!(decode::<RamaValue>(&body).unwrap(), RamaValue::Float(0.5));
+    }
+
+    #[tokio::test]
+    async fn msgpack_and_json_answers_decode_alike() {
+        let data = json!([{"id": "#__L9007199254740993", "role": "#__Kadmin", "ratio": 0.25, "nested": {"#__Kx": [true, null]}}]);
+        let (json_client, json_gateway) = gateway(&data, false);
+        let (msgpack_client, msgpack_gateway) = gateway(&data, true);
+
+        for client in [&json_client, &msgpack_client] {
+            let query = || client.pstate_query("profiles", "$$profiles").all();
+            let values: Vec<Value> = query().select().await.unwrap();
+            let decoded: Vec<RamaValue> = query().select().await.unwrap();
+            let raw = query().select_raw().await.unwrap();
+            assert_eq!(values, data.as_array().unwrap().clone());
+            assert_eq!(decoded, [RamaValue::decode(&data[0])]);
+            assert_eq!(serde_json::from_str::<Value>(raw.get()).unwrap(), data);
+        }
+        // Both were asked for MessagePack; only one gateway sent it
+        for gateway in [json_gateway, msgpack_gateway] {
+            assert!(gateway.accepts.lock().unwrap().iter().all(|accept| accept.as_deref() == Some("application/msgpack, application/json;q=0.9")));
+        }
+    }
+
+    #[tokio::test]
+    async fn typed_results_match_across_formats() {
+        let (json_client, _) = gateway(&profiles(), false);
+        let (msgpack_client, _) = gateway(&profiles(), true);
+        let mut results = Vec::new();
+        for client in [&json_client, &msgpack_client] {
+            let all: Vec<Profile> = client.pstate_query("profiles", "$$profiles").all().select().await.unwrap();
+            let one: Vec<Profile> = client.pstate_query("profiles", "$$profiles").key("alice").select_one().await.unwrap();
+            results.push((all, one));
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0].0[1].manager.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn negotiation_can_be_turned_off() {
+        let gateway = Arc::new(Gateway { data: profiles(), transcodes: true, accepts: Mutex::default() });
+        let client = ClientBuilder::new("http://gateway:1984").transport(gateway.clone()).accept_msgpack(false).build().unwrap();
+        let all: Vec<Profile> = client.pstate_query("profiles", "$$profiles").all().select().await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(*gateway.accepts.lock().unwrap(), [None]);
+    }
+
+    #[tokio::test]
+    async fn a_msgpack_type_mismatch_has_its_own_code() {
+        let (client, _) = gateway(&profiles(), true);
+        let err = client.pstate_query("profiles", "$$profiles").all().select::<Vec<u32>>().await.unwrap_err();
+        assert_eq!(err.code(), "RAMA-CLIENT-MSGPACK", "{:?}", err);
+        assert!(matches!(err.kind(), ClientError::MsgPack(_)), "{:?}", err);
+    }
+}
@@ -0,0 +1,77 @@
+use opentelemetry::propagation::Injector;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// Writes propagator fields (`traceparent`, `tracestate`, ...) into request headers.
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        // Guard: Nothing to propagate (e.g. an empty `tracestate`)
+        if value.is_empty() {
+            return;
+        }
+        // Guard: Not representable as a header; propagators only emit ASCII, so never expected
+        let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) else {
+            return;
+        };
+        self.0.insert(name, value);
+    }
+}
+
+// Adds the trace context of the current span (the request span, inside `send_bytes`) to
+// `headers`, using the global propagator. A no-op until one is installed.
+pub(crate) fn inject_context(headers: &mut HeaderMap) {
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers));
+    });
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::testing::FakeCluster;
+    use opentelemetry::trace::{SpanId, TraceContextExt, TracerProvider};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use tracing::Instrument;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test]
+    async fn every_attempt_carries_the_request_span_as_parent() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+        let subscriber = tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(provider.tracer("rama-client-test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // One redirect, so the request takes two attempts
+        let cluster = FakeCluster::new();
+        cluster.pstate("users", "$$names", serde_json::json!({"alice": 1}));
+        let client = cluster.client();
+        let outer = tracing::info_span!("outer");
+        let trace_id = outer.context().span().span_context().trace_id();
+        let selected: Vec<u32> = client.pstate_query("users", "$$names").key("alice").select().instrument(outer).await.unwrap();
+        assert_eq!(selected, [1]);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let outer = spans.iter().find(|span| span.name == "outer").unwrap();
+        let request = spans.iter().find(|span| span.name == "rama_request").unwrap();
+        assert_eq!(request.span_context.trace_id(), trace_id);
+        assert_eq!(request.parent_span_id, outer.span_context.span_id());
+        assert_ne!(request.parent_span_id, SpanId::INVALID);
+        // Re-recorded fields are appended, so the last value is the final one
+        let attribute = |key: &str| request.attributes.iter().rfind(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string());
+        assert_eq!(attribute("attempt").as_deref(), Some("2"));
+        assert_eq!(attribute("status").as_deref(), Some("200"));
+
+        // Both the conductor and the supervisor saw the request span as the parent
+        let expected = format!("00-{}-{}-01", trace_id, request.span_context.span_id());
+        let requests = cluster.requests();
+        assert_eq!(requests.len(), 2);
+        for request in requests {
+            assert_eq!(request.headers["traceparent"], expected.as_str());
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::builder::{decode_rama_long, rama_long};
+use crate::logging::debug;
+use crate::{Client, ClientError};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A sorted-map key type that `PStatePager` can page by.
+pub trait PageKey: Ord + Clone + Sized {
+    /// The smallest key, where paging starts by default.
+    fn min_key() -> Self;
+    /// The key as a navigator argument, e.g. `rama_long` for Longs.
+    fn to_nav(&self) -> Value;
+    /// Parses a key from a response. Map keys arrive as JSON object keys (strings).
+    fn from_response(key: &Value) -> Option<Self>;
+}
+
+impl PageKey for String {
+    fn min_key() -> Self {
+        String::new()
+    }
+
+    fn to_nav(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_response(key: &Value) -> Option<Self> {
+        key.as_str().map(str::to_string)
+    }
+}
+
+impl PageKey for i64 {
+    fn min_key() -> Self {
+        i64::MIN
+    }
+
+    fn to_nav(&self) -> Value {
+        rama_long(*self)
+    }
+
+    fn from_response(key: &Value) -> Option<Self> {
+        decode_rama_long(key)
+    }
+}
+
+/// Pages through a sorted-map PState with successive `sortedMapRangeFrom` queries.
+///
+/// Created with `PStateQueryBuilder::paginate`; the builder's path must navigate to the
+/// sorted map. Each page starts just after the last key of the previous one, so pages
+/// neither overlap nor skip entries, and paging stops after the first short page.
+#[derive(Debug)]
+pub struct PStatePager<'a, K, R> {
+    client: &'a Client,
+    module: String,
+    pstate: String,
+    path: Vec<Value>,
+    hedge: Option<Duration>,
+    page_size: u32,
+    // Last key returned; the next range starts here (inclusive), so it is dropped from the page.
+    last_key: Option<K>,
+    start: Option<K>,
+    done: bool,
+    _values: PhantomData<fn() -> R>,
+}
+
+impl<'a, K: PageKey, R: DeserializeOwned> PStatePager<'a, K, R> {
+    pub(crate) fn new(client: &'a Client, module: String, pstate: String, path: Vec<Value>, hedge: Option<Duration>, page_size: u32) -> Self {
+        Self {
+            client,
+            module,
+            pstate,
+            path,
+            hedge,
+            page_size: page_size.max(1),
+            last_key: None,
+            start: None,
+            done: false,
+            _values: PhantomData,
+        }
+    }
+
+    /// Starts paging at `key` (inclusive) instead of the smallest key.
+    pub fn start_from(mut self, key: K) -> Self {
+        self.start = Some(key);
+        self
+    }
+
+    /// Fetches the next page of `(key, value)` entries in key order.
+    /// Returns `Ok(None)` once the map is exhausted.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<(K, R)>>, ClientError> {
+        // Guard: Already exhausted
+        if self.done {
+            return Ok(None);
+        }
+
+        // The start key is inclusive: after the first page, ask for one extra entry to make
+        // up for the previous page's last key, which comes back again.
+        let (start, requested) = match &self.last_key {
+            Some(last) => (last.clone(), self.page_size + 1),
+            None => (self.start.clone().unwrap_or_else(K::min_key), self.page_size),
+        };
+        let mut query = self.client.pstate_query(&self.module, &self.pstate);
+        for nav in &self.path {
+            query = query.nav(nav.clone());
+        }
+        query = query.nav(json!(["sortedMapRangeFrom", start.to_nav(), requested]));
+        if let Some(delay) = self.hedge {
+            query = query.hedge(delay);
+        }
+        let submap: Value = match query.select_one().await {
+            // A null submap (nothing at the path) is an empty page, as `decode_entries` treats it.
+            Err(e) if matches!(e.kind(), ClientError::NotFound { .. }) => Value::Null,
+            result => result?,
+        };
+
+        let mut entries = decode_entries::<K, R>(submap, K::from_response)?;
+        let received = entries.len();
+        // Map key order in JSON isn't guaranteed to match the PState's, so restore it.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if let Some(last) = &self.last_key {
+            entries.retain(|(key, _)| key > last);
+        }
+
+        // Guard: Short page means nothing is left
+        if received < requested as usize {
+            self.done = true;
+        }
+        debug!("Pager for PState '{}' fetched {} entries (done: {})", self.pstate, entries.len(), self.done);
+
+        match entries.last() {
+            Some((key, _)) => self.last_key = Some(key.clone()),
+            None => {
+                self.done = true;
+                return Ok(None);
+            }
+        }
+        Ok(Some(entries))
+    }
+}
+
+// Accepts a submap as a JSON object or as a list of [key, value] pairs.
+pub(crate) fn decode_entries<K, R: DeserializeOwned>(submap: Value, parse_key: fn(&Value) -> Option<K>) -> Result<Vec<(K, R)>, ClientError> {
+    let pairs: Vec<(Value, Value)> = match submap {
+        Value::Object(map) => map.into_iter().map(|(k, v)| (Value::String(k), v)).collect(),
+        Value::Array(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Array(mut pair) if pair.len() == 2 => {
+                    let v = pair.pop()?;
+                    Some((pair.pop()?, v))
+                }
+                _ => None,
+            })
+            .collect(),
+        Value::Null => Vec::new(),
+        other => {
+            return Err(ClientError::Json(serde::de::Error::custom(format!("expected a sorted map, got {}", other))));
+        }
+    };
+    pairs
+        .into_iter()
+        .map(|(k, v)| {
+            let key = parse_key(&k)
+                .ok_or_else(|| ClientError::Json(serde::de::Error::custom(format!("unparseable map key {}", k))))?;
+            Ok((key, serde_json::from_value(v)?))
+        })
+        .collect()
+}
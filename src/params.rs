@@ -0,0 +1,120 @@
+use crate::canonical::{self, Canonical};
+use crate::ClientError;
+use bytes::Bytes;
+use serde_json::Value;
+
+// Marks a named placeholder navigator, followed by the parameter's name.
+const PARAM_PREFIX: &str = "#__param:";
+
+// The navigator `PStateQueryBuilder::key_param` adds.
+pub(crate) fn placeholder(name: &str) -> Value {
+    Value::String(format!("{}{}", PARAM_PREFIX, name))
+}
+
+// The parameter name, if `nav` is a placeholder.
+pub(crate) fn param_name(nav: &Value) -> Option<&str> {
+    nav.as_str()?.strip_prefix(PARAM_PREFIX)
+}
+
+// Fails if `path` still has a placeholder, so one is never sent to the server.
+pub(crate) fn check_bound(path: &[Value]) -> Result<(), ClientError> {
+    match path.iter().find_map(param_name) {
+        Some(name) => Err(unbound(name)),
+        None => Ok(()),
+    }
+}
+
+fn unbound(name: &str) -> ClientError {
+    ClientError::UnboundQueryParameter(name.to_string())
+}
+
+// A path's request body, serialized once, with gaps where its placeholders go.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BodyTemplate {
+    // Distinct parameter names, in order of first appearance
+    pub(crate) params: Vec<String>,
+    // The JSON around the gaps: one more than there are gaps
+    segments: Vec<Vec<u8>>,
+    // For each gap, the index in `params` of the value that fills it
+    gaps: Vec<usize>,
+}
+
+impl BodyTemplate {
+    // The template for `path`, or None if it has no placeholders.
+    pub(crate) fn new(path: &[Value]) -> Option<Self> {
+        // Guard: Nothing to fill in
+        if !path.iter().any(|nav| param_name(nav).is_some()) {
+            return None;
+        }
+        let mut template = Self { params: Vec::new(), segments: Vec::new(), gaps: Vec::new() };
+        let mut segment = b"[".to_vec();
+        for (index, nav) in path.iter().enumerate() {
+            if index > 0 {
+                segment.push(b',');
+            }
+            let Some(name) = param_name(nav) else {
+                segment.extend_from_slice(canonical::to_string(nav).as_bytes());
+                continue;
+            };
+            let param = match template.params.iter().position(|p| p == name) {
+                Some(param) => param,
+                None => {
+                    template.params.push(name.to_string());
+                    template.params.len() - 1
+                }
+            };
+            template.segments.push(std::mem::take(&mut segment));
+            template.gaps.push(param);
+        }
+        segment.push(b']');
+        template.segments.push(segment);
+        Some(template)
+    }
+
+    // The body with every gap filled from `bound` (indexed like `params`).
+    pub(crate) fn render(&self, bound: &[Option<Value>]) -> Result<Bytes, ClientError> {
+        let mut body = Vec::with_capacity(self.segments.iter().map(Vec::len).sum::<usize>() + 16 * self.gaps.len());
+        for (segment, &param) in self.segments.iter().zip(&self.gaps) {
+            body.extend_from_slice(segment);
+            let value = bound[param].as_ref().ok_or_else(|| unbound(&self.params[param]))?;
+            serde_json::to_writer(&mut body, &Canonical(value))?;
+        }
+        body.extend_from_slice(self.segments.last().map_or(&[][..], Vec::as_slice));
+        Ok(Bytes::from(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical::CanonicalPath;
+    use serde_json::json;
+
+    #[test]
+    fn rendered_template_matches_serializing_the_bound_path() {
+        let path = [json!("users"), placeholder("id"), json!(["mapVals"]), placeholder("field"), placeholder("id")];
+        let template = BodyTemplate::new(&path).unwrap();
+        assert_eq!(template.params, ["id", "field"]);
+
+        let bound = [Some(json!({"b": 1, "a": "#__L7"})), Some(json!("name"))];
+        let rendered = template.render(&bound).unwrap();
+        let expected = [json!("users"), json!({"b": 1, "a": "#__L7"}), json!(["mapVals"]), json!("name"), json!({"b": 1, "a": "#__L7"})];
+        assert_eq!(rendered, serde_json::to_vec(&CanonicalPath(&expected)).unwrap());
+    }
+
+    #[test]
+    fn paths_without_placeholders_have_no_template() {
+        assert_eq!(BodyTemplate::new(&[json!("users"), json!(["all"])]), None);
+    }
+
+    #[test]
+    fn unbound_placeholders_are_reported_by_name() {
+        let template = BodyTemplate::new(&[placeholder("id"), placeholder("field")]).unwrap();
+        let err = template.render(&[Some(json!(1)), None]).unwrap_err();
+        assert!(matches!(&err, ClientError::UnboundQueryParameter(name) if name == "field"), "{err:?}");
+        assert_eq!(err.to_string(), "Query parameter 'field' was never bound (see `PreparedQuery::bind`)");
+
+        let err = check_bound(&[json!("users"), placeholder("id")]).unwrap_err();
+        assert!(matches!(&err, ClientError::UnboundQueryParameter(name) if name == "id"), "{err:?}");
+    }
+}
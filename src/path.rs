@@ -0,0 +1,220 @@
+use crate::builder::{rama_function, ToRamaValue};
+use crate::canonical::{self, CanonicalPath};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// An owned PState path, independent of any client or query.
+///
+/// Build a path once (e.g. at startup) and attach it to queries with
+/// `PStateQueryBuilder::nav_path`; `PStateQueryBuilder::into_path` goes the other way.
+/// Navigator methods produce the same JSON as the builder's.
+///
+/// Paths are serialized canonically wherever the client writes them (request bodies,
+/// prepared queries, `Display`): object keys are sorted at every level, so equal paths
+/// are always the same bytes, however their maps were built.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(transparent)]
+pub struct Path(Vec<Value>);
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an implicit navigator. See `PStateQueryBuilder::nav`.
+    pub fn nav(mut self, value: impl ToRamaValue) -> Self {
+        self.0.push(value.to_rama_value());
+        self
+    }
+
+    /// Adds a key navigator. See `PStateQueryBuilder::key`.
+    pub fn key(self, key: impl ToRamaValue) -> Self {
+        self.nav(key)
+    }
+
+    /// Adds a filterPred navigator using a Rama function reference.
+    pub fn filter_pred_fn(self, function_name: &str) -> Self {
+        self.nav(rama_function(function_name))
+    }
+
+    /// Adds any explicit navigator. See `PStateQueryBuilder::explicit_nav`.
+    pub fn explicit_nav(mut self, op: &str, args: impl IntoIterator<Item = impl Into<Value>>) -> Self {
+        let mut nav_array = vec![Value::String(op.to_string())];
+        nav_array.extend(args.into_iter().map(Into::into));
+        self.0.push(Value::Array(nav_array));
+        self
+    }
+
+    /// Adds the "all" navigator: `["all"]`.
+    pub fn all(self) -> Self {
+        self.explicit_nav("all", Vec::<Value>::new())
+    }
+
+    /// Adds the "must" navigator: `["must", key1, key2, ...]`. See `PStateQueryBuilder::must`.
+    pub fn must(self, keys: impl IntoIterator<Item = impl ToRamaValue>) -> Self {
+        self.explicit_nav("must", keys.into_iter().map(|key| key.to_rama_value()))
+    }
+
+    /// Adds a "must" navigator for a single key: `["must", key]`.
+    pub fn must_key(self, key: impl ToRamaValue) -> Self {
+        self.must([key])
+    }
+
+    /// Adds the "sortedMapRange" navigator. See `PStateQueryBuilder::sorted_map_range`.
+    pub fn sorted_map_range(self, start: impl ToRamaValue, end: impl ToRamaValue) -> Self {
+        self.explicit_nav("sortedMapRange", [start.to_rama_value(), end.to_rama_value()])
+    }
+
+    /// Adds the "sortedMapRangeFrom" navigator. See `PStateQueryBuilder::sorted_map_range_from`.
+    pub fn sorted_map_range_from(self, start: impl ToRamaValue, max: u32) -> Self {
+        self.explicit_nav("sortedMapRangeFrom", [start.to_rama_value(), Value::from(max)])
+    }
+
+    /// Adds the "mapVals" navigator: `["mapVals"]`.
+    pub fn map_vals(self) -> Self {
+        self.explicit_nav("mapVals", Vec::<Value>::new())
+    }
+
+    /// The navigators, in order.
+    pub fn navigators(&self) -> &[Value] {
+        &self.0
+    }
+
+    /// The path as it is sent: a JSON array of navigators.
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.0.clone())
+    }
+
+    /// The exact bytes sent for this path, as compact JSON with every object's keys in
+    /// sorted order. Unlike `to_json().to_string()`, this doesn't depend on whether
+    /// serde_json's `preserve_order` feature is enabled somewhere in the build, so it is
+    /// stable enough for snapshots and fingerprints.
+    pub fn to_canonical_json(&self) -> String {
+        canonical::path_to_string(&self.0)
+    }
+}
+
+impl From<Vec<Value>> for Path {
+    fn from(navigators: Vec<Value>) -> Self {
+        Self(navigators)
+    }
+}
+
+impl From<Path> for Vec<Value> {
+    fn from(path: Path) -> Self {
+        path.0
+    }
+}
+
+// Pretty-printed JSON, like `PStateQueryBuilder`'s `Display`.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(&CanonicalPath(&self.0)).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// Builds a `Path` from a list of navigators, e.g.
+/// `rama_path!["profiles", key = 42i64, all, filter = Ops::IS_EVEN, ["sortedMapRangeFrom", 10i64, 5]]`.
+///
+/// Each item expands to the matching `Path` method, so encoding is the same as the fluent API:
+/// - `all`, `map_vals`: `all()`, `map_vals()`
+/// - `key = k`: `key(k)`; `must = k`: `must_key(k)`
+/// - `filter = Ops::NAME`: `filter_pred_fn("Ops.NAME")`; `filter = "fn"`: `filter_pred_fn("fn")`
+/// - `[op, args...]`: `explicit_nav(op, args)`, arguments encoded per `ToRamaValue`
+/// - anything else: `nav(item)`
+///
+/// ```
+/// use rama_client::{rama_path, Path};
+///
+/// let declared = rama_path!["profiles", key = 42i64, all, must = "tags", filter = Ops::IS_EVEN, ["sortedMapRangeFrom", 10i64, 5]];
+/// let fluent = Path::new()
+///     .nav("profiles")
+///     .key(42i64)
+///     .all()
+///     .must_key("tags")
+///     .filter_pred_fn("Ops.IS_EVEN")
+///     .sorted_map_range_from(10i64, 5);
+/// assert_eq!(declared, fluent);
+/// assert_eq!(rama_path![], Path::new());
+/// ```
+///
+/// Unknown shorthands and items that aren't expressions don't compile:
+///
+/// ```compile_fail
+/// let path = rama_client::rama_path!["profiles", range = 10];
+/// ```
+///
+/// ```compile_fail
+/// let path = rama_client::rama_path!["profiles", key 42];
+/// ```
+///
+/// ```compile_fail
+/// let path = rama_client::rama_path!["profiles", filter = Ops::];
+/// ```
+#[macro_export]
+macro_rules! rama_path {
+    (@munch $path:expr;) => { $path };
+    (@munch $path:expr; all $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.all(); $($($rest)*)?)
+    };
+    (@munch $path:expr; map_vals $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.map_vals(); $($($rest)*)?)
+    };
+    (@munch $path:expr; key = $key:expr $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.key($key); $($($rest)*)?)
+    };
+    (@munch $path:expr; must = $key:expr $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.must_key($key); $($($rest)*)?)
+    };
+    (@munch $path:expr; filter = Ops::$name:ident $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.filter_pred_fn(concat!("Ops.", stringify!($name))); $($($rest)*)?)
+    };
+    (@munch $path:expr; filter = $function:expr $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.filter_pred_fn($function); $($($rest)*)?)
+    };
+    (@munch $path:expr; $name:ident = $value:expr $(, $($rest:tt)*)?) => {
+        compile_error!(concat!("unknown rama_path! shorthand `", stringify!($name), "`; expected `key`, `must` or `filter`"))
+    };
+    (@munch $path:expr; [$op:expr $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.explicit_nav($op, ::std::iter::empty::<bool>()); $($($rest)*)?)
+    };
+    (@munch $path:expr; [$op:expr, $($arg:expr),+ $(,)?] $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.explicit_nav($op, [$($crate::ToRamaValue::to_rama_value(&$arg)),+]); $($($rest)*)?)
+    };
+    (@munch $path:expr; $nav:expr $(, $($rest:tt)*)?) => {
+        $crate::rama_path!(@munch $path.nav($nav); $($($rest)*)?)
+    };
+    ($($items:tt)*) => {
+        $crate::rama_path!(@munch $crate::Path::new(); $($items)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Path;
+    use crate::Keyword;
+    use serde_json::json;
+
+    #[test]
+    fn rama_path_expands_to_the_fluent_calls() {
+        let user = String::from("alice");
+        let declared = crate::rama_path![user.clone(), map_vals, ["all"], ["stop",], filter = "com.example/valid?", must = Keyword("id".into()), json!({"k": 1})];
+        let fluent = Path::new()
+            .nav(user)
+            .map_vals()
+            .all()
+            .explicit_nav("stop", Vec::<bool>::new())
+            .filter_pred_fn("com.example/valid?")
+            .must_key(Keyword("id".into()))
+            .nav(json!({"k": 1}));
+        assert_eq!(declared, fluent);
+    }
+
+    #[test]
+    fn explicit_arrays_encode_their_arguments() {
+        let path = crate::rama_path![["sortedMapRange", 1i64, 'z'], ["custom", 2i8, 3i32, "s"]];
+        assert_eq!(path.to_json(), json!([["sortedMapRange", "#__L1", "#__Cz"], ["custom", "#__B2", 3, "s"]]));
+    }
+}
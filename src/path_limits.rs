@@ -0,0 +1,168 @@
+use crate::canonical::CanonicalPath;
+use crate::{Client, ClientError};
+use serde_json::Value;
+use std::io;
+
+/// The size of a path, from `PStateQueryBuilder::path_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathStats {
+    /// Navigation steps: one per navigator, except that a `must` navigator counts each of
+    /// its keys (`["must", a, b]` navigates two levels).
+    pub navigators: usize,
+    /// Length of the path's canonical JSON, as sent (see `Path::to_canonical_json`).
+    pub bytes: usize,
+}
+
+/// Upper bounds on the paths a client sends, set with `ClientBuilder::max_path_navigators`
+/// and `ClientBuilder::max_path_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimits {
+    pub max_navigators: usize,
+    pub max_bytes: usize,
+}
+
+impl Default for PathLimits {
+    /// 10,000 navigators and 1 MiB: far beyond hand-written paths, well short of a
+    /// runaway generated one.
+    fn default() -> Self {
+        Self { max_navigators: 10_000, max_bytes: 1 << 20 }
+    }
+}
+
+impl PathLimits {
+    // Fails with `PathTooLarge` if `stats` exceeds either limit.
+    pub(crate) fn check(&self, stats: PathStats) -> Result<(), ClientError> {
+        // Guard: Within both limits
+        if stats.navigators <= self.max_navigators && stats.bytes <= self.max_bytes {
+            return Ok(());
+        }
+        Err(ClientError::PathTooLarge { navigators: stats.navigators, bytes: stats.bytes, limits: *self })
+    }
+}
+
+impl Client {
+    // Measures `path` and checks it against this client's limits, before anything is sent.
+    pub(crate) fn check_path_size(&self, path: &[Value]) -> Result<(), ClientError> {
+        self.path_limits.check(measure(path))
+    }
+
+    // Like `check_path_size`, for a path already serialized to `bytes` bytes.
+    pub(crate) fn check_serialized_path_size(&self, path: &[Value], bytes: usize) -> Result<(), ClientError> {
+        self.path_limits.check(PathStats { navigators: count_navigators(path), bytes })
+    }
+}
+
+// The size `path` is sent at.
+pub(crate) fn measure(path: &[Value]) -> PathStats {
+    let mut bytes = ByteCount(0);
+    serde_json::to_writer(&mut bytes, &CanonicalPath(path)).expect("JSON values always serialize");
+    PathStats { navigators: count_navigators(path), bytes: bytes.0 }
+}
+
+fn count_navigators(path: &[Value]) -> usize {
+    path.iter()
+        .map(|nav| match nav.as_array().map(Vec::as_slice) {
+            Some([op, keys @ ..]) if op == "must" => keys.len().max(1),
+            _ => 1,
+        })
+        .sum()
+}
+
+// Counts the bytes written to it, so a path is measured without being buffered.
+struct ByteCount(usize);
+
+impl io::Write for ByteCount {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measure, PathLimits, PathStats};
+    use crate::testing::FakeCluster;
+    use crate::{Client, ClientError};
+    use serde_json::{json, Value};
+
+    fn stats(navigators: usize, bytes: usize) -> PathStats {
+        PathStats { navigators, bytes }
+    }
+
+    #[test]
+    fn checks_each_limit_inclusively() {
+        let limits = PathLimits { max_navigators: 3, max_bytes: 100 };
+        assert!(limits.check(stats(2, 99)).is_ok());
+        assert!(limits.check(stats(3, 100)).is_ok());
+        assert!(matches!(limits.check(stats(4, 100)), Err(ClientError::PathTooLarge { navigators: 4, bytes: 100, .. })));
+        assert!(matches!(limits.check(stats(3, 101)), Err(ClientError::PathTooLarge { navigators: 3, bytes: 101, .. })));
+    }
+
+    #[test]
+    fn measures_the_canonical_form() {
+        let path = [json!("alice"), json!({"b": 1, "a": [2]}), json!(["must", "x", "y", "z"]), json!(["all"])];
+        let canonical = r#"["alice",{"a":[2],"b":1},["must","x","y","z"],["all"]]"#;
+        assert_eq!(measure(&path), stats(6, canonical.len()));
+        assert_eq!(measure(&[json!(["must"])]), stats(1, r#"[["must"]]"#.len()));
+    }
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": {"tags": ["a"]}}));
+        cluster
+    }
+
+    async fn select(client: &Client, path: &[Value]) -> Result<Vec<Value>, ClientError> {
+        let mut query = client.pstate_query("profiles", "$$profiles");
+        for nav in path {
+            query = query.nav(nav);
+        }
+        query.select().await
+    }
+
+    #[tokio::test]
+    async fn navigator_limit_below_at_and_above() {
+        let cluster = cluster();
+        let client = cluster.client_builder().max_path_navigators(3).build().unwrap();
+
+        assert!(select(&client, &[json!("alice"), json!("tags")]).await.is_ok());
+        assert!(select(&client, &[json!("alice"), json!("tags"), json!(["all"])]).await.is_ok());
+        let sent = cluster.requests().len();
+
+        let err = select(&client, &[json!("alice"), json!("tags"), json!(["all"]), json!(["stop"])]).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::PathTooLarge { navigators: 4, .. }), "{:?}", err);
+        let err = select(&client, &[json!(["must", "a", "b", "c", "d"])]).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::PathTooLarge { navigators: 4, .. }), "{:?}", err);
+        // Rejected before anything was sent
+        assert_eq!(cluster.requests().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn byte_limit_below_at_and_above() {
+        let path = [json!("alice"), json!("tags")];
+        let bytes = measure(&path).bytes;
+        assert_eq!(bytes, r#"["alice","tags"]"#.len());
+
+        for (max_bytes, fits) in [(bytes + 1, true), (bytes, true), (bytes - 1, false)] {
+            let cluster = cluster();
+            let client = cluster.client_builder().max_path_bytes(max_bytes).build().unwrap();
+            let query = client.pstate_query("profiles", "$$profiles").key("alice").key("tags");
+            assert_eq!(query.path_stats(), stats(2, bytes));
+            match query.select::<Value>().await {
+                Ok(_) => assert!(fits, "a {} byte path passed a {} byte limit", bytes, max_bytes),
+                Err(err) => {
+                    assert!(!fits, "{:?}", err);
+                    let ClientError::PathTooLarge { bytes: measured, limits, .. } = err.kind() else {
+                        panic!("expected PathTooLarge, got {:?}", err);
+                    };
+                    assert_eq!((*measured, limits.max_bytes), (bytes, max_bytes));
+                    assert!(cluster.requests().is_empty());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::logging::debug;
+use crate::redact::Redacted;
+use crate::rt::Instant;
+use crate::{Client, ClientError};
+use reqwest::StatusCode;
+use std::time::Duration;
+use url::Url;
+
+// Timeout used by `Client::ping` and `Client::ping_module`, short enough for readiness probes.
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl Client {
+    /// Checks the conductor is reachable and answering, and returns the round-trip time.
+    /// Times out after 2 seconds, whatever the client's own timeouts.
+    ///
+    /// The REST API has no health endpoint, so this GETs the conductor URL, which has no
+    /// side effects. Any answer counts except 401/403 (credentials rejected) and 5xx, which
+    /// fail with `ClientError::UnexpectedStatus`. Connection, TLS and other transport
+    /// failures fail with `ClientError::Unreachable`.
+    pub async fn ping(&self) -> Result<Duration, ClientError> {
+        self.ping_with_timeout(DEFAULT_PING_TIMEOUT).await
+    }
+
+    /// Like `ping`, with a custom timeout.
+    pub async fn ping_with_timeout(&self, timeout: Duration) -> Result<Duration, ClientError> {
+        let url = self.base_url().clone();
+        let started = Instant::now();
+        let response = match crate::rt::timeout(timeout, self.get(url.clone())).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => return Err(unreachable(&url, e)),
+            Err(_) => return Err(ClientError::Timeout(timeout)),
+        };
+        let elapsed = started.elapsed();
+        let status = response.status;
+        debug!("Ping to conductor {} answered {} in {:?}", Redacted(&url), status, elapsed);
+
+        // Guard: Credentials rejected or server failing
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN || status.is_server_error() {
+            return Err(ClientError::UnexpectedStatus(status, Redacted(&url).to_string()));
+        }
+        Ok(elapsed)
+    }
+
+    /// Checks `module` is deployed and resolves to its supervisors, and returns the
+    /// round-trip time. Times out after 2 seconds, whatever the client's own timeouts.
+    ///
+    /// Runs `discover_supervisors`, so nothing is written and the supervisor cache is
+    /// warmed as a side effect. Fails with `ClientError::ModuleNotFound` if the module isn't
+    /// deployed, and otherwise like `ping`.
+    pub async fn ping_module(&self, module: &str) -> Result<Duration, ClientError> {
+        self.ping_module_with_timeout(module, DEFAULT_PING_TIMEOUT).await
+    }
+
+    /// Like `ping_module`, with a custom timeout.
+    pub async fn ping_module_with_timeout(&self, module: &str, timeout: Duration) -> Result<Duration, ClientError> {
+        let started = Instant::now();
+        let supervisors = match crate::rt::timeout(timeout, self.discover_supervisors(module)).await {
+            Ok(Ok(supervisors)) => supervisors,
+            Ok(Err(e @ (ClientError::Http(_) | ClientError::Transport(_)))) => return Err(unreachable(self.base_url(), e)),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err(ClientError::Timeout(timeout)),
+        };
+        let elapsed = started.elapsed();
+        debug!("Ping for module '{}' resolved {} supervisors in {:?}", module, supervisors.len(), elapsed);
+        Ok(elapsed)
+    }
+}
+
+// Wraps a transport failure reaching `url` with a description of what went wrong.
+fn unreachable(url: &Url, error: ClientError) -> ClientError {
+    let reason = match &error {
+        ClientError::Http(e) if e.is_timeout() => "connection timed out",
+        ClientError::Http(e) if is_tls(e) => "TLS handshake failed",
+        ClientError::Http(e) if crate::is_connect(e) => "connection failed",
+        ClientError::Http(_) => "request failed",
+        _ => "transport failed",
+    };
+    ClientError::Unreachable { url: Redacted(url).to_string(), reason, source: Box::new(error) }
+}
+
+// Whether a reqwest error was caused by TLS. reqwest doesn't expose this, so look for
+// the TLS library's error in the source chain.
+fn is_tls(error: &reqwest::Error) -> bool {
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        let message = cause.to_string().to_ascii_lowercase();
+        if ["tls", "ssl", "certificate", "handshake"].iter().any(|word| message.contains(word)) {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::FakeCluster;
+    use crate::ClientError;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn ping_module_warms_the_cache_and_reports_unknown_modules() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({}));
+        let client = cluster.client();
+
+        client.ping_module("profiles").await.unwrap();
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, ["fake-supervisor-1:1984"]);
+        let err = client.ping_module("missing").await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::ModuleNotFound { module, .. } if module == "missing"), "{:?}", err);
+        // Nothing was appended or selected
+        assert!(cluster.requests().iter().all(|request| request.path_suffix.is_empty()));
+    }
+
+    // A local URL nothing listens on.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    async fn refused_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    #[tokio::test]
+    async fn refused_connections_are_unreachable() {
+        let client = crate::Client::new(refused_url().await).unwrap();
+        for err in [client.ping().await.unwrap_err(), client.ping_module("profiles").await.unwrap_err()] {
+            assert!(matches!(err.kind(), ClientError::Unreachable { reason: "connection failed", .. }), "{:?}", err);
+            assert_eq!(err.code(), "RAMA-TRANSPORT-CONNECT");
+        }
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    #[tokio::test]
+    async fn server_errors_and_rejected_credentials_fail_the_ping() {
+        for (status, code) in [(503, "RAMA-SERVER-5XX"), (401, "RAMA-SERVER-AUTH")] {
+            let server = crate::testing::MockServer::start(move |_| (status, vec![], b"nope".to_vec())).await;
+            let err = crate::Client::new(&server.url).unwrap().ping().await.unwrap_err();
+            let expected = reqwest::StatusCode::from_u16(status).unwrap();
+            assert!(matches!(err.kind(), ClientError::UnexpectedStatus(s, _) if *s == expected), "{:?}", err);
+            assert_eq!(err.code(), code);
+            assert!(server.requests()[0].head.starts_with("GET / "), "{}", server.requests()[0].head);
+        }
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    #[tokio::test]
+    async fn any_other_answer_counts() {
+        let server = crate::testing::MockServer::start(|_| (404, vec![], Vec::new())).await;
+        crate::Client::new(&server.url).unwrap().ping().await.unwrap();
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    #[tokio::test]
+    async fn stalled_servers_time_out() {
+        // Accepts connections and never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+        let client = crate::Client::new(url).unwrap();
+
+        let err = client.ping_with_timeout(std::time::Duration::from_millis(100)).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::Timeout(timeout) if *timeout == std::time::Duration::from_millis(100)), "{:?}", err);
+        let err = client.ping_module_with_timeout("profiles", std::time::Duration::from_millis(100)).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::Timeout(_)), "{:?}", err);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::logging::{debug, warn};
+use crate::builder::{depot_name, pstate_name};
+use crate::redact::Redacted;
+use crate::{Client, ClientError, TransportResponse};
+use futures::future::join_all;
+use serde_json::{json, Value};
+use std::time::Duration;
+use url::Url;
+
+// Per-check timeout used by `Client::preflight`.
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A startup check run by `Client::preflight`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightCheck {
+    /// The conductor answers HTTP at all.
+    Reachable,
+    /// The conductor doesn't reject the client's credentials (401/403).
+    Auth,
+    /// The module is deployed.
+    ModuleExists(String),
+    /// The PState exists in the module: `PStateExists(module, pstate)`.
+    PStateExists(String, String),
+    /// The depot exists in the module: `DepotExists(module, depot)`.
+    DepotExists(String, String),
+}
+
+/// The outcome of one preflight check.
+#[derive(Debug)]
+pub struct PreflightResult {
+    pub check: PreflightCheck,
+    /// None if the check passed.
+    pub error: Option<ClientError>,
+}
+
+impl PreflightResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results of `Client::preflight`, in the order the checks were given.
+#[derive(Debug)]
+pub struct PreflightReport {
+    pub results: Vec<PreflightResult>,
+}
+
+impl PreflightReport {
+    /// True if every check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.results.iter().all(PreflightResult::passed)
+    }
+
+    /// The checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightResult> {
+        self.results.iter().filter(|r| !r.passed())
+    }
+}
+
+impl Client {
+    /// Runs startup checks concurrently, each with a 5 second timeout, so services can fail
+    /// fast on bad Rama configuration.
+    ///
+    /// The REST API has no metadata endpoints, so existence checks use the cheapest
+    /// side-effect-free requests available: a `["stop"]` select for PStates (navigates
+    /// nowhere, returns nothing), and a GET for modules and depots, where 404 means
+    /// missing (and 401/403 fails the check too). Nothing is ever appended.
+    pub async fn preflight(&self, checks: Vec<PreflightCheck>) -> PreflightReport {
+        self.preflight_with_timeout(checks, DEFAULT_CHECK_TIMEOUT).await
+    }
+
+    /// Like `preflight`, with a custom per-check timeout.
+    pub async fn preflight_with_timeout(&self, checks: Vec<PreflightCheck>, timeout: Duration) -> PreflightReport {
+        let runs = checks.into_iter().map(|check| async move {
+            let error = match crate::rt::timeout(timeout, self.run_check(&check)).await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e),
+                Err(_) => Some(ClientError::Timeout(timeout)),
+            };
+            match &error {
+                None => debug!("Preflight check {:?} passed", check),
+                Some(e) => warn!("[{}] Preflight check {:?} failed: {}", e.code(), check, e),
+            }
+            PreflightResult { check, error }
+        });
+        PreflightReport { results: join_all(runs).await }
+    }
+
+    async fn run_check(&self, check: &PreflightCheck) -> Result<(), ClientError> {
+        match check {
+            PreflightCheck::Reachable => {
+                self.get(self.base_url().clone()).await?;
+                Ok(())
+            }
+            PreflightCheck::Auth => {
+                let status = self.get(self.base_url().clone()).await?.status;
+                check_credentials(status, self.base_url())
+            }
+            PreflightCheck::ModuleExists(module) => self.probe_exists(module, "").await,
+            PreflightCheck::PStateExists(module, pstate) => {
+                let path_suffix = format!("pstate/{}/select", pstate_name(pstate));
+                let stop_path = vec![json!(["stop"])];
+                self.send_idempotent_request::<_, Value>(module, &path_suffix, &stop_path, None).await?;
+                Ok(())
+            }
+            PreflightCheck::DepotExists(module, depot) => {
+                self.probe_exists(module, &format!("depot/{}/append", depot_name(depot))).await
+            }
+        }
+    }
+
+    // GETs a REST URL; 404 means the module or object doesn't exist, any other response
+    // that it does, unless the credentials were rejected.
+    async fn probe_exists(&self, module: &str, path_suffix: &str) -> Result<(), ClientError> {
+        let url = self.build_url(module, path_suffix)?;
+        let response = self.get(url.clone()).await?;
+        check_credentials(response.status, &url)?;
+        // Guard: Not found
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            let body = response.text().await.unwrap_or_default();
+            return Err(match path_suffix.split('/').nth(1) {
+                Some(object) => ClientError::ObjectNotFound { module: module.to_string(), object: object.to_string(), body },
+                None => ClientError::ModuleNotFound { module: module.to_string(), body },
+            });
+        }
+        Ok(())
+    }
+
+    // GETs `url` with the client's default and auth headers (and the trace context, with
+    // `otel`). The body is left unread.
+    pub(crate) async fn get(&self, url: Url) -> Result<TransportResponse, ClientError> {
+        let mut headers = self.default_headers.clone();
+        self.authorize(&mut headers).await?;
+        #[cfg(feature = "otel")]
+        crate::otel::inject_context(&mut headers);
+        self.transport.0.get(url, headers).await
+    }
+}
+
+// Fails if `status` (from `url`) means the credentials were rejected (401/403).
+fn check_credentials(status: reqwest::StatusCode, url: &Url) -> Result<(), ClientError> {
+    // Guard: Credentials rejected
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(ClientError::UnexpectedStatus(status, Redacted(url).to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PreflightCheck::{self, *};
+    use crate::testing::{FakeCluster, RequireToken};
+    use crate::ClientError;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    fn checks() -> Vec<PreflightCheck> {
+        let s = str::to_string;
+        vec![
+            Reachable,
+            Auth,
+            ModuleExists(s("profiles")),
+            PStateExists(s("profiles"), s("$$profiles")),
+            PStateExists(s("profiles"), s("$$missing")),
+            DepotExists(s("profiles"), s("*edits")),
+        ]
+    }
+
+    async fn preflight(token: &str) -> super::PreflightReport {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30})).depot("profiles", "*edits");
+        let client = cluster.client_builder().transport(RequireToken::new(&cluster, "good-token")).bearer_auth(token).build().unwrap();
+        client.preflight(checks()).await
+    }
+
+    #[tokio::test]
+    async fn reports_a_missing_pstate() {
+        let report = preflight("good-token").await;
+        assert!(!report.is_healthy());
+        let failures: Vec<_> = report.failures().map(|r| &r.check).collect();
+        assert_eq!(failures, [&checks()[4]]);
+        let error = report.results[4].error.as_ref().unwrap();
+        assert!(matches!(error.kind(), ClientError::ObjectNotFound { object, .. } if object == "$$missing"), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn reports_a_wrong_credential() {
+        let report = preflight("wrong-token").await;
+        assert_eq!(report.results.len(), 6);
+        // The conductor answers, but rejects every request
+        assert!(report.results[0].passed());
+        for result in &report.results[1..] {
+            let error = result.error.as_ref().unwrap_or_else(|| panic!("{:?} passed", result.check));
+            assert!(matches!(error.kind(), ClientError::UnexpectedStatus(StatusCode::UNAUTHORIZED, _)), "{:?}: {:?}", result.check, error);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
+
+// Field selection for `PStateQueryBuilder::project` (client-side) and
+// `PStateQueryBuilder::project_on_server` (rewritten into a `multiPath`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Projection {
+    fields: Vec<String>,
+    pub(crate) on_server: bool,
+}
+
+impl Projection {
+    pub(crate) fn new(fields: &[&str], on_server: bool) -> Self {
+        Self { fields: fields.iter().map(|f| f.to_string()).collect(), on_server }
+    }
+
+    // The path to send: on the server, `path` followed by a `multiPath` with one key path
+    // per field, so each result comes back as one value per field, in field order.
+    pub(crate) fn wire_path<'p>(&self, path: &'p [Value]) -> Cow<'p, [Value]> {
+        // Guard: Projected client-side; the path is sent as built
+        if !self.on_server {
+            return Cow::Borrowed(path);
+        }
+        let key_paths = self.fields.iter().map(|field| Value::Array(tokens(field).into_iter().map(Value::String).collect()));
+        let multi_path = std::iter::once(json!("multiPath")).chain(key_paths).collect();
+        let mut wire_path = path.to_vec();
+        wire_path.push(Value::Array(multi_path));
+        Cow::Owned(wire_path)
+    }
+
+    // Projects the result list of a `select` sent with `wire_path`.
+    //
+    // On the server, every run of one value per field is rebuilt into an object, leaving
+    // out fields that selected null (missing keys navigate to null); a run that's all null
+    // was no object at all and becomes null.
+    pub(crate) fn apply_all(&self, values: Vec<Value>) -> Vec<Value> {
+        // Guard: Projected client-side
+        if !self.on_server {
+            return values.into_iter().map(|v| self.apply(v)).collect();
+        }
+        values
+            .chunks(self.fields.len().max(1))
+            .map(|run| {
+                // Guard: Nothing at any field
+                if run.iter().all(Value::is_null) {
+                    return Value::Null;
+                }
+                let mut object = Value::Object(Map::new());
+                for (field, value) in self.fields.iter().zip(run).filter(|(_, value)| !value.is_null()) {
+                    insert_at(&mut object, &tokens(field), value.clone());
+                }
+                object
+            })
+            .collect()
+    }
+
+    // Keeps only the listed fields of an object. Non-objects pass through unchanged.
+    // A field starting with '/' is a JSON pointer; the picked value is kept at the same
+    // nested position. Missing fields are left out.
+    pub(crate) fn apply(&self, value: Value) -> Value {
+        // Guard: Only objects have fields to pick
+        if !value.is_object() {
+            return value;
+        }
+
+        let mut projected = Value::Object(Map::new());
+        for field in &self.fields {
+            let tokens = tokens(field);
+            let pointer: String = tokens.iter().map(|t| format!("/{}", escape(t))).collect();
+            if let Some(picked) = value.pointer(&pointer) {
+                insert_at(&mut projected, &tokens, picked.clone());
+            }
+        }
+        projected
+    }
+
+    // Applies the projection to each element of a `select` result list.
+    pub(crate) fn apply_each(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.apply(v)).collect()),
+            other => other,
+        }
+    }
+}
+
+// The keys a field navigates: one for a plain name, one per JSON pointer token.
+fn tokens(field: &str) -> Vec<String> {
+    match field.strip_prefix('/') {
+        Some(pointer) => pointer.split('/').map(unescape).collect(),
+        None => vec![field.to_string()],
+    }
+}
+
+// Sets `value` at the nested object position given by `tokens`, creating objects on the way.
+fn insert_at(target: &mut Value, tokens: &[String], value: Value) {
+    let Some((last, parents)) = tokens.split_last() else { return };
+    let mut current = target;
+    for token in parents {
+        // Guard: An earlier, shorter pick already put a non-object here; it contains this one
+        let Value::Object(map) = current else { return };
+        current = map.entry(token.clone()).or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(map) = current {
+        map.insert(last.clone(), value);
+    }
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> Value {
+        json!({"name": "alice", "age": 30, "profile": {"avatar_url": "a.png", "bio": "hi"}, "a/b": 1})
+    }
+
+    #[test]
+    fn client_side_keeps_listed_top_level_keys() {
+        let projection = Projection::new(&["name", "age", "missing"], false);
+        assert_eq!(projection.apply(profile()), json!({"name": "alice", "age": 30}));
+    }
+
+    #[test]
+    fn client_side_pointers_keep_nested_position() {
+        let projection = Projection::new(&["/profile/avatar_url", "/a~1b", "/profile/nothing"], false);
+        assert_eq!(projection.apply(profile()), json!({"profile": {"avatar_url": "a.png"}, "a/b": 1}));
+    }
+
+    #[test]
+    fn client_side_passes_non_objects_through() {
+        let projection = Projection::new(&["name"], false);
+        assert_eq!(projection.apply_all(vec![json!(7), Value::Null, profile()]), [json!(7), Value::Null, json!({"name": "alice"})]);
+        assert_eq!(projection.wire_path(&[json!("alice")]), Cow::Borrowed(&[json!("alice")][..]));
+    }
+
+    #[test]
+    fn server_side_appends_a_multi_path_golden() {
+        let projection = Projection::new(&["name", "/profile/avatar_url", "/a~1b"], true);
+        let path = [json!("alice")];
+        let wire_path = projection.wire_path(&path);
+        assert_eq!(
+            serde_json::to_string(&wire_path).unwrap(),
+            r#"["alice",["multiPath",["name"],["profile","avatar_url"],["a/b"]]]"#
+        );
+    }
+
+    #[test]
+    fn server_side_rebuilds_one_object_per_run_of_values() {
+        let projection = Projection::new(&["name", "/profile/avatar_url"], true);
+        let values = vec![json!("alice"), json!("a.png"), json!("bob"), Value::Null, Value::Null, Value::Null];
+        assert_eq!(
+            projection.apply_all(values),
+            [json!({"name": "alice", "profile": {"avatar_url": "a.png"}}), json!({"name": "bob"}), Value::Null]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::builder::{decode_rama_keyword, decode_rama_long, Keyword};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Number, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A response value with Rama special types decoded: the inverse of `ToRamaValue`.
+///
+/// Strings with a `#__` prefix (`"#__L5"`, `"#__Kid"`, ...) become the matching variant;
+/// everything else maps to the JSON it came from. A `#__` string that isn't a valid
+/// special type (e.g. `"#__Lfive"`) is kept as a `String`.
+///
+/// Map keys are always strings in JSON, so they're kept as text with any special-type
+/// prefix removed (`"#__L5"` becomes `"5"`, `"#__Kid"` becomes `"id"`).
+///
+/// Besides `decode`, a `RamaValue` can be deserialized directly from any self-describing
+/// format, e.g. as the result type of a query answered in MessagePack (see
+/// `ClientBuilder::accept_msgpack`). Special-type strings decode the same way; MessagePack
+/// 32-bit floats become `Float`, and non-string map keys are written as JSON text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RamaValue {
+    Null,
+    Bool(bool),
+    Byte(i8),
+    Short(i16),
+    Long(i64),
+    Float(f32),
+    /// A plain JSON number: an Integer or a Double.
+    Number(Number),
+    Char(char),
+    Keyword(Keyword),
+    /// A function reference, e.g. `"Ops.IS_EVEN"`.
+    Function(String),
+    String(String),
+    List(Vec<RamaValue>),
+    Map(BTreeMap<String, RamaValue>),
+}
+
+impl RamaValue {
+    /// Decodes `value` and everything nested in it.
+    pub fn decode(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(b) => Self::Bool(*b),
+            Value::Number(n) => Self::Number(n.clone()),
+            Value::String(s) => decode_string(s),
+            Value::Array(items) => Self::List(items.iter().map(Self::decode).collect()),
+            Value::Object(map) => Self::Map(map.iter().map(|(key, value)| (plain_key(key), Self::decode(value))).collect()),
+        }
+    }
+
+    /// Plain JSON with the special types unwrapped: numbers for Bytes, Shorts, Longs and
+    /// Floats, strings for Chars, Keywords (without the `:`) and function references.
+    /// This is the form `serde` can deserialize into ordinary Rust types.
+    pub fn into_json(self) -> Value {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => Value::Bool(b),
+            Self::Byte(n) => Value::from(n),
+            Self::Short(n) => Value::from(n),
+            Self::Long(n) => Value::from(n),
+            // Via the shortest decimal form, so 0.1f32 becomes 0.1 rather than 0.10000000149
+            Self::Float(f) => Value::from(f.to_string().parse::<f64>().unwrap_or(f64::NAN)),
+            Self::Number(n) => Value::Number(n),
+            Self::Char(c) => Value::String(c.to_string()),
+            Self::Keyword(Keyword(name)) => Value::String(name),
+            Self::Function(name) | Self::String(name) => Value::String(name),
+            Self::List(items) => Value::Array(items.into_iter().map(Self::into_json).collect()),
+            Self::Map(map) => Value::Object(map.into_iter().map(|(key, value)| (key, value.into_json())).collect()),
+        }
+    }
+}
+
+fn decode_string(s: &str) -> RamaValue {
+    let value = Value::String(s.to_string());
+    let decoded = match s.get(..4) {
+        Some("#__L") => decode_rama_long(&value).map(RamaValue::Long),
+        Some("#__B") => s[4..].parse().ok().map(RamaValue::Byte),
+        Some("#__S") => s[4..].parse().ok().map(RamaValue::Short),
+        Some("#__F") => s[4..].parse().ok().map(RamaValue::Float),
+        Some("#__C") => {
+            let mut chars = s[4..].chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(RamaValue::Char(c)),
+                _ => None,
+            }
+        }
+        Some("#__K") => decode_rama_keyword(&value).filter(|name| !name.is_empty()).map(|name| RamaValue::Keyword(Keyword(name.to_string()))),
+        Some("#__f") => Some(RamaValue::Function(s[4..].to_string())),
+        _ => None,
+    };
+    decoded.unwrap_or_else(|| RamaValue::String(s.to_string()))
+}
+
+// A map key as text, without any special-type prefix.
+fn plain_key(key: &str) -> String {
+    key_text(decode_string(key))
+}
+
+fn key_text(key: RamaValue) -> String {
+    match key.into_json() {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+impl<'de> Deserialize<'de> for RamaValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RamaValueVisitor)
+    }
+}
+
+struct RamaValueVisitor;
+
+impl<'de> Visitor<'de> for RamaValueVisitor {
+    type Value = RamaValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a Rama value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<RamaValue, E> {
+        Ok(RamaValue::Bool(b))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<RamaValue, E> {
+        Ok(RamaValue::Number(n.into()))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> Result<RamaValue, E> {
+        Ok(RamaValue::Number(n.into()))
+    }
+
+    // Only binary formats have single-precision floats, and those mark a Java Float.
+    fn visit_f32<E>(self, f: f32) -> Result<RamaValue, E> {
+        Ok(RamaValue::Float(f))
+    }
+
+    // NaN and infinities have no JSON form, so become Null as in serde_json.
+    fn visit_f64<E>(self, f: f64) -> Result<RamaValue, E> {
+        Ok(Number::from_f64(f).map_or(RamaValue::Null, RamaValue::Number))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<RamaValue, E> {
+        Ok(decode_string(s))
+    }
+
+    // MessagePack `bin`: a list of byte values, as serde_json represents bytes.
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<RamaValue, E> {
+        Ok(RamaValue::List(bytes.iter().map(|b| RamaValue::Number((*b).into())).collect()))
+    }
+
+    fn visit_unit<E>(self) -> Result<RamaValue, E> {
+        Ok(RamaValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<RamaValue, E> {
+        Ok(RamaValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<RamaValue, D::Error> {
+        RamaValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RamaValue, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(RamaValue::List(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RamaValue, A::Error> {
+        let mut entries = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry::<RamaValue, RamaValue>()? {
+            entries.insert(key_text(key), value);
+        }
+        Ok(RamaValue::Map(entries))
+    }
+}
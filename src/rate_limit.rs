@@ -0,0 +1,126 @@
+use crate::logging::debug;
+use std::time::Duration;
+use tokio::sync::Mutex;
+// Refills are timed on the same clock as `rt::sleep`, so they stay in step with the
+// waits (and with paused time in tests).
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+use tokio::time::Instant;
+#[cfg(not(all(not(target_arch = "wasm32"), feature = "tokio")))]
+use crate::rt::Instant;
+
+// Token bucket shared by a client and its clones: holds up to `burst` tokens, refilled
+// at `requests_per_second`. Each logical request takes one token, waiting if none is left.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    per_token: Duration,
+    burst: f64,
+    // tokio's Mutex queues waiters in FIFO order, and a waiter holds the lock while it
+    // sleeps for its token, so requests are let through in the order they arrived.
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: u32, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            per_token: Duration::from_secs(1) / requests_per_second.max(1),
+            burst,
+            bucket: Mutex::new(Bucket { tokens: burst, refilled_at: Instant::now() }),
+        }
+    }
+
+    pub(crate) async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        self.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            let wait = self.per_token.mul_f64(1.0 - bucket.tokens);
+            debug!("Rate limit reached; delaying request by {:?}", wait);
+            crate::rt::sleep(wait).await;
+            self.refill(&mut bucket);
+        }
+        // Sleep granularity can leave us a hair short of a whole token; never go far negative.
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let earned = now.duration_since(bucket.refilled_at).as_secs_f64() / self.per_token.as_secs_f64();
+        bucket.tokens = (bucket.tokens + earned).min(self.burst);
+        bucket.refilled_at = now;
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use crate::testing::FakeCluster;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        cluster
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ten_requests_at_five_per_second_take_two_seconds() {
+        let client = cluster().client_builder().rate_limit(5, 1).build().unwrap();
+        let started = Instant::now();
+        for _ in 0..10 {
+            client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        }
+        // The first goes straight through, the other nine wait 200ms each
+        let elapsed = started.elapsed();
+        assert!((Duration::from_millis(1750)..Duration::from_millis(2050)).contains(&elapsed), "{:?}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_burst_passes_at_once_and_refills_while_idle() {
+        let client = cluster().client_builder().rate_limit(5, 5).build().unwrap();
+        let select = || async { client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap() };
+
+        let started = Instant::now();
+        for _ in 0..5 {
+            select().await;
+        }
+        assert!(started.elapsed() < Duration::from_millis(50), "{:?}", started.elapsed());
+        select().await;
+        assert!(started.elapsed() >= Duration::from_millis(190), "{:?}", started.elapsed());
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let refilled = Instant::now();
+        for _ in 0..5 {
+            select().await;
+        }
+        assert!(refilled.elapsed() < Duration::from_millis(50), "{:?}", refilled.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_requests_share_the_bucket() {
+        let client = cluster().client_builder().rate_limit(10, 1).build().unwrap();
+        let started = Instant::now();
+        let selects = (0..10).map(|_| client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>());
+        for result in futures::future::join_all(selects).await {
+            result.unwrap();
+        }
+        let elapsed = started.elapsed();
+        assert!((Duration::from_millis(850)..Duration::from_millis(1050)).contains(&elapsed), "{:?}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unlimited_by_default() {
+        let client = cluster().client();
+        let started = Instant::now();
+        for _ in 0..50 {
+            client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        }
+        assert!(started.elapsed() < Duration::from_millis(50), "{:?}", started.elapsed());
+    }
+}
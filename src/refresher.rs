@@ -0,0 +1,147 @@
+use crate::logging::{debug, info, warn};
+use crate::{rt, Client};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Handle to a background supervisor cache refresher started by
+/// `Client::spawn_cache_refresher`.
+///
+/// The refresher runs until `abort` is called or the handle is dropped, so keep the handle
+/// for as long as the cache should be kept warm.
+#[derive(Debug)]
+pub struct RefresherHandle {
+    stop: Arc<Notify>,
+}
+
+impl RefresherHandle {
+    /// Stops the refresher. A refresh round in progress is abandoned; entries it already
+    /// updated stay updated.
+    pub fn abort(&self) {
+        // Stores a permit if the task isn't waiting right now, so the stop isn't lost.
+        self.stop.notify_one();
+    }
+}
+
+impl Drop for RefresherHandle {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+impl Client {
+    /// Starts a background task that re-discovers the supervisors of every module in the
+    /// supervisor cache once per `interval` (see `discover_supervisors`), so the cache stays
+    /// current without waiting for live traffic to hit a 308.
+    ///
+    /// A successful discovery replaces the module's entry; a failed one leaves it as it was,
+    /// since stale locations are better than none. Changed locations are logged at info.
+    /// The task stops when the returned handle is aborted or dropped.
+    pub fn spawn_cache_refresher(&self, interval: Duration) -> RefresherHandle {
+        let stop = Arc::new(Notify::new());
+        let client = self.clone();
+        let stopped = stop.clone();
+        // The output is (), so nobody needs the receiver.
+        drop(rt::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = stopped.notified() => break,
+                    _ = async {
+                        rt::sleep(interval).await;
+                        client.refresh_supervisor_cache().await;
+                    } => {}
+                }
+            }
+            debug!("Supervisor cache refresher stopped");
+        }));
+        RefresherHandle { stop }
+    }
+
+    // One refresh round over the modules cached right now, in sequence.
+    async fn refresh_supervisor_cache(&self) {
+        let modules: Vec<String> = self.supervisor_cache.lock().unwrap().keys().cloned().collect();
+        debug!("Refreshing supervisor cache for {} module(s)", modules.len());
+        for module in modules {
+            let before = self.supervisor_cache.lock().unwrap().get(&module).map(|entry| entry.supervisors.clone());
+            match self.discover_supervisors(&module).await {
+                Ok(supervisors) if before.as_ref() != Some(&supervisors) && !supervisors.is_empty() => {
+                    info!("Supervisors for module '{}' changed: {:?} -> {:?}", module, before.unwrap_or_default(), supervisors);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("[{}] Refreshing supervisors for module '{}' failed; keeping cached entry: {}", e.code(), module, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{Reply, Scripted};
+    use crate::Client;
+    use reqwest::StatusCode;
+    use serde_json::Value;
+    use std::time::Duration;
+
+    const SUPERVISOR_1: &str = "supervisor-1:1984";
+    const SUPERVISOR_2: &str = "supervisor-2:1984";
+
+    fn cached(client: &Client) -> Option<Vec<String>> {
+        client.export_supervisor_cache().modules.get("profiles").map(|entry| entry.supervisors.clone())
+    }
+
+    // Lets the refresher run the rounds due in the next `rounds` intervals.
+    async fn run_rounds(rounds: u32) {
+        for _ in 0..rounds {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn the_cache_tracks_changed_supervisor_locations() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR_1)]);
+        script.script(SUPERVISOR_1, [Reply::ok([30])]);
+        let client = script.client_builder().build().unwrap();
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+        assert_eq!(cached(&client).unwrap(), [SUPERVISOR_1]);
+
+        let refresher = client.spawn_cache_refresher(Duration::from_secs(10));
+        script.script(Scripted::CONDUCTOR, [Reply::Redirect(SUPERVISOR_2, vec![SUPERVISOR_2, SUPERVISOR_1])]);
+        run_rounds(1).await;
+        assert_eq!(cached(&client).unwrap(), [SUPERVISOR_2, SUPERVISOR_1]);
+
+        // A failed discovery keeps the entry
+        script.script(Scripted::CONDUCTOR, [Reply::Status(StatusCode::SERVICE_UNAVAILABLE, vec![], String::new())]);
+        run_rounds(2).await;
+        assert_eq!(cached(&client).unwrap(), [SUPERVISOR_2, SUPERVISOR_1]);
+
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR_1)]);
+        run_rounds(1).await;
+        assert_eq!(cached(&client).unwrap(), [SUPERVISOR_1]);
+
+        // Nothing changes once the refresher is stopped
+        refresher.abort();
+        let sent = script.hosts().len();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR_2)]);
+        run_rounds(3).await;
+        assert_eq!(cached(&client).unwrap(), [SUPERVISOR_1]);
+        assert_eq!(script.hosts().len(), sent);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropping_the_handle_stops_the_refresher() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR_1)]);
+        script.script(SUPERVISOR_1, [Reply::ok([30])]);
+        let client = script.client_builder().build().unwrap();
+        client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap();
+
+        drop(client.spawn_cache_refresher(Duration::from_secs(10)));
+        let sent = script.hosts().len();
+        run_rounds(3).await;
+        assert_eq!(script.hosts().len(), sent);
+    }
+}
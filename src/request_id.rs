@@ -0,0 +1,41 @@
+use std::future::Future;
+
+// The ID of the logical request running on the current task. Hedged attempts and
+// retries run inside the same scope, so they share one ID.
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// A random (version 4) UUID in its hyphenated form, without needing the `uuid` feature.
+pub(crate) fn generate() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+pub(crate) fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+// Runs `fut` with `id` as its request ID. Without one, an enclosing scope's ID is kept,
+// or a new one generated.
+pub(crate) async fn scope<F: Future>(id: Option<String>, fut: F) -> F::Output {
+    let id = id.or_else(current).unwrap_or_else(generate);
+    REQUEST_ID.scope(id, fut).await
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn generated_ids_are_v4_uuids() {
+        let id = super::generate();
+        let groups: Vec<&str> = id.split('-').collect();
+        assert_eq!(groups.iter().map(|g| g.len()).collect::<Vec<_>>(), [8, 4, 4, 4, 12]);
+        assert!(id.chars().all(|c| c == '-' || matches!(c, '0'..='9' | 'a'..='f')), "{}", id);
+        assert!(groups[2].starts_with('4'), "{}", id);
+        assert!(groups[3].starts_with(['8', '9', 'a', 'b']), "{}", id);
+        assert_ne!(super::generate(), id);
+    }
+}
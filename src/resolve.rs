@@ -0,0 +1,125 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+// A resolver set via `ClientBuilder::dns_resolver`, wrapped so the builder can derive `Debug`.
+#[derive(Clone)]
+pub(crate) struct DnsResolver(pub(crate) Arc<dyn Resolve>);
+
+impl fmt::Debug for DnsResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DnsResolver")
+    }
+}
+
+impl Resolve for DnsResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.0.resolve(name)
+    }
+}
+
+// Resolves through `inner` (or the system resolver), then moves IPv4 addresses ahead of
+// IPv6 ones. The connector tries the family of the first address and only falls back to
+// the other (after its happy-eyeballs delay), so a blackholed IPv6 route no longer slows
+// down the first connection to a dual-stack host.
+#[derive(Debug)]
+pub(crate) struct PreferIpv4 {
+    inner: Option<DnsResolver>,
+}
+
+impl PreferIpv4 {
+    pub(crate) fn new(inner: Option<DnsResolver>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Resolve for PreferIpv4 {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolving = match &self.inner {
+            Some(inner) => inner.resolve(name),
+            None => system_resolve(name),
+        };
+        Box::pin(async move {
+            let (mut v4, v6): (Vec<SocketAddr>, Vec<SocketAddr>) = resolving.await?.partition(SocketAddr::is_ipv4);
+            v4.extend(v6);
+            Ok(Box::new(v4.into_iter()) as Addrs)
+        })
+    }
+}
+
+// getaddrinfo on a blocking thread, as reqwest's default resolver does.
+fn system_resolve(name: Name) -> Resolving {
+    Box::pin(async move {
+        let addrs = tokio::task::spawn_blocking(move || (name.as_str(), 0).to_socket_addrs())
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)??;
+        Ok(Box::new(addrs) as Addrs)
+    })
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::PreferIpv4;
+    use crate::testing::MockServer;
+    use crate::ClientBuilder;
+    use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    // Answers every name with `addrs`, recording the names asked for.
+    #[derive(Default)]
+    struct FakeResolver {
+        addrs: Vec<SocketAddr>,
+        names: Mutex<Vec<String>>,
+    }
+
+    impl FakeResolver {
+        fn new(addrs: &[&str]) -> Arc<Self> {
+            Arc::new(Self { addrs: addrs.iter().map(|addr| addr.parse().unwrap()).collect(), ..Self::default() })
+        }
+
+        fn names(&self) -> Vec<String> {
+            self.names.lock().unwrap().clone()
+        }
+    }
+
+    impl Resolve for FakeResolver {
+        fn resolve(&self, name: Name) -> Resolving {
+            self.names.lock().unwrap().push(name.as_str().to_string());
+            let addrs = self.addrs.clone();
+            Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) })
+        }
+    }
+
+    #[tokio::test]
+    async fn ipv4_addresses_move_ahead_keeping_their_order() {
+        let fake = FakeResolver::new(&["[2001:db8::1]:0", "10.0.0.1:0", "[2001:db8::2]:0", "10.0.0.2:0"]);
+        let resolver = PreferIpv4::new(Some(super::DnsResolver(fake.clone())));
+        let addrs: Vec<String> = resolver.resolve(Name::from_str("supervisor-1.test").unwrap()).await.unwrap().map(|addr| addr.to_string()).collect();
+        assert_eq!(addrs, ["10.0.0.1:0", "10.0.0.2:0", "[2001:db8::1]:0", "[2001:db8::2]:0"]);
+        assert_eq!(fake.names(), ["supervisor-1.test"]);
+    }
+
+    #[tokio::test]
+    async fn the_resolver_is_consulted_for_the_conductor_and_supervisors() {
+        let supervisor = MockServer::start(|_| (200, Vec::new(), b"[30]".to_vec())).await;
+        let supervisor_host = format!("supervisor-1.test:{}", supervisor.url.rsplit(':').next().unwrap());
+        let conductor = MockServer::start(move |_| {
+            let location = format!("http://{}/rest/profiles/pstate/$$profiles/select", supervisor_host);
+            (308, vec![("location", location), ("supervisor-locations", format!("[\"{}\"]", supervisor_host))], Vec::new())
+        })
+        .await;
+        let conductor_url = conductor.url.replace("127.0.0.1", "rama-conductor.test");
+
+        for prefer_ipv4 in [false, true] {
+            // Unresolvable names; only the fake knows them
+            let fake = FakeResolver::new(&["127.0.0.1:0"]);
+            let client = ClientBuilder::new(&conductor_url).dns_resolver(fake.clone()).prefer_ipv4(prefer_ipv4).build().unwrap();
+            let ages: Vec<u32> = client.pstate_query("profiles", "$$profiles").key("alice").select().await.unwrap();
+            assert_eq!(ages, [30]);
+            assert_eq!(fake.names(), ["rama-conductor.test", "supervisor-1.test"]);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::ClientError;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use crate::rt::{SystemTime, UNIX_EPOCH};
+use std::time::Duration;
+
+/// When and how long to wait before retrying a request the server turned away.
+///
+/// Only 429 (Too Many Requests) and 503 (Service Unavailable) responses are retried:
+/// the server didn't process those requests, so retrying is safe even for depot appends.
+/// Each retry re-runs the whole request, including routing.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt. 0 fails fast.
+    pub max_retries: u32,
+    /// Wait before the first retry when the server gives no `Retry-After`; doubles per retry.
+    pub initial_backoff: Duration,
+    /// Cap on the doubling backoff.
+    pub max_backoff: Duration,
+    /// Whether to wait as long as a `Retry-After` header asks instead of backing off.
+    pub honor_retry_after: bool,
+    /// Cap on a `Retry-After` wait, so a misbehaving server can't stall the client.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            honor_retry_after: true,
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Self::default() }
+    }
+
+    // How long to wait before retry number `retry` (0-based), or None to give up.
+    pub(crate) fn delay_for(&self, err: &ClientError, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        // Guard: Out of retries
+        if retry >= self.max_retries {
+            return None;
+        }
+
+        // Guard: Not a "come back later" response
+        let ClientError::UnexpectedStatus(status, _) = err else { return None };
+        if *status != StatusCode::TOO_MANY_REQUESTS && *status != StatusCode::SERVICE_UNAVAILABLE {
+            return None;
+        }
+
+        match retry_after {
+            Some(wait) if self.honor_retry_after => Some(wait.min(self.max_retry_after)),
+            _ => Some(self.initial_backoff.saturating_mul(2u32.saturating_pow(retry)).min(self.max_backoff)),
+        }
+    }
+}
+
+// Parses `Retry-After` as delta-seconds or an HTTP-date. Dates in the past mean "now".
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    // Compared as offsets from the epoch: httpdate uses std's clock, which browsers lack.
+    let date = httpdate::parse_http_date(value).ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    Some(date.saturating_sub(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_retry_after, RetryPolicy};
+    use crate::ClientError;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::StatusCode;
+    use std::time::{Duration, SystemTime};
+
+    fn retry_after(value: &str) -> Option<Duration> {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        parse_retry_after(&headers)
+    }
+
+    fn status(code: StatusCode) -> ClientError {
+        ClientError::UnexpectedStatus(code, "http://supervisor-1:1984/rest/m/pstate/$$p/select".to_string())
+    }
+
+    #[test]
+    fn parses_delta_seconds() {
+        assert_eq!(retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after(" 0 "), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn parses_http_dates() {
+        let in_ten_minutes = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(600));
+        let wait = retry_after(&in_ten_minutes).unwrap();
+        assert!(wait > Duration::from_secs(590) && wait <= Duration::from_secs(600), "{:?}", wait);
+        // A date already past means "now"
+        let a_minute_ago = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+        assert_eq!(retry_after(&a_minute_ago), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn ignores_missing_and_malformed_values() {
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+        assert_eq!(retry_after("soon"), None);
+        assert_eq!(retry_after("-5"), None);
+    }
+
+    #[test]
+    fn honors_retry_after_up_to_the_cap() {
+        let policy = RetryPolicy { max_retry_after: Duration::from_secs(30), ..RetryPolicy::default() };
+        let unavailable = status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(policy.delay_for(&unavailable, 0, Some(Duration::from_secs(5))), Some(Duration::from_secs(5)));
+        let absurd = retry_after("999999999");
+        assert_eq!(policy.delay_for(&unavailable, 0, absurd), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn backs_off_without_retry_after() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            max_retries: 10,
+            ..RetryPolicy::default()
+        };
+        let too_many = status(StatusCode::TOO_MANY_REQUESTS);
+        let delays: Vec<_> = (0..4).map(|retry| policy.delay_for(&too_many, retry, None).unwrap().as_millis()).collect();
+        assert_eq!(delays, [100, 200, 300, 300]);
+
+        let ignoring = RetryPolicy { honor_retry_after: false, ..policy };
+        assert_eq!(ignoring.delay_for(&too_many, 0, Some(Duration::from_secs(5))), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn only_retries_429_and_503_within_the_budget() {
+        let policy = RetryPolicy { max_retries: 2, ..RetryPolicy::default() };
+        let unavailable = status(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(policy.delay_for(&unavailable, 1, None).is_some());
+        assert_eq!(policy.delay_for(&unavailable, 2, None), None);
+        assert_eq!(policy.delay_for(&status(StatusCode::INTERNAL_SERVER_ERROR), 0, None), None);
+        assert_eq!(policy.delay_for(&ClientError::MaxRedirectsExceeded, 0, None), None);
+        assert_eq!(RetryPolicy::none().delay_for(&unavailable, 0, None), None);
+    }
+}
@@ -0,0 +1,69 @@
+// Runtime primitives, so the core doesn't depend on an executor:
+// - natively with the `tokio` feature (the default), Tokio's timers and `tokio::spawn`;
+// - natively without it, `futures-timer` and a thread per background task;
+// - in browsers (wasm32), JS event-loop timers and tasks, and time from `web-time`.
+// Everything else the client uses from tokio (`sync`, `select!`, `task_local!`) works on
+// any executor.
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+// The deadline passed before the future finished. See `timeout`.
+#[derive(Debug)]
+pub(crate) struct Elapsed;
+
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(all(not(target_arch = "wasm32"), not(feature = "tokio")))]
+    futures_timer::Delay::new(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
+
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    tokio::select! {
+        output = future => Ok(output),
+        _ = sleep(duration) => Err(Elapsed),
+    }
+}
+
+// Runs `future` in the background; its output arrives on the returned receiver. The
+// receiver errors if the task panicked.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn<F>(future: F) -> tokio::sync::oneshot::Receiver<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let task = async move {
+        // Nobody waiting for the output is fine.
+        let _ = sender.send(future.await);
+    };
+    #[cfg(feature = "tokio")]
+    tokio::spawn(task);
+    // No executor to borrow: background tasks (stale refreshes, appender workers) are
+    // rare and long-lived enough to get a thread each.
+    #[cfg(not(feature = "tokio"))]
+    std::thread::spawn(move || futures::executor::block_on(task));
+    receiver
+}
+
+// Browser futures (e.g. `fetch`) aren't `Send`, so tasks run on the local event loop.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn spawn<F>(future: F) -> tokio::sync::oneshot::Receiver<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = sender.send(future.await);
+    });
+    receiver
+}
@@ -0,0 +1,184 @@
+use crate::builder::PreparedQuery;
+use crate::logging::{debug, warn};
+use crate::{Client, ClientError};
+use futures::future::join_all;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use crate::rt::Instant;
+use std::time::Duration;
+
+/// Options for `Client::snapshot`.
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    /// How many verification passes to run after the initial pass. Each pass re-runs the
+    /// queries whose result changed in the previous pass (the first re-runs all of them).
+    pub verification_passes: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self { verification_passes: 1 }
+    }
+}
+
+/// Results of a multi-query snapshot, in the order the queries were given.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub values: Vec<Value>,
+    pub report: ConsistencyReport,
+}
+
+/// How consistent a snapshot's results are with each other.
+///
+/// Rama has no multi-PState read transactions, so a snapshot can't be atomic.
+/// This report describes how far it may be from one.
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyReport {
+    /// Spread between the earliest and latest client-side completion times of the returned values.
+    pub max_skew: Duration,
+    /// Indexes of queries whose result changed during verification.
+    pub unstable: Vec<usize>,
+    /// Indexes of queries whose result was still changing on the final verification pass.
+    pub still_unstable: Vec<usize>,
+    /// Number of verification passes actually run (stops early once nothing changes).
+    pub passes_run: usize,
+}
+
+impl ConsistencyReport {
+    /// True if no query changed during verification.
+    pub fn is_stable(&self) -> bool {
+        self.unstable.is_empty()
+    }
+}
+
+impl Client {
+    /// Runs several queries concurrently so their results reflect roughly the same moment,
+    /// then re-runs them to detect state that was changing mid-snapshot.
+    ///
+    /// Any query failing fails the whole snapshot. Timings are client-side completion
+    /// times, as the REST API doesn't expose server timestamps.
+    pub async fn snapshot(&self, queries: Vec<PreparedQuery>, opts: SnapshotOptions) -> Result<Snapshot, ClientError> {
+        // --- Initial pass ---
+        let mut values = Vec::with_capacity(queries.len());
+        let mut completed_at = Vec::with_capacity(queries.len());
+        for (value, at) in self.run_timed(&queries, &(0..queries.len()).collect::<Vec<_>>()).await? {
+            values.push(value);
+            completed_at.push(at);
+        }
+
+        // --- Verification passes ---
+        let mut unstable = BTreeSet::new();
+        let mut to_check: Vec<usize> = (0..queries.len()).collect();
+        let mut passes_run = 0;
+        while passes_run < opts.verification_passes && !to_check.is_empty() {
+            passes_run += 1;
+            let results = self.run_timed(&queries, &to_check).await?;
+
+            let mut changed = Vec::new();
+            for (index, (value, at)) in to_check.iter().copied().zip(results) {
+                if value != values[index] {
+                    debug!("Snapshot query {} changed during verification pass {}", index, passes_run);
+                    changed.push(index);
+                    unstable.insert(index);
+                }
+                values[index] = value;
+                completed_at[index] = at;
+            }
+            to_check = changed;
+        }
+
+        if !unstable.is_empty() {
+            warn!("Snapshot of {} queries has unstable results: {:?}", queries.len(), unstable);
+        }
+
+        let max_skew = match (completed_at.iter().min(), completed_at.iter().max()) {
+            (Some(first), Some(last)) => *last - *first,
+            _ => Duration::ZERO,
+        };
+        Ok(Snapshot {
+            values,
+            report: ConsistencyReport {
+                max_skew,
+                unstable: unstable.into_iter().collect(),
+                still_unstable: to_check,
+                passes_run,
+            },
+        })
+    }
+
+    // Runs the queries at `indexes` concurrently, returning each value with its completion time.
+    async fn run_timed(&self, queries: &[PreparedQuery], indexes: &[usize]) -> Result<Vec<(Value, Instant)>, ClientError> {
+        let runs = indexes.iter().map(|&index| async move {
+            let value = queries[index].execute::<Value>(self).await?;
+            Ok::<_, ClientError>((value, Instant::now()))
+        });
+        join_all(runs).await.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotOptions;
+    use crate::builder::PreparedQuery;
+    use crate::testing::{Reply, Scripted};
+    use crate::Client;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    // A client with module `counters` cached on supervisor 1 and `profiles` on supervisor 2.
+    async fn client() -> (Arc<Scripted>, Client, Vec<PreparedQuery>) {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984"), Reply::redirect("supervisor-2:1984")]);
+        script.script("supervisor-1:1984", [Reply::ok([0])]);
+        script.script("supervisor-2:1984", [Reply::ok([0])]);
+        let client = script.client_builder().build().unwrap();
+        let queries = vec![
+            client.pstate_query("counters", "$$counts").key("edits").prepare(),
+            client.pstate_query("profiles", "$$profiles").key("alice").prepare(),
+        ];
+        for query in &queries {
+            query.execute::<Value>(&client).await.unwrap();
+        }
+        (script, client, queries)
+    }
+
+    fn passes(verification_passes: usize) -> SnapshotOptions {
+        SnapshotOptions { verification_passes }
+    }
+
+    #[tokio::test]
+    async fn stable_results_need_one_verification_pass() {
+        let (_, client, queries) = client().await;
+        let snapshot = client.snapshot(queries, passes(3)).await.unwrap();
+        assert_eq!(snapshot.values, [json!([0]), json!([0])]);
+        assert!(snapshot.report.is_stable());
+        assert_eq!(snapshot.report.passes_run, 1);
+    }
+
+    #[tokio::test]
+    async fn detects_a_result_that_changed_between_passes() {
+        let (script, client, queries) = client().await;
+        script.script("supervisor-1:1984", [Reply::ok([1]), Reply::ok([2])]);
+        script.script("supervisor-2:1984", [Reply::ok([7])]);
+
+        let snapshot = client.snapshot(queries, passes(3)).await.unwrap();
+        assert_eq!(snapshot.values, [json!([2]), json!([7])]);
+        assert!(!snapshot.report.is_stable());
+        assert_eq!(snapshot.report.unstable, [0]);
+        assert!(snapshot.report.still_unstable.is_empty());
+        // The second pass re-ran only the changed query, and it had settled
+        assert_eq!(snapshot.report.passes_run, 2);
+    }
+
+    #[tokio::test]
+    async fn reports_results_still_changing_after_the_last_pass() {
+        let (script, client, queries) = client().await;
+        script.script("supervisor-1:1984", [Reply::ok([1]), Reply::ok([2]), Reply::ok([3])]);
+        script.script("supervisor-2:1984", [Reply::ok([7])]);
+
+        let snapshot = client.snapshot(queries, passes(2)).await.unwrap();
+        assert_eq!(snapshot.values, [json!([3]), json!([7])]);
+        assert_eq!((snapshot.report.unstable.as_slice(), snapshot.report.still_unstable.as_slice()), (&[0][..], &[0][..]));
+        assert_eq!(snapshot.report.passes_run, 2);
+    }
+}
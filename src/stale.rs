@@ -0,0 +1,212 @@
+use crate::budget::{self, BudgetTracker, Subsystem};
+use crate::logging::debug;
+use crate::ClientError;
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use crate::rt::Instant;
+use std::time::Duration;
+
+/// Graceful degradation policy for reads.
+///
+/// When set on the client, successful `select_or_stale` / `select_one_or_stale` results are
+/// remembered. If a later identical read fails with a retryable error (see
+/// `ClientError::is_retryable`), the remembered result is returned instead, provided it is no
+/// older than `max_staleness`, and a background refresh is started to repopulate it.
+/// Depot appends are never served stale.
+///
+/// Results older than `max_staleness` are dropped, and at most 10,000 are kept (fewer under
+/// a `MemoryBudget`), oldest evicted first.
+#[derive(Debug, Clone, Copy)]
+pub struct ServeStale {
+    pub max_staleness: Duration,
+}
+
+/// A result together with where it came from.
+#[derive(Debug)]
+pub struct WithMeta<R> {
+    pub value: R,
+    /// True if `value` is a last-known-good result rather than a fresh response.
+    pub stale: bool,
+    /// The error that caused a stale result to be served, if any.
+    pub warning: Option<ClientError>,
+}
+
+impl<R> WithMeta<R> {
+    pub(crate) fn fresh(value: R) -> Self {
+        Self { value, stale: false, warning: None }
+    }
+
+    /// Converts the value while keeping the metadata.
+    pub fn map<U>(self, f: impl FnOnce(R) -> U) -> WithMeta<U> {
+        WithMeta { value: f(self.value), stale: self.stale, warning: self.warning }
+    }
+}
+
+// Key: (module, path_suffix, serialized request body)
+type StaleKey = (String, String, Bytes);
+
+// Most results kept, memory budget or not: expired results are pruned, but a client reading
+// many distinct keys within `max_staleness` could otherwise grow the store without limit.
+const MAX_ENTRIES: usize = 10_000;
+
+// Last-known-good read results, shared by all clones of a client.
+#[derive(Debug)]
+pub(crate) struct StaleStore {
+    entries: Mutex<HashMap<StaleKey, (Instant, Value)>>,
+    // Keys with a background refresh in flight, so an outage doesn't spawn one per read.
+    refreshing: Mutex<HashSet<StaleKey>>,
+    budget: Arc<BudgetTracker>,
+    // `ServeStale::max_staleness`: older results can never be served, so they're dropped.
+    max_staleness: Duration,
+    // When expired results were last swept out (at most once per `max_staleness`)
+    pruned_at: Mutex<Instant>,
+    max_entries: usize,
+}
+
+impl StaleStore {
+    pub(crate) fn new(budget: Arc<BudgetTracker>, max_staleness: Duration) -> Self {
+        Self {
+            entries: Mutex::default(),
+            refreshing: Mutex::default(),
+            budget,
+            max_staleness,
+            pruned_at: Mutex::new(Instant::now()),
+            max_entries: MAX_ENTRIES,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: StaleKey, value: Value) {
+        let mut entries = self.entries.lock().unwrap();
+        self.budget.add(Subsystem::StaleResults, entry_size(&key, &value));
+        if let Some((_, old)) = entries.insert(key.clone(), (Instant::now(), value)) {
+            self.budget.remove(Subsystem::StaleResults, entry_size(&key, &old));
+        }
+
+        // --- Expiry: drop results too old to ever be served ---
+        let mut pruned_at = self.pruned_at.lock().unwrap();
+        if pruned_at.elapsed() >= self.max_staleness {
+            let expired: Vec<StaleKey> = entries.iter().filter(|(_, (at, _))| at.elapsed() > self.max_staleness).map(|(k, _)| k.clone()).collect();
+            for key in expired {
+                self.remove(&mut entries, &key);
+            }
+            *pruned_at = Instant::now();
+        }
+
+        // --- Eviction pressure: drop the oldest results until under budget and the cap ---
+        while (self.budget.over_budget() || entries.len() > self.max_entries) && entries.len() > 1 {
+            let Some(oldest) = entries.iter().min_by_key(|(_, (at, _))| *at).map(|(k, _)| k.clone()) else {
+                break;
+            };
+            debug!("Stale result store full; evicting stale result for module '{}', path '{}'", oldest.0, oldest.1);
+            self.remove(&mut entries, &oldest);
+        }
+    }
+
+    // Returns the stored value if it is no older than `max_staleness`, dropping it if it is.
+    pub(crate) fn get(&self, key: &StaleKey) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        let (stored_at, value) = entries.get(key)?;
+        if stored_at.elapsed() <= self.max_staleness {
+            return Some(value.clone());
+        }
+        self.remove(&mut entries, key);
+        None
+    }
+
+    fn remove(&self, entries: &mut HashMap<StaleKey, (Instant, Value)>, key: &StaleKey) {
+        if let Some((_, removed)) = entries.remove(key) {
+            self.budget.remove(Subsystem::StaleResults, entry_size(key, &removed));
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    // Marks a refresh as in flight. Returns false if one already is.
+    pub(crate) fn begin_refresh(&self, key: &StaleKey) -> bool {
+        self.refreshing.lock().unwrap().insert(key.clone())
+    }
+
+    pub(crate) fn end_refresh(&self, key: &StaleKey) {
+        self.refreshing.lock().unwrap().remove(key);
+    }
+}
+
+fn entry_size((module, path_suffix, body): &StaleKey, value: &Value) -> usize {
+    module.len() + path_suffix.len() + body.len() + budget::value_size(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt;
+    use crate::testing::FakeCluster;
+    use crate::{MemoryBudget, RetryPolicy};
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    fn key(n: usize) -> StaleKey {
+        ("profiles".to_string(), "pstate/$$profiles/select".to_string(), Bytes::from(format!("[{n}]")))
+    }
+
+    fn store(max_staleness: Duration, budget: Option<MemoryBudget>) -> StaleStore {
+        StaleStore::new(Arc::new(BudgetTracker::new(budget)), max_staleness)
+    }
+
+    #[tokio::test]
+    async fn expired_results_are_dropped_on_get_and_insert() {
+        let store = store(Duration::from_millis(50), None);
+        store.insert(key(1), json!(1));
+        store.insert(key(2), json!(2));
+        assert_eq!(store.get(&key(1)), Some(json!(1)));
+
+        rt::sleep(Duration::from_millis(80)).await;
+        assert_eq!(store.get(&key(1)), None);
+        assert_eq!(store.len(), 1);
+        store.insert(key(3), json!(3));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.budget.usage().stale_results_bytes, entry_size(&key(3), &json!(3)));
+    }
+
+    #[test]
+    fn store_is_bounded_without_a_budget() {
+        let mut store = store(Duration::from_secs(60), None);
+        store.max_entries = 3;
+        for n in 0..5 {
+            store.insert(key(n), json!(n));
+        }
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.get(&key(1)), None);
+        assert_eq!(store.get(&key(4)), Some(json!(4)));
+    }
+
+    #[tokio::test]
+    async fn serves_stale_until_max_staleness_then_surfaces_the_error() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let client = cluster
+            .client_builder()
+            .serve_stale(ServeStale { max_staleness: Duration::from_millis(100) })
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        let select = || client.pstate_query("profiles", "$$profiles").key("alice").select_or_stale::<u32>();
+
+        let fresh = select().await.unwrap();
+        assert_eq!((fresh.value, fresh.stale), (vec![30], false));
+
+        // The cluster goes down
+        cluster.respond_with("profiles", "pstate/$$profiles/select", StatusCode::SERVICE_UNAVAILABLE, "down");
+        let stale = select().await.unwrap();
+        assert_eq!((stale.value, stale.stale), (vec![30], true));
+        assert!(stale.warning.is_some_and(|e| e.is_retryable()));
+
+        rt::sleep(Duration::from_millis(150)).await;
+        let error = select().await.unwrap_err();
+        assert!(matches!(error.kind(), ClientError::UnexpectedStatus(StatusCode::SERVICE_UNAVAILABLE, _)), "{error:?}");
+    }
+}
@@ -0,0 +1,528 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::{Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use crate::rt::Instant;
+use serde_json::Value;
+
+// --- Supervisor Cache Entry ---
+
+// Supervisor locations learned for one module, and when they were learned.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheEntry {
+    // Every supervisor once, for requests not routed by partition
+    pub(crate) supervisors: Vec<String>,
+    pub(crate) locations: SupervisorLocations,
+    pub(crate) cached_at: Instant,
+}
+
+impl CacheEntry {
+    pub(crate) fn new(locations: SupervisorLocations) -> Self {
+        Self::learned_at(locations, Instant::now())
+    }
+
+    pub(crate) fn learned_at(locations: SupervisorLocations, cached_at: Instant) -> Self {
+        Self { supervisors: locations.supervisors(), locations, cached_at }
+    }
+
+    // Bytes of strings held, for the memory budget.
+    pub(crate) fn strings_size(&self) -> usize {
+        crate::budget::strings_size(&self.supervisors) + self.locations.strings_size()
+    }
+}
+
+// --- Supervisor-Locations Header ---
+
+// The supervisors announced in one `Supervisor-Locations` header, in any of the shapes
+// conductors send.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SupervisorLocations {
+    // `["host:port", ...]`
+    List(Vec<String>),
+    // `[["host:port", ...], ...]`: the supervisors of each partition (index = partition)
+    Partitioned(Vec<Vec<String>>),
+    // `{"0": "host:port", ...}`: the supervisor of each task group. A task group owns
+    // the partition with its index.
+    Tasks(BTreeMap<u32, String>),
+}
+
+impl SupervisorLocations {
+    // Entries are normalized to `host:port`; ones that can't be are dropped with a warning,
+    // so one bad entry doesn't cost the whole list.
+    pub(crate) fn parse(header: &str) -> Result<Self, serde_json::Error> {
+        let value: Value = serde_json::from_str(header)?;
+        let locations = match value {
+            Value::Object(map) => {
+                let tasks = map
+                    .into_iter()
+                    .map(|(task, supervisor)| {
+                        let task = task.parse::<u32>().map_err(|_| serde::de::Error::custom(format!("task group '{}' isn't an index", task)))?;
+                        Ok((task, serde_json::from_value(supervisor)?))
+                    })
+                    .collect::<Result<BTreeMap<u32, String>, serde_json::Error>>()?;
+                Self::Tasks(tasks.into_iter().filter_map(|(task, entry)| Some((task, normalize_entry(&entry)?))).collect())
+            }
+            Value::Array(ref items) if !items.is_empty() && items.iter().all(Value::is_array) => {
+                let partitions: Vec<Vec<String>> = serde_json::from_value(value)?;
+                Self::Partitioned(partitions.iter().map(|partition| normalize_entries(partition)).collect())
+            }
+            value => Self::List(normalize_entries(&serde_json::from_value::<Vec<String>>(value)?)),
+        };
+        Ok(locations)
+    }
+
+    // Every supervisor once, in header order (task order for `Tasks`).
+    pub(crate) fn supervisors(&self) -> Vec<String> {
+        let all: Vec<&String> = match self {
+            Self::List(supervisors) => supervisors.iter().collect(),
+            Self::Partitioned(partitions) => partitions.iter().flatten().collect(),
+            Self::Tasks(tasks) => tasks.values().collect(),
+        };
+        let mut supervisors: Vec<String> = Vec::with_capacity(all.len());
+        for supervisor in all {
+            if !supervisors.contains(supervisor) {
+                supervisors.push(supervisor.clone());
+            }
+        }
+        supervisors
+    }
+
+    // The supervisors of partition `index`, if the header says which they are.
+    pub(crate) fn partition(&self, index: u32) -> Option<Vec<String>> {
+        match self {
+            Self::List(_) => None,
+            Self::Partitioned(partitions) => partitions.get(index as usize).filter(|p| !p.is_empty()).cloned(),
+            Self::Tasks(tasks) => tasks.get(&index).map(|supervisor| vec![supervisor.clone()]),
+        }
+    }
+
+    // Per-partition supervisors (index = partition; empty where unknown), or None for a
+    // plain list.
+    pub(crate) fn partitions(&self) -> Option<Vec<Vec<String>>> {
+        match self {
+            Self::List(_) => None,
+            Self::Partitioned(partitions) => Some(partitions.clone()),
+            Self::Tasks(tasks) => {
+                let count = tasks.keys().next_back().map_or(0, |last| *last as usize + 1);
+                let mut partitions = vec![Vec::new(); count];
+                for (task, supervisor) in tasks {
+                    partitions[*task as usize].push(supervisor.clone());
+                }
+                Some(partitions)
+            }
+        }
+    }
+
+    fn strings_size(&self) -> usize {
+        match self {
+            Self::List(supervisors) => crate::budget::strings_size(supervisors),
+            Self::Partitioned(partitions) => partitions.iter().map(|p| crate::budget::strings_size(p)).sum(),
+            Self::Tasks(tasks) => tasks.values().map(|s| std::mem::size_of::<(u32, String)>() + s.len()).sum(),
+        }
+    }
+}
+
+// --- Supervisor Rewriting ---
+
+/// Where to connect instead of an announced supervisor, returned by a
+/// `ClientBuilder::supervisor_rewriter` callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketTarget {
+    /// Hostname or IP address; IPv6 addresses may be given with or without brackets.
+    pub host: String,
+    pub port: u16,
+}
+
+impl SocketTarget {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self { host: host.into(), port }
+    }
+
+    // The host as `Url::set_host` takes it (IPv6 bracketed) and the port.
+    pub(crate) fn into_host_port(self) -> (String, u16) {
+        (bracket_ipv6(self.host), self.port)
+    }
+}
+
+/// Callback for `ClientBuilder::supervisor_rewriter`: given a supervisor's `host:port`,
+/// returns where to connect instead, or None to skip that supervisor.
+pub type SupervisorRewriter = Arc<dyn Fn(&str) -> Option<SocketTarget> + Send + Sync>;
+
+// The registered rewriter, with a Debug impl for `Client`.
+#[derive(Clone, Default)]
+pub(crate) struct Rewriter(pub(crate) Option<SupervisorRewriter>);
+
+impl fmt::Debug for Rewriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Rewriter(registered)" } else { "Rewriter(none)" })
+    }
+}
+
+// --- Host Overrides ---
+
+// Replacement addresses for supervisors, e.g. port-forwards to cluster-internal hosts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HostOverrides {
+    // Keyed by normalized `host:port`
+    exact: HashMap<String, (String, u16)>,
+    // Applies to supervisors without an exact entry
+    wildcard: Option<(String, u16)>,
+}
+
+impl HostOverrides {
+    // Adds overrides; the key `*` sets the wildcard. Later entries win.
+    pub(crate) fn extend(&mut self, overrides: HashMap<String, (String, u16)>) {
+        for (supervisor, (host, port)) in overrides {
+            let host = bracket_ipv6(host);
+            if supervisor.trim() == "*" {
+                self.wildcard = Some((host, port));
+                continue;
+            }
+            let key = match parse_host_port(&supervisor) {
+                Some((supervisor_host, supervisor_port)) => format!("{}:{}", supervisor_host, supervisor_port),
+                None => supervisor,
+            };
+            self.exact.insert(key, (host, port));
+        }
+    }
+
+    // The address to use instead of supervisor `host:port`, if any.
+    pub(crate) fn get(&self, host: &str, port: u16) -> Option<&(String, u16)> {
+        self.exact.get(&format!("{}:{}", host, port)).or(self.wildcard.as_ref())
+    }
+}
+
+// Brackets an IPv6 address, as `Url::set_host` requires; other hosts are returned as-is.
+fn bracket_ipv6(host: String) -> String {
+    match host.parse::<Ipv6Addr>() {
+        Ok(ip) => format!("[{}]", ip),
+        Err(_) => host,
+    }
+}
+
+// --- Supervisor-Locations Entry Parsing ---
+
+fn normalize_entries(entries: &[String]) -> Vec<String> {
+    entries.iter().filter_map(|entry| normalize_entry(entry)).collect()
+}
+
+// An entry as `host:port` (IPv6 bracketed), or None with a warning if it can't be parsed.
+fn normalize_entry(entry: &str) -> Option<String> {
+    let Some((host, port)) = parse_host_port(entry) else {
+        crate::logging::warn!("Ignoring unparseable Supervisor-Locations entry '{}'", entry);
+        return None;
+    };
+    Some(format!("{}:{}", host, port))
+}
+
+/// Splits a `Supervisor-Locations` entry into a host suitable for `Url::set_host` and a port.
+///
+/// Accepted forms:
+/// - `host:port` and `1.2.3.4:port`
+/// - `[::1]:port` (bracketed IPv6)
+/// - `2001:db8::1:port` (unbracketed IPv6; the last `:`-separated group is taken as the port)
+///
+/// Any of these may have surrounding whitespace, a scheme prefix (`http://`) and trailing
+/// slashes, as some proxies rewrite them; the scheme is ignored. Entries with a path are
+/// rejected.
+///
+/// IPv6 hosts are returned in the bracketed form the `url` crate requires.
+/// Returns None if the entry can't be parsed.
+pub(crate) fn parse_host_port(entry: &str) -> Option<(String, u16)> {
+    let entry = entry.trim();
+    let entry = entry.split_once("://").map_or(entry, |(_, rest)| rest);
+    let entry = entry.trim_end_matches('/');
+
+    // Guard: Path after the authority
+    if entry.contains('/') {
+        return None;
+    }
+
+    // Bracketed IPv6 and plain IPv4 socket addresses
+    if let Ok(addr) = entry.parse::<SocketAddr>() {
+        let host = match addr {
+            SocketAddr::V4(v4) => v4.ip().to_string(),
+            SocketAddr::V6(v6) => format!("[{}]", v6.ip()),
+        };
+        return Some((host, addr.port()));
+    }
+
+    // Guard: Bracketed entries that aren't valid socket addresses
+    if entry.starts_with('[') {
+        return None;
+    }
+
+    let (host, port_str) = entry.rsplit_once(':')?;
+    let port = port_str.parse::<u16>().ok()?;
+
+    // Guard: Empty host
+    if host.is_empty() {
+        return None;
+    }
+
+    // A host that still contains ':' can only be an unbracketed IPv6 address.
+    if host.contains(':') {
+        let ip = host.parse::<Ipv6Addr>().ok()?;
+        return Some((format!("[{}]", ip), port));
+    }
+
+    Some((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_host_port, HostOverrides, SupervisorLocations};
+    use crate::testing::{Reply, Scripted};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use url::Url;
+
+    fn host_port(host: &str, port: u16) -> Option<(String, u16)> {
+        Some((host.to_string(), port))
+    }
+
+    #[test]
+    fn parses_ipv6_ipv4_and_hostname_entries() {
+        assert_eq!(parse_host_port("[::1]:8080"), host_port("[::1]", 8080));
+        assert_eq!(parse_host_port("[2001:db8::1]:1984"), host_port("[2001:db8::1]", 1984));
+        assert_eq!(parse_host_port("2001:db8::1:8080"), host_port("[2001:db8::1]", 8080));
+        assert_eq!(parse_host_port("10.0.0.7:1984"), host_port("10.0.0.7", 1984));
+        assert_eq!(parse_host_port("supervisor-1.rama.internal:1984"), host_port("supervisor-1.rama.internal", 1984));
+    }
+
+    #[test]
+    fn parsed_hosts_round_trip_into_urls() {
+        for (entry, expected) in [
+            ("[::1]:8080", "http://[::1]:8080/rest"),
+            ("2001:db8::1:8080", "http://[2001:db8::1]:8080/rest"),
+            ("10.0.0.7:1984", "http://10.0.0.7:1984/rest"),
+            ("supervisor-1:1984", "http://supervisor-1:1984/rest"),
+        ] {
+            let (host, port) = parse_host_port(entry).unwrap();
+            let mut url = Url::parse("http://conductor:1984/rest").unwrap();
+            url.set_host(Some(&host)).unwrap();
+            url.set_port(Some(port)).unwrap();
+            assert_eq!(url.as_str(), expected, "{}", entry);
+        }
+    }
+
+    #[test]
+    fn rejects_unparseable_entries() {
+        for entry in ["", "supervisor-1", "supervisor-1:port", ":1984", "[::1", "[::1]", "[nope]:1984", "::1", "supervisor-1:1984/rest"] {
+            assert_eq!(parse_host_port(entry), None, "{:?}", entry);
+        }
+    }
+
+    #[test]
+    fn drops_unparseable_entries_and_keeps_the_rest() {
+        let locations = SupervisorLocations::parse(r#"["supervisor-1", "[::1]:1984", "10.0.0.7:1984"]"#).unwrap();
+        assert_eq!(locations, SupervisorLocations::List(vec!["[::1]:1984".to_string(), "10.0.0.7:1984".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn routes_to_a_cached_ipv6_supervisor() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::Redirect("[::1]:1984", vec!["[::1]:1984", "not-a-supervisor"])]);
+        script.script("[::1]:1984", [Reply::ok([30])]);
+        let client = script.client_builder().build().unwrap();
+
+        for _ in 0..2 {
+            let (_, meta) = client.pstate_query("profiles", "$$profiles").key("alice").select_with_meta::<Value>().await.unwrap();
+            assert_eq!(meta.final_url.as_str(), "http://[::1]:1984/rest/profiles/pstate/$$profiles/select");
+        }
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, ["[::1]:1984"]);
+        assert_eq!(script.hosts(), [Scripted::CONDUCTOR, "[::1]:1984", "[::1]:1984"]);
+    }
+
+    // --- Partitioned Locations ---
+
+    const SUPERVISOR_1: &str = "supervisor-1:1984";
+    const SUPERVISOR_2: &str = "supervisor-2:1984";
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_flat_and_per_partition_lists() {
+        let flat = SupervisorLocations::parse(&json!([SUPERVISOR_1, SUPERVISOR_2]).to_string()).unwrap();
+        assert_eq!(flat, SupervisorLocations::List(strings(&[SUPERVISOR_1, SUPERVISOR_2])));
+        assert_eq!((flat.partition(0), flat.partitions()), (None, None));
+
+        let header = json!([[SUPERVISOR_1], [SUPERVISOR_2, SUPERVISOR_1], []]).to_string();
+        let partitioned = SupervisorLocations::parse(&header).unwrap();
+        assert_eq!(partitioned.supervisors(), [SUPERVISOR_1, SUPERVISOR_2]);
+        assert_eq!(partitioned.partition(1), Some(strings(&[SUPERVISOR_2, SUPERVISOR_1])));
+        // Empty and out-of-range partitions are unknown
+        assert_eq!((partitioned.partition(2), partitioned.partition(3)), (None, None));
+        assert_eq!(partitioned.partitions().unwrap().len(), 3);
+    }
+
+    // A 308 to SUPERVISOR_1 with `Supervisor-Locations` as given.
+    fn redirect_with(locations: Value) -> Reply {
+        let location = format!("http://{}/rest/profiles/pstate/$$profiles/select", SUPERVISOR_1);
+        let headers = vec![("location", location), ("supervisor-locations", locations.to_string())];
+        Reply::Status(StatusCode::PERMANENT_REDIRECT, headers, String::new())
+    }
+
+    fn scripted(locations: Value) -> Arc<Scripted> {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [redirect_with(locations)]);
+        script.script(SUPERVISOR_1, [Reply::ok([1])]);
+        script.script(SUPERVISOR_2, [Reply::ok([2])]);
+        script
+    }
+
+    async fn select_partition(client: &crate::Client, partition: Option<u32>) -> u32 {
+        let mut query = client.pstate_query("profiles", "$$profiles").key("alice");
+        if let Some(index) = partition {
+            query = query.partition_index(index);
+        }
+        query.select::<u32>().await.unwrap()[0]
+    }
+
+    #[tokio::test]
+    async fn partition_reads_go_to_the_partitions_supervisor() {
+        let script = scripted(json!([[SUPERVISOR_1], [SUPERVISOR_2]]));
+        let client = script.client_builder().build().unwrap();
+        select_partition(&client, None).await;
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].partitions, Some(vec![strings(&[SUPERVISOR_1]), strings(&[SUPERVISOR_2])]));
+
+        for _ in 0..5 {
+            assert_eq!(select_partition(&client, Some(1)).await, 2);
+            assert_eq!(select_partition(&client, Some(0)).await, 1);
+        }
+        // An unknown partition is routed as usual, through the cache
+        select_partition(&client, Some(7)).await;
+        assert_eq!(script.hosts().iter().filter(|host| *host == Scripted::CONDUCTOR).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_flat_list_routes_partition_reads_as_usual() {
+        let script = scripted(json!([SUPERVISOR_1]));
+        let client = script.client_builder().build().unwrap();
+        select_partition(&client, None).await;
+        assert_eq!(client.export_supervisor_cache().modules["profiles"].partitions, None);
+        assert_eq!(select_partition(&client, Some(1)).await, 1);
+    }
+
+    #[test]
+    fn parses_a_task_group_object() {
+        let header = json!({"1": SUPERVISOR_2, "0": SUPERVISOR_1, "3": "http://supervisor-3:1984/"}).to_string();
+        let tasks = SupervisorLocations::parse(&header).unwrap();
+        assert_eq!(tasks.supervisors(), [SUPERVISOR_1, SUPERVISOR_2, "supervisor-3:1984"]);
+        assert_eq!(tasks.partition(1), Some(strings(&[SUPERVISOR_2])));
+        assert_eq!(tasks.partition(2), None);
+        assert_eq!(tasks.partitions(), Some(vec![strings(&[SUPERVISOR_1]), strings(&[SUPERVISOR_2]), vec![], strings(&["supervisor-3:1984"])]));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        for header in [r#"{"first": "supervisor-1:1984"}"#, r#"{"0": 1984}"#, r#"{"-1": "supervisor-1:1984"}"#, "[1, 2]", r#""supervisor-1:1984""#, "not json"] {
+            assert!(SupervisorLocations::parse(header).is_err(), "{}", header);
+        }
+    }
+
+    #[tokio::test]
+    async fn task_group_locations_are_cached_and_route_partition_reads() {
+        let script = scripted(json!({"0": SUPERVISOR_1, "1": SUPERVISOR_2}));
+        let client = script.client_builder().build().unwrap();
+        select_partition(&client, None).await;
+        for _ in 0..5 {
+            assert_eq!(select_partition(&client, Some(1)).await, 2);
+            assert_eq!(select_partition(&client, Some(0)).await, 1);
+        }
+        // Without a partition, any of the task groups' supervisors
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..40 {
+            seen.insert(select_partition(&client, None).await);
+        }
+        assert_eq!(seen.len(), 2);
+        assert_eq!(script.hosts().iter().filter(|host| *host == Scripted::CONDUCTOR).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_malformed_header_fails_the_request() {
+        let script = scripted(json!({"first": SUPERVISOR_1}));
+        let client = script.client_builder().build().unwrap();
+        let err = client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), crate::ClientError::InvalidSupervisorLocations(_)), "{:?}", err);
+        assert!(client.export_supervisor_cache().modules.is_empty());
+    }
+
+    // Entries as proxies and hand-written configs have been seen to send them.
+    #[test]
+    fn normalizes_messy_entries() {
+        let table: [(&str, Option<&str>); 16] = [
+            ("supervisor-1:1984", Some("supervisor-1:1984")),
+            ("  supervisor-1:1984\t", Some("supervisor-1:1984")),
+            ("http://supervisor-1:8888", Some("supervisor-1:8888")),
+            ("https://supervisor-1:8888/", Some("supervisor-1:8888")),
+            ("supervisor-1:8888/", Some("supervisor-1:8888")),
+            ("supervisor-1:8888//", Some("supervisor-1:8888")),
+            (" http://10.0.0.7:1984/ ", Some("10.0.0.7:1984")),
+            ("http://[::1]:1984/", Some("[::1]:1984")),
+            ("fe80::1:1984", Some("[fe80::1]:1984")),
+            ("SUPERVISOR-1.Example.COM:1984", Some("SUPERVISOR-1.Example.COM:1984")),
+            ("http://supervisor-1:8888/rest", None),
+            ("supervisor-1:8888/rest/", None),
+            ("http://supervisor-1", None),
+            ("supervisor-1:99999", None),
+            ("http://", None),
+            ("   ", None),
+        ];
+        for (entry, expected) in table {
+            assert_eq!(super::normalize_entry(entry).as_deref(), expected, "{:?}", entry);
+        }
+    }
+
+    #[test]
+    fn one_bad_entry_doesnt_cost_the_list() {
+        let header = json!(["http://supervisor-1:1984/", "supervisor-2:1984/rest", " supervisor-2:1984 "]).to_string();
+        assert_eq!(SupervisorLocations::parse(&header).unwrap(), SupervisorLocations::List(strings(&[SUPERVISOR_1, SUPERVISOR_2])));
+        // With every entry bad, the list is empty rather than an error
+        assert_eq!(SupervisorLocations::parse(r#"["http://x/y"]"#).unwrap().supervisors(), Vec::<String>::new());
+    }
+
+    // --- Host Overrides ---
+
+    const FORWARDED: &str = "localhost:9001";
+
+    fn overrides(entries: &[(&str, &str, u16)]) -> HashMap<String, (String, u16)> {
+        entries.iter().map(|(supervisor, host, port)| (supervisor.to_string(), (host.to_string(), *port))).collect()
+    }
+
+    #[test]
+    fn exact_overrides_win_over_the_wildcard() {
+        let mut host_overrides = HostOverrides::default();
+        assert_eq!(host_overrides.get("supervisor-1", 1984), None);
+
+        host_overrides.extend(overrides(&[("http://supervisor-1:1984/", "localhost", 9001), ("2001:db8::1:1984", "::1", 9002)]));
+        assert_eq!(host_overrides.get("supervisor-1", 1984).cloned(), host_port("localhost", 9001));
+        assert_eq!(host_overrides.get("[2001:db8::1]", 1984).cloned(), host_port("[::1]", 9002));
+        assert_eq!(host_overrides.get("supervisor-2", 1984), None);
+
+        host_overrides.extend(overrides(&[(" * ", "localhost", 9000)]));
+        assert_eq!(host_overrides.get("supervisor-2", 1984).cloned(), host_port("localhost", 9000));
+        assert_eq!(host_overrides.get("supervisor-1", 1984).cloned(), host_port("localhost", 9001));
+    }
+
+    #[tokio::test]
+    async fn cached_supervisors_are_overridden_but_not_the_conductor() {
+        for entries in [[(SUPERVISOR_1, "localhost", 9001)], [("*", "localhost", 9001)]] {
+            let script = scripted(json!([SUPERVISOR_1]));
+            script.script(FORWARDED, [Reply::ok([3])]);
+            let client = script.client_builder().host_override(overrides(&entries)).build().unwrap();
+
+            // The redirect fills the cache, so even the first request reaches the override
+            for _ in 0..2 {
+                assert_eq!(select_partition(&client, None).await, 3);
+            }
+            assert_eq!(script.hosts(), [Scripted::CONDUCTOR, FORWARDED, FORWARDED]);
+            // The cache keeps the announced address
+            assert_eq!(client.export_supervisor_cache().modules["profiles"].supervisors, [SUPERVISOR_1]);
+        }
+    }
+}
@@ -0,0 +1,942 @@
+//! An in-memory fake of the Rama REST API, for testing code built on `Client` without a
+//! running cluster.
+//!
+//! `FakeCluster` is an `HttpTransport`, so clients built from it never touch the network
+//! but still go through the real routing code: the first request for a module reaches the
+//! fake conductor, which answers 308 with `Location` and `Supervisor-Locations`, and later
+//! requests go straight to a cached fake supervisor.
+//!
+//! Requests are answered, in order of precedence, by canned responses registered with
+//! `respond`, in-memory depots (`depot`) that record appended values and answer with the
+//! ack returns registered with `ack_return`, and in-memory PStates
+//! (`pstate`) that answer selects over key paths. Anything else gets a 404. Responses can
+//! be delayed per host with `latency`.
+//!
+//! To exercise the default transport too, `serve` puts the cluster on localhost ports and
+//! returns a `FakeServer` whose clients talk to it over HTTP.
+
+use crate::builder::{depot_name, pstate_name};
+use crate::transport::{HttpTransport, TransportFuture, TransportResponse};
+use crate::{rt, Client, ClientBuilder, ClientError};
+use bytes::Bytes;
+use percent_encoding::percent_decode_str;
+use reqwest::header::{HeaderMap, HeaderValue, LOCATION};
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+/// Host of the fake conductor, as used in `FakeCluster::CONDUCTOR_URL`.
+const CONDUCTOR_HOST: &str = "fake-conductor";
+
+/// A fake Rama cluster. Clones share state, so a test can keep one to register responses
+/// and inspect requests while clients built from it are in use.
+#[derive(Debug, Clone)]
+pub struct FakeCluster {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    supervisors: Vec<String>,
+    // Keyed by (module, path_suffix)
+    responses: HashMap<(String, String), (StatusCode, Bytes)>,
+    // Keyed by (module, depot), in append order
+    depots: HashMap<(String, String), Vec<Value>>,
+    // Keyed by (module, depot): topology name -> ack return value
+    acks: HashMap<(String, String), Map<String, Value>>,
+    // Keyed by (module, pstate)
+    pstates: HashMap<(String, String), Value>,
+    // Keyed by host:port, or "*" for every host without its own entry
+    latencies: HashMap<String, Duration>,
+    requests: Vec<FakeRequest>,
+}
+
+/// A request received by a `FakeCluster`.
+#[derive(Debug, Clone)]
+pub struct FakeRequest {
+    pub url: Url,
+    pub module: String,
+    /// The path under `/rest/<module>/`, e.g. `pstate/$$profiles/select`.
+    pub path_suffix: String,
+    pub headers: HeaderMap,
+    /// The request body as JSON (`Null` for GETs and bodies that aren't JSON).
+    pub body: Value,
+}
+
+impl Default for FakeCluster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeCluster {
+    /// Base URL of the fake conductor. Clients from `client`/`client_builder` use it.
+    pub const CONDUCTOR_URL: &'static str = "http://fake-conductor:1984";
+
+    /// A cluster with one supervisor, `fake-supervisor-1:1984`.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                supervisors: vec!["fake-supervisor-1:1984".to_string()],
+                responses: HashMap::new(),
+                depots: HashMap::new(),
+                acks: HashMap::new(),
+                pstates: HashMap::new(),
+                latencies: HashMap::new(),
+                requests: Vec::new(),
+            })),
+        }
+    }
+
+    /// Replaces the supervisors (`host:port`) announced in `Supervisor-Locations`.
+    /// With none, the conductor answers requests itself instead of redirecting.
+    pub fn with_supervisors(self, supervisors: &[&str]) -> Self {
+        self.lock().supervisors = supervisors.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// A `ClientBuilder` for `CONDUCTOR_URL` that sends requests to this cluster.
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new(Self::CONDUCTOR_URL).transport(Arc::new(self.clone()))
+    }
+
+    /// A default-configured client for this cluster.
+    pub fn client(&self) -> Client {
+        self.client_builder().build().expect("the fake conductor URL is valid")
+    }
+
+    // --- Registration ---
+
+    /// Answers requests to `path_suffix` of `module` (e.g. `query/myQuery/invoke`) with
+    /// 200 and `body` as JSON. Replaces any earlier response for the same endpoint.
+    pub fn respond(&self, module: &str, path_suffix: &str, body: impl Serialize) -> &Self {
+        let body = serde_json::to_vec(&body).expect("canned response serializes to JSON");
+        self.respond_with(module, path_suffix, StatusCode::OK, body)
+    }
+
+    /// Answers requests to `path_suffix` of `module` with `status` and a raw body,
+    /// e.g. to simulate server errors.
+    pub fn respond_with(&self, module: &str, path_suffix: &str, status: StatusCode, body: impl Into<Bytes>) -> &Self {
+        let key = (module.to_string(), path_suffix.trim_start_matches('/').to_string());
+        self.lock().responses.insert(key, (status, body.into()));
+        self
+    }
+
+    /// Creates an empty in-memory depot. Appends to it succeed and are recorded.
+    /// Depot and PState names get the same prefix canonicalization as in `Client`.
+    pub fn depot(&self, module: &str, depot: &str) -> &Self {
+        self.lock().depots.entry((module.to_string(), depot_name(depot))).or_default();
+        self
+    }
+
+    /// Makes `topology` return `value` as its ack for every append to `depot` made with
+    /// `AckLevel::Ack` (the default). Appends with other ack levels get `{}`, as from Rama.
+    pub fn ack_return(&self, module: &str, depot: &str, topology: &str, value: impl Into<Value>) -> &Self {
+        let key = (module.to_string(), depot_name(depot));
+        self.lock().acks.entry(key).or_default().insert(topology.to_string(), value.into());
+        self
+    }
+
+    /// The values appended to a depot so far, in order (after client-side transforms).
+    pub fn appended(&self, module: &str, depot: &str) -> Vec<Value> {
+        self.lock().depots.get(&(module.to_string(), depot_name(depot))).cloned().unwrap_or_default()
+    }
+
+    /// Sets the contents of an in-memory PState, usually a JSON object used as a map.
+    ///
+    /// Selects support key navigators (strings and numbers) and the `all`, `mapVals`,
+    /// `must`, `multiPath` and `stop` explicit navigators; a path using anything else gets
+    /// a 400.
+    pub fn pstate(&self, module: &str, pstate: &str, value: Value) -> &Self {
+        self.lock().pstates.insert((module.to_string(), pstate_name(pstate)), value);
+        self
+    }
+
+    /// Delays every response from `host_port` (e.g. `fake-supervisor-1:1984`, or the
+    /// conductor's `fake-conductor:1984`) by `latency`, to exercise hedging, timeouts and
+    /// concurrency limits. The key `"*"` applies to every host without its own entry.
+    pub fn latency(&self, host_port: &str, latency: Duration) -> &Self {
+        self.lock().latencies.insert(host_port.to_string(), latency);
+        self
+    }
+
+    /// Every request received so far, including ones answered with a redirect.
+    pub fn requests(&self) -> Vec<FakeRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    // --- Request Handling ---
+
+    // Answers one request. GETs (`body` None) only probe: a known endpoint answers 405.
+    fn handle(&self, url: Url, headers: HeaderMap, body: Option<Bytes>) -> Result<TransportResponse, ClientError> {
+        let mut state = self.lock();
+
+        // Guard: Not a host of this cluster (what a connection failure would be)
+        let host_port = host_port(&url);
+        let is_conductor = url.host_str() == Some(CONDUCTOR_HOST);
+        if !is_conductor && !state.supervisors.contains(&host_port) {
+            return Err(ClientError::Transport(format!("FakeCluster has no host '{}'", host_port).into()));
+        }
+
+        // Guard: Not a REST API path
+        let Some((module, path_suffix)) = url.path().strip_prefix("/rest/").and_then(|rest| rest.split_once('/')) else {
+            return Ok(empty(StatusCode::NOT_FOUND));
+        };
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let module = decode(module);
+        let path_suffix = path_suffix.split('/').map(decode).collect::<Vec<_>>().join("/");
+        state.requests.push(FakeRequest {
+            url: url.clone(),
+            module: module.clone(),
+            path_suffix: path_suffix.clone(),
+            headers,
+            body: body.as_deref().and_then(|b| serde_json::from_slice(b).ok()).unwrap_or(Value::Null),
+        });
+
+        // --- Module root probes: known modules redirect like a real conductor ---
+        if path_suffix.is_empty() {
+            let known = state.responses.keys().chain(state.depots.keys()).chain(state.pstates.keys()).any(|(m, _)| *m == module);
+            return Ok(match (known, is_conductor) {
+                (false, _) => empty(StatusCode::NOT_FOUND),
+                (true, true) => redirect(&state.supervisors, &url).unwrap_or_else(|| empty(StatusCode::METHOD_NOT_ALLOWED)),
+                (true, false) => empty(StatusCode::METHOD_NOT_ALLOWED),
+            });
+        }
+
+        // --- Conductor: redirect to a supervisor (other GET probes aren't routed) ---
+        if is_conductor && body.is_some() {
+            if let Some(response) = redirect(&state.supervisors, &url) {
+                return Ok(response);
+            }
+        }
+
+        // --- Canned responses ---
+        if let Some((status, response)) = state.responses.get(&(module.clone(), path_suffix.clone())) {
+            return Ok(match body {
+                Some(_) => TransportResponse::from_bytes(*status, HeaderMap::new(), response.clone()),
+                None => empty(StatusCode::METHOD_NOT_ALLOWED),
+            });
+        }
+
+        let is_get = body.is_none();
+        let request_body = state.requests.last().map(|r| r.body.clone()).unwrap_or_default();
+        let parts: Vec<&str> = path_suffix.split('/').collect();
+        match parts[..] {
+            ["depot", depot, "append"] => {
+                // Guard: Unknown depot
+                let key = (module, depot.to_string());
+                let Some(appended) = state.depots.get_mut(&key) else {
+                    return Ok(empty(StatusCode::NOT_FOUND));
+                };
+                if is_get {
+                    return Ok(empty(StatusCode::METHOD_NOT_ALLOWED));
+                }
+                appended.push(request_body.get("data").cloned().unwrap_or_default());
+                let acks = match request_body.get("ackLevel").and_then(Value::as_str) {
+                    None | Some("ack") => state.acks.get(&key).cloned().unwrap_or_default(),
+                    Some(_) => Map::new(),
+                };
+                Ok(json_response(&Value::Object(acks)))
+            }
+            ["pstate", pstate, operation @ ("select" | "selectOne")] => {
+                // Guard: Unknown PState
+                let Some(root) = state.pstates.get(&(module, pstate.to_string())) else {
+                    return Ok(empty(StatusCode::NOT_FOUND));
+                };
+                if is_get {
+                    return Ok(empty(StatusCode::METHOD_NOT_ALLOWED));
+                }
+                let path = request_body.as_array().cloned().unwrap_or_default();
+                let Some(mut results) = select(root, &path) else {
+                    return Ok(empty(StatusCode::BAD_REQUEST));
+                };
+                if operation == "select" {
+                    return Ok(json_response(&Value::Array(results)));
+                }
+                // Guard: selectOne needs exactly one result
+                if results.len() != 1 {
+                    return Ok(empty(StatusCode::BAD_REQUEST));
+                }
+                Ok(json_response(&results.remove(0)))
+            }
+            _ => Ok(empty(StatusCode::NOT_FOUND)),
+        }
+    }
+}
+
+impl FakeCluster {
+    // The configured latency of the host `url` points at.
+    fn latency_of(&self, url: &Url) -> Option<Duration> {
+        let state = self.lock();
+        let host_port = host_port(url);
+        state.latencies.get(&host_port).or_else(|| state.latencies.get("*")).copied()
+    }
+
+    // Answers after the host's latency, if any. The request is handled (and recorded) when
+    // it is sent, like a server that's slow to respond rather than slow to accept.
+    fn delayed(&self, url: Url, headers: HeaderMap, body: Option<Bytes>) -> TransportFuture<'_> {
+        let latency = self.latency_of(&url);
+        let result = self.handle(url, headers, body);
+        Box::pin(async move {
+            if let Some(latency) = latency {
+                rt::sleep(latency).await;
+            }
+            result
+        })
+    }
+}
+
+impl HttpTransport for FakeCluster {
+    fn post(&self, url: Url, headers: HeaderMap, body: Bytes) -> TransportFuture<'_> {
+        self.delayed(url, headers, Some(body))
+    }
+
+    fn get(&self, url: Url, headers: HeaderMap) -> TransportFuture<'_> {
+        self.delayed(url, headers, None)
+    }
+}
+
+// --- Serving over HTTP ---
+
+/// A `FakeCluster` served over HTTP on localhost, from `FakeCluster::serve`. Clients from
+/// `client`/`client_builder` use the default transport, so requests go through reqwest and
+/// a real network stack. Stops listening when dropped.
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+#[derive(Debug)]
+pub struct FakeServer {
+    url: Url,
+    listeners: Vec<tokio::task::JoinHandle<()>>,
+}
+
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+impl FakeServer {
+    /// URL of the served conductor, e.g. `http://127.0.0.1:41234/`.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// A `ClientBuilder` for `url`, with the default transport.
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::new(self.url.as_str())
+    }
+
+    /// A default-configured client for the served cluster.
+    pub fn client(&self) -> Client {
+        self.client_builder().build().expect("the served conductor URL is valid")
+    }
+}
+
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        for listener in &self.listeners {
+            listener.abort();
+        }
+    }
+}
+
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+impl FakeCluster {
+    /// Serves the cluster over HTTP on localhost, for tests that need the real transport
+    /// (TLS aside): the conductor and each supervisor get a port of their own, and
+    /// redirects announce the supervisors' real addresses. Requests are still recorded,
+    /// and `latency` still applies, under the fake host names. Supervisors set after this
+    /// aren't served. Must be called inside a Tokio runtime.
+    pub async fn serve(&self) -> std::io::Result<FakeServer> {
+        let hosts: Vec<String> = std::iter::once(format!("{}:1984", CONDUCTOR_HOST)).chain(self.lock().supervisors.clone()).collect();
+        let mut listeners = Vec::new();
+        for _ in &hosts {
+            listeners.push(tokio::net::TcpListener::bind("127.0.0.1:0").await?);
+        }
+        let addresses = listeners.iter().map(|listener| Ok(listener.local_addr()?.to_string())).collect::<std::io::Result<Vec<_>>>()?;
+        // (fake host:port, address it is served on), the conductor first
+        let served: Arc<Vec<(String, String)>> = Arc::new(hosts.into_iter().zip(addresses).collect());
+        let url = Url::parse(&format!("http://{}", served[0].1)).expect("socket addresses are valid hosts");
+
+        let listeners = listeners
+            .into_iter()
+            .enumerate()
+            .map(|(index, listener)| {
+                let (cluster, served) = (self.clone(), served.clone());
+                tokio::spawn(async move {
+                    while let Ok((stream, _)) = listener.accept().await {
+                        let (cluster, served) = (cluster.clone(), served.clone());
+                        tokio::spawn(async move {
+                            let _ = cluster.serve_connection(stream, &served[index].0, &served).await;
+                        });
+                    }
+                })
+            })
+            .collect();
+        Ok(FakeServer { url, listeners })
+    }
+
+    // Answers one request on a served connection as `host_port`, translating between the
+    // fake host names and the addresses they are served on.
+    async fn serve_connection(&self, mut stream: tokio::net::TcpStream, host_port: &str, served: &[(String, String)]) -> std::io::Result<()> {
+        // Guard: Closed before sending a request
+        let Some(request) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+        let mut request_line = request.head.lines().next().unwrap_or_default().split(' ');
+        let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or("/"));
+        let url = Url::parse(&format!("http://{}{}", host_port, target)).map_err(std::io::Error::other)?;
+        let headers: HeaderMap = request
+            .head
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                Some((reqwest::header::HeaderName::from_bytes(name.trim().as_bytes()).ok()?, HeaderValue::from_str(value.trim()).ok()?))
+            })
+            .collect();
+        let body = (method != "GET").then(|| Bytes::from(request.body));
+
+        let (status, headers, body) = match self.delayed(url, headers, body).await {
+            Ok(response) => {
+                let status = response.status.as_u16();
+                let headers: Vec<(String, String)> = response
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), served_addresses(value.to_str().unwrap_or_default(), served)))
+                    .collect();
+                (status, headers, response.bytes().await.map_err(std::io::Error::other)?.to_vec())
+            }
+            // A host no longer in the cluster
+            Err(e) => (502, Vec::new(), e.to_string().into_bytes()),
+        };
+        write_response(&mut stream, status, headers, &body).await
+    }
+}
+
+// `value` with every fake host:port replaced by the address it is served on.
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+fn served_addresses(value: &str, served: &[(String, String)]) -> String {
+    served.iter().fold(value.to_string(), |value, (host_port, address)| value.replace(host_port.as_str(), address))
+}
+
+// A 308 from the conductor to the first supervisor, or None if there are no supervisors.
+fn redirect(supervisors: &[String], url: &Url) -> Option<TransportResponse> {
+    let supervisor = supervisors.first()?;
+    let mut location = url.clone();
+    let (host, port) = supervisor.rsplit_once(':').expect("supervisors are host:port");
+    let _ = location.set_host(Some(host));
+    let _ = location.set_port(port.parse().ok());
+    let locations = serde_json::to_string(supervisors).expect("strings serialize");
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, HeaderValue::from_str(location.as_str()).expect("URLs are valid header values"));
+    headers.insert("Supervisor-Locations", HeaderValue::from_str(&locations).expect("JSON is a valid header value"));
+    Some(TransportResponse::from_bytes(StatusCode::PERMANENT_REDIRECT, headers, Bytes::new()))
+}
+
+// Navigates `root` along a path of key and simple explicit navigators.
+// Returns None for navigators the fake doesn't implement.
+fn select(root: &Value, path: &[Value]) -> Option<Vec<Value>> {
+    let mut current = vec![root.clone()];
+    for nav in path {
+        let mut next = Vec::new();
+        for value in current {
+            match nav {
+                Value::String(_) | Value::Number(_) => next.push(child(&value, nav).cloned().unwrap_or(Value::Null)),
+                Value::Array(explicit) => match explicit.first().and_then(Value::as_str)? {
+                    "all" => match value {
+                        Value::Array(items) => next.extend(items),
+                        Value::Object(entries) => next.extend(entries.into_iter().map(|(k, v)| json!([k, v]))),
+                        _ => {}
+                    },
+                    "mapVals" => {
+                        if let Value::Object(entries) = value {
+                            next.extend(entries.into_iter().map(|(_, v)| v));
+                        }
+                    }
+                    "must" => next.extend(explicit[1..].iter().filter_map(|key| child(&value, key)).cloned()),
+                    "multiPath" => {
+                        for sub_path in &explicit[1..] {
+                            next.extend(select(&value, sub_path.as_array()?)?);
+                        }
+                    }
+                    "stop" => {}
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+        current = next;
+    }
+    Some(current)
+}
+
+// The entry for `key` in a JSON object (numbers match their decimal form) or array.
+fn child<'v>(value: &'v Value, key: &Value) -> Option<&'v Value> {
+    match (value, key) {
+        (Value::Object(map), Value::String(k)) => map.get(k),
+        (Value::Object(map), Value::Number(n)) => map.get(&n.to_string()),
+        (Value::Array(items), Value::Number(n)) => items.get(usize::try_from(n.as_u64()?).ok()?),
+        _ => None,
+    }
+}
+
+fn json_response(value: &Value) -> TransportResponse {
+    let body = serde_json::to_vec(value).expect("JSON values serialize");
+    TransportResponse::from_bytes(StatusCode::OK, HeaderMap::new(), body)
+}
+
+fn empty(status: StatusCode) -> TransportResponse {
+    TransportResponse::from_bytes(status, HeaderMap::new(), Bytes::new())
+}
+
+fn host_port(url: &Url) -> String {
+    format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default())
+}
+
+// --- Scripted Transport ---
+
+// A transport for the crate's own tests, for what `FakeCluster` can't fake: each host
+// answers with the replies scripted for it, in order, repeating the last one, so any host
+// can redirect anywhere (loops, chains), send `Retry-After`, or lose a module. Requests to
+// hosts without a script fail as unreachable. Records the exact bytes sent.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct Scripted {
+    replies: Mutex<HashMap<String, Vec<Reply>>>,
+    sent: Mutex<Vec<(Url, Bytes)>>,
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) enum Reply {
+    // `status`, with headers and a body
+    Status(StatusCode, Vec<(&'static str, String)>, String),
+    // A 308 to the requested path on a host:port, announcing the given supervisors
+    Redirect(&'static str, Vec<&'static str>),
+}
+
+#[cfg(test)]
+impl Reply {
+    pub(crate) fn ok(body: impl Serialize) -> Self {
+        Self::Status(StatusCode::OK, Vec::new(), serde_json::to_string(&body).expect("JSON values serialize"))
+    }
+
+    pub(crate) fn redirect(host_port: &'static str) -> Self {
+        Self::Redirect(host_port, vec![host_port])
+    }
+}
+
+#[cfg(test)]
+impl Scripted {
+    // The conductor's host:port; clients from `client_builder` start there.
+    pub(crate) const CONDUCTOR: &'static str = "conductor:1984";
+
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    // Sets what `host_port` answers, replacing any earlier script.
+    pub(crate) fn script(&self, host_port: &str, replies: impl IntoIterator<Item = Reply>) -> &Self {
+        self.replies.lock().unwrap().insert(host_port.to_string(), replies.into_iter().collect());
+        self
+    }
+
+    pub(crate) fn client_builder(self: &Arc<Self>) -> ClientBuilder {
+        ClientBuilder::new(format!("http://{}", Self::CONDUCTOR)).transport(self.clone())
+    }
+
+    // The host:port of every request sent so far, in order.
+    pub(crate) fn hosts(&self) -> Vec<String> {
+        self.sent.lock().unwrap().iter().map(|(url, _)| host_port(url)).collect()
+    }
+
+    // The body of every request sent so far, in order.
+    pub(crate) fn bodies(&self) -> Vec<Bytes> {
+        self.sent.lock().unwrap().iter().map(|(_, body)| body.clone()).collect()
+    }
+
+    fn answer(&self, url: Url, body: Bytes) -> Result<TransportResponse, ClientError> {
+        let host = host_port(&url);
+        self.sent.lock().unwrap().push((url.clone(), body));
+        let reply = {
+            let mut replies = self.replies.lock().unwrap();
+            // Guard: Unscripted host (what a connection failure would be)
+            let Some(script) = replies.get_mut(&host).filter(|script| !script.is_empty()) else {
+                return Err(ClientError::Transport(format!("Scripted has no host '{}'", host).into()));
+            };
+            match script.len() {
+                1 => script[0].clone(),
+                _ => script.remove(0),
+            }
+        };
+        Ok(match reply {
+            Reply::Status(status, headers, body) => {
+                let headers = headers.into_iter().map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap())).collect();
+                TransportResponse::from_bytes(status, headers, body)
+            }
+            Reply::Redirect(to, supervisors) => {
+                let supervisors: Vec<String> = supervisors.iter().map(|s| s.to_string()).collect();
+                redirect(&supervisors, &url).map(|mut response| {
+                    let location = url.as_str().replacen(&host, to, 1);
+                    response.headers.insert(LOCATION, HeaderValue::from_str(&location).unwrap());
+                    response
+                }).expect("a redirect announces at least one supervisor")
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+impl HttpTransport for Scripted {
+    fn post(&self, url: Url, _headers: HeaderMap, body: Bytes) -> TransportFuture<'_> {
+        let result = self.answer(url, body);
+        Box::pin(async move { result })
+    }
+
+    fn get(&self, url: Url, _headers: HeaderMap) -> TransportFuture<'_> {
+        let result = self.answer(url, Bytes::new());
+        Box::pin(async move { result })
+    }
+}
+
+// A transport for the crate's own tests that answers 401 to requests without
+// `Authorization: Bearer <token>` and passes the rest on to a `FakeCluster`.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct RequireToken {
+    cluster: FakeCluster,
+    authorization: String,
+}
+
+#[cfg(test)]
+impl RequireToken {
+    pub(crate) fn new(cluster: &FakeCluster, token: &str) -> Arc<Self> {
+        Arc::new(Self { cluster: cluster.clone(), authorization: format!("Bearer {}", token) })
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        headers.get(reqwest::header::AUTHORIZATION).is_some_and(|value| *value == self.authorization)
+    }
+
+    fn unauthorized() -> TransportFuture<'static> {
+        Box::pin(async { Ok(empty(StatusCode::UNAUTHORIZED)) })
+    }
+}
+
+#[cfg(test)]
+impl HttpTransport for RequireToken {
+    fn post(&self, url: Url, headers: HeaderMap, body: Bytes) -> TransportFuture<'_> {
+        match self.authorized(&headers) {
+            true => self.cluster.post(url, headers, body),
+            false => Self::unauthorized(),
+        }
+    }
+
+    fn get(&self, url: Url, headers: HeaderMap) -> TransportFuture<'_> {
+        match self.authorized(&headers) {
+            true => self.cluster.get(url, headers),
+            false => Self::unauthorized(),
+        }
+    }
+}
+
+// A minimal HTTP/1.1 server on localhost, for the crate's own tests of what happens inside
+// the default (reqwest) transport, such as content encodings and Unix sockets. Each
+// request gets the response `respond` builds for it, on a connection that is then closed.
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "tokio"))]
+pub(crate) struct MockServer {
+    pub(crate) url: String,
+    received: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+#[derive(Debug, Clone)]
+pub(crate) struct MockRequest {
+    // Request line and headers, as received
+    pub(crate) head: String,
+    pub(crate) body: Vec<u8>,
+}
+
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+impl MockRequest {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "tokio"))]
+impl MockServer {
+    // `respond` returns the status, extra headers and body of each response.
+    pub(crate) async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&MockRequest) -> (u16, Vec<(&'static str, String)>, Vec<u8>) + Send + Sync + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("a free local port");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let (respond, log) = (Arc::new(respond), received.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (respond, log) = (respond.clone(), log.clone());
+                tokio::spawn(async move {
+                    let _ = Self::serve(stream, &*respond, &log).await;
+                });
+            }
+        });
+        Self { url, received }
+    }
+
+    // Like `start`, but listening on a Unix socket at `path`. `url` is then just the path.
+    #[cfg(all(unix, feature = "uds"))]
+    pub(crate) async fn start_unix<F>(path: &std::path::Path, respond: F) -> Self
+    where
+        F: Fn(&MockRequest) -> (u16, Vec<(&'static str, String)>, Vec<u8>) + Send + Sync + 'static,
+    {
+        let listener = tokio::net::UnixListener::bind(path).expect("a free socket path");
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let (respond, log) = (Arc::new(respond), received.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (respond, log) = (respond.clone(), log.clone());
+                tokio::spawn(async move {
+                    let _ = Self::serve(stream, &*respond, &log).await;
+                });
+            }
+        });
+        Self { url: path.display().to_string(), received }
+    }
+
+    pub(crate) fn requests(&self) -> Vec<MockRequest> {
+        self.received.lock().unwrap().clone()
+    }
+
+    async fn serve<S, F>(mut stream: S, respond: &F, log: &Mutex<Vec<MockRequest>>) -> std::io::Result<()>
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+        F: Fn(&MockRequest) -> (u16, Vec<(&'static str, String)>, Vec<u8>),
+    {
+        // Guard: Closed before sending a request
+        let Some(request) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+        let (status, headers, body) = respond(&request);
+        log.lock().unwrap().push(request);
+        write_response(&mut stream, status, headers, &body).await
+    }
+}
+
+// Reads one HTTP/1.1 request (head and `Content-Length` body), or None if the peer
+// closes the connection first.
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+async fn read_request<S: tokio::io::AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<Option<MockRequest>> {
+    use tokio::io::AsyncReadExt;
+    let mut buffer = Vec::new();
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let mut chunk = [0; 4096];
+        match stream.read(&mut chunk).await? {
+            0 => return Ok(None),
+            read => buffer.extend_from_slice(&chunk[..read]),
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut request = MockRequest { head, body: buffer[head_end + 4..].to_vec() };
+    let length: usize = request.header("content-length").and_then(|length| length.parse().ok()).unwrap_or(0);
+    while request.body.len() < length {
+        let mut chunk = vec![0; length - request.body.len()];
+        match stream.read(&mut chunk).await? {
+            0 => break,
+            read => request.body.extend_from_slice(&chunk[..read]),
+        }
+    }
+    Ok(Some(request))
+}
+
+// Writes a response and closes the connection.
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32"), feature = "tokio"))]
+async fn write_response<S, N, V>(stream: &mut S, status: u16, headers: Vec<(N, V)>, body: &[u8]) -> std::io::Result<()>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+    N: std::fmt::Display,
+    V: std::fmt::Display,
+{
+    use tokio::io::AsyncWriteExt;
+    let mut response = format!("HTTP/1.1 {} Mock\r\ncontent-length: {}\r\nconnection: close\r\n", status, body.len());
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FakeCluster;
+    use crate::builder::AckLevel;
+    use crate::{AckReturn, ClientError};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn hosts(cluster: &FakeCluster) -> Vec<String> {
+        cluster.requests().iter().map(|r| r.url.host_str().unwrap().to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn redirects_from_the_conductor_then_serves_from_the_supervisor() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let client = cluster.client();
+
+        for _ in 0..2 {
+            let ages: Vec<u32> = client.pstate_query("profiles", "profiles").key("alice").select().await.unwrap();
+            assert_eq!(ages, [30]);
+        }
+        assert_eq!(hosts(&cluster), ["fake-conductor", "fake-supervisor-1", "fake-supervisor-1"]);
+        let request = &cluster.requests()[1];
+        assert_eq!((request.module.as_str(), request.path_suffix.as_str()), ("profiles", "pstate/$$profiles/select"));
+        assert_eq!(request.body, json!(["alice"]));
+    }
+
+    #[tokio::test]
+    async fn without_supervisors_the_conductor_answers() {
+        let cluster = FakeCluster::new().with_supervisors(&[]);
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        let age: u32 = cluster.client().pstate_query("profiles", "$$profiles").key("alice").select_one().await.unwrap();
+        assert_eq!(age, 30);
+        assert_eq!(hosts(&cluster), ["fake-conductor"]);
+    }
+
+    #[tokio::test]
+    async fn canned_responses_answer_their_endpoint() {
+        let cluster = FakeCluster::new();
+        cluster.respond("profiles", "query/topScores/invoke", json!([1, 2, 3]));
+        cluster.respond_with("profiles", "/query/broken/invoke", StatusCode::INTERNAL_SERVER_ERROR, "boom");
+        let client = cluster.client_builder().retry_policy(crate::RetryPolicy::none()).build().unwrap();
+
+        let scores: Vec<u32> = client.query_invoke("profiles", "topScores").arg(10).invoke().await.unwrap();
+        assert_eq!(scores, [1, 2, 3]);
+        assert_eq!(cluster.requests().last().unwrap().body, json!([10]));
+        let err = client.query_invoke("profiles", "broken").invoke::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(StatusCode::INTERNAL_SERVER_ERROR, _)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn depots_record_appends_and_ack_by_level() {
+        let cluster = FakeCluster::new();
+        cluster.depot("profiles", "edits").ack_return("profiles", "*edits", "counter", "#__L7");
+        let client = cluster.client();
+
+        let ack: AckReturn = client.depot_append("profiles", "*edits", json!({"n": 1})).append().await.unwrap();
+        assert_eq!(ack.get_as::<i64>("counter").unwrap(), Some(7));
+        for level in [AckLevel::AppendAck, AckLevel::None] {
+            let ack: AckReturn = client.depot_append("profiles", "*edits", json!({"n": 2})).ack_level(level).append().await.unwrap();
+            assert!(ack.raw().is_empty());
+        }
+        assert_eq!(cluster.appended("profiles", "edits"), [json!({"n": 1}), json!({"n": 2}), json!({"n": 2})]);
+
+        let err = client.depot_append("profiles", "*missing", json!(1)).append::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::ObjectNotFound { object, .. } if object == "*missing"), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn pstates_answer_key_path_selects() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("m", "$$p", json!({
+            "alice": {"age": 30, "tags": ["a", "b"]},
+            "bob": {"age": 25, "tags": []},
+            "7": "seven",
+        }));
+        let client = cluster.client();
+        let select = |path: Vec<Value>| {
+            let mut query = client.pstate_query("m", "$$p");
+            for nav in path {
+                query = query.nav(nav);
+            }
+            query.select::<Value>()
+        };
+
+        assert_eq!(select(vec![json!("alice"), json!("tags"), json!(["all"])]).await.unwrap(), [json!("a"), json!("b")]);
+        assert_eq!(select(vec![json!("bob"), json!(["mapVals"])]).await.unwrap(), [json!(25), json!([])]);
+        assert_eq!(select(vec![json!(7)]).await.unwrap(), [json!("seven")]);
+        assert_eq!(select(vec![json!("carol")]).await.unwrap(), [Value::Null]);
+        assert_eq!(select(vec![json!(["must", "carol", "bob"]), json!("age")]).await.unwrap(), [json!(25)]);
+        assert_eq!(
+            select(vec![json!("alice"), json!(["multiPath", ["age"], ["tags", 1]])]).await.unwrap(),
+            [json!(30), json!("b")]
+        );
+
+        let err = select(vec![json!(["sortedMapRange", "a", "b"])]).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(StatusCode::BAD_REQUEST, _)), "{:?}", err);
+        let err = client.pstate_query("m", "$$p").all().select_one::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(StatusCode::BAD_REQUEST, _)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn latency_delays_responses() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("m", "$$p", json!({"k": 1}));
+        cluster.latency("fake-supervisor-1:1984", Duration::from_millis(100));
+        let client = cluster.client();
+
+        let started = crate::rt::Instant::now();
+        client.pstate_query("m", "$$p").key("k").select::<u32>().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100), "{:?}", started.elapsed());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn serves_over_http_through_the_default_transport() {
+        let cluster = FakeCluster::new();
+        cluster.pstate("profiles", "$$profiles", json!({"alice": 30}));
+        cluster.depot("profiles", "*edits").ack_return("profiles", "*edits", "counter", "#__L7");
+        let server = cluster.serve().await.unwrap();
+        assert_eq!(server.url().host_str(), Some("127.0.0.1"));
+        let client = server.client();
+
+        for _ in 0..2 {
+            let ages: Vec<u32> = client.pstate_query("profiles", "$$profiles").key("alice").select().await.unwrap();
+            assert_eq!(ages, [30]);
+        }
+        let ack: AckReturn = client.depot_append("profiles", "*edits", json!({"n": 1})).append().await.unwrap();
+        assert_eq!(ack.get_as::<i64>("counter").unwrap(), Some(7));
+        assert_eq!(cluster.appended("profiles", "*edits"), [json!({"n": 1})]);
+
+        // Recorded under the fake names, but the client followed the redirect to a real port
+        assert_eq!(hosts(&cluster), ["fake-conductor", "fake-supervisor-1", "fake-supervisor-1", "fake-supervisor-1"]);
+        let supervisor = &client.export_supervisor_cache().modules["profiles"].supervisors[0];
+        assert!(supervisor.starts_with("127.0.0.1:") && !server.url().as_str().contains(supervisor.as_str()), "{}", supervisor);
+        let request = &cluster.requests()[1];
+        assert_eq!(request.headers["host"], supervisor.as_str());
+        assert!(request.headers.contains_key("content-length"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn served_errors_and_latency_reach_the_client() {
+        let cluster = FakeCluster::new().with_supervisors(&[]);
+        cluster.pstate("m", "$$p", json!({"k": 1}));
+        cluster.respond_with("m", "query/broken/invoke", StatusCode::INTERNAL_SERVER_ERROR, "boom");
+        cluster.latency("*", Duration::from_millis(50));
+        let server = cluster.serve().await.unwrap();
+        let client = server.client_builder().retry_policy(crate::RetryPolicy::none()).build().unwrap();
+
+        let started = crate::rt::Instant::now();
+        assert_eq!(client.pstate_query("m", "$$p").key("k").select::<u32>().await.unwrap(), [1]);
+        assert!(started.elapsed() >= Duration::from_millis(50), "{:?}", started.elapsed());
+        let err = client.query_invoke("m", "broken").invoke::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(StatusCode::INTERNAL_SERVER_ERROR, _)), "{:?}", err);
+        assert_eq!(hosts(&cluster), ["fake-conductor", "fake-conductor"]);
+    }
+}
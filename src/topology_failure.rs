@@ -0,0 +1,231 @@
+use crate::logging::error;
+use crate::transport::TransportResponse;
+use crate::{Client, ClientError, Route};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Keys naming the failed topology in an error body.
+const TOPOLOGY_KEYS: [&str; 2] = ["topology", "topologyName"];
+// Keys holding the error description, most specific first.
+const MESSAGE_KEYS: [&str; 4] = ["message", "error", "exception", "cause"];
+// Keys an error body may nest the actual failure under.
+const WRAPPER_KEYS: [&str; 4] = ["error", "exception", "cause", "failure"];
+// Keys of a map of topology name -> failure, for bodies reporting several.
+const FAILURES_KEYS: [&str; 2] = ["failures", "topologyFailures"];
+
+impl Client {
+    // Sends a depot append. A 200 ack reporting a topology exception fails with
+    // `TopologyFailure` instead of being returned as the result.
+    pub(crate) async fn send_append<T: Serialize, R: DeserializeOwned>(
+        &self,
+        module: &str,
+        path_suffix: &str,
+        body: &T,
+    ) -> Result<R, ClientError> {
+        let body_bytes = Bytes::from(serde_json::to_vec(body)?);
+        self.send_bytes_with(module, path_suffix, &body_bytes, Route::default(), decode_ack).await
+    }
+}
+
+// `decode_json` for append acks, checking each topology's ack value for an exception first.
+async fn decode_ack<R: DeserializeOwned>(response: TransportResponse) -> Result<R, ClientError> {
+    let ack: Value = serde_json::from_slice(&response.bytes().await?)?;
+    if let Some(err) = from_ack(&ack) {
+        error!("[{}] Append acked with a topology failure: {}", err.code(), err);
+        return Err(err);
+    }
+    serde_json::from_value(ack).map_err(|e| {
+        let err = ClientError::Json(e);
+        error!("[{}] Failed to deserialize OK response: {}", err.code(), err);
+        err
+    })
+}
+
+// A topology failure reported inside a 200 ack: a topology whose ack value is an object
+// with an `exception` field. Other ack values are never taken for failures, since a
+// topology may legitimately return a map with e.g. an `error` field.
+pub(crate) fn from_ack(ack: &Value) -> Option<ClientError> {
+    let Value::Object(topologies) = ack else {
+        return None;
+    };
+    topologies.iter().find_map(|(topology, value)| {
+        let exception = value.as_object()?.get("exception")?;
+        Some(ClientError::TopologyFailure {
+            topology: topology.clone(),
+            message: message_of(exception),
+            details: ack.clone(),
+        })
+    })
+}
+
+// A topology failure reported in the body of an error response to an append, or None if
+// the body doesn't name a topology (it's then reported as a status error).
+//
+// Recognized shapes, in order:
+// - `{"topology": "t", "message": "...", ...}` (also `topologyName`; `error`, `exception`
+//   or `cause` for the message)
+// - the same nested under `error`, `exception`, `cause` or `failure`
+// - `{"failures": {"t": <failure>, ...}}` (also `topologyFailures`); the first is reported
+// - as a last resort, text (the whole body, or a JSON message) naming the topology, e.g.
+//   `Exception in topology 'counter': ...`
+pub(crate) fn from_error_body(body: &str) -> Option<ClientError> {
+    let (topology, message, details) = match serde_json::from_str::<Value>(body) {
+        Ok(value) => {
+            let (topology, message) = from_json(&value)?;
+            (topology, message, value)
+        }
+        Err(_) => {
+            let topology = topology_in_text(body)?;
+            (topology, body.trim().to_string(), Value::String(body.to_string()))
+        }
+    };
+    Some(ClientError::TopologyFailure { topology, message, details })
+}
+
+fn from_json(value: &Value) -> Option<(String, String)> {
+    match value {
+        Value::Object(map) => from_object(map),
+        Value::String(text) => Some((topology_in_text(text)?, text.trim().to_string())),
+        _ => None,
+    }
+}
+
+fn from_object(map: &Map<String, Value>) -> Option<(String, String)> {
+    if let Some(topology) = TOPOLOGY_KEYS.iter().find_map(|key| map.get(*key)?.as_str()) {
+        let message = MESSAGE_KEYS
+            .iter()
+            .find_map(|key| map.get(*key))
+            .map_or_else(|| Value::Object(map.clone()).to_string(), message_of);
+        return Some((topology.to_string(), message));
+    }
+    let nested = WRAPPER_KEYS.iter().filter_map(|key| map.get(*key)).find_map(from_json);
+    nested.or_else(|| {
+        let failures = FAILURES_KEYS.iter().find_map(|key| map.get(*key)?.as_object())?;
+        let (topology, failure) = failures.iter().next()?;
+        Some((topology.clone(), message_of(failure)))
+    })
+}
+
+// The human-readable part of a failure: a string as is, or an object's first message
+// field (searched recursively), falling back to the JSON itself.
+fn message_of(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Object(map) => MESSAGE_KEYS
+            .iter()
+            .find_map(|key| map.get(*key))
+            .map_or_else(|| value.to_string(), message_of),
+        other => other.to_string(),
+    }
+}
+
+// The topology named in free text as `topology 'name'`, `topology "name"` or
+// `topology name` (case-insensitive).
+fn topology_in_text(text: &str) -> Option<String> {
+    let start = text.to_ascii_lowercase().find("topology ")? + "topology ".len();
+    let rest = text[start..].trim_start();
+    let name = match rest.chars().next()? {
+        quote @ ('\'' | '"') => rest[1..].split(quote).next()?,
+        _ => rest.split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))).next()?,
+    };
+    let name = name.trim_end_matches('.');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_ack, from_error_body};
+    use crate::testing::{FakeCluster, Reply, Scripted};
+    use crate::{AckReturn, ClientError};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+
+    // Error bodies of failed appends, in the shapes `from_error_body` recognizes
+    const FLAT: &str = r#"{"topology": "counter", "message": "java.lang.ArithmeticException: / by zero"}"#;
+    const TOPOLOGY_NAME: &str = r#"{"topologyName": "counter", "exception": {"class": "java.lang.IllegalStateException", "message": "bad state"}}"#;
+    const NESTED: &str = r#"{"error": {"topology": "counter", "cause": "java.lang.NullPointerException"}}"#;
+    const FAILURES: &str = r#"{"failures": {"counter": {"exception": "java.lang.RuntimeException: boom"}}}"#;
+    const NO_MESSAGE: &str = r#"{"topology": "counter", "stage": 3}"#;
+    const TEXT: &str = "Exception in topology 'counter': java.lang.RuntimeException: boom\n";
+    const JSON_TEXT: &str = r#""Topology counter failed: boom""#;
+
+    fn failure(err: Option<ClientError>) -> (String, String, Value) {
+        match err {
+            Some(ClientError::TopologyFailure { topology, message, details }) => (topology, message, details),
+            other => panic!("expected a topology failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_each_error_body_shape() {
+        let table = [
+            (FLAT, "java.lang.ArithmeticException: / by zero"),
+            (TOPOLOGY_NAME, "bad state"),
+            (NESTED, "java.lang.NullPointerException"),
+            (FAILURES, "java.lang.RuntimeException: boom"),
+            (NO_MESSAGE, r#"{"stage":3,"topology":"counter"}"#),
+            (TEXT, "Exception in topology 'counter': java.lang.RuntimeException: boom"),
+            (JSON_TEXT, "Topology counter failed: boom"),
+        ];
+        for (body, message) in table {
+            let (topology, parsed_message, _) = failure(from_error_body(body));
+            assert_eq!((topology.as_str(), parsed_message.as_str()), ("counter", message), "{}", body);
+        }
+        // The raw payload is kept for logging
+        assert_eq!(failure(from_error_body(FLAT)).2, serde_json::from_str::<Value>(FLAT).unwrap());
+        assert_eq!(failure(from_error_body(TEXT)).2, json!(TEXT));
+    }
+
+    #[test]
+    fn bodies_without_a_topology_are_not_failures() {
+        for body in ["", "Internal Server Error", r#"{"message": "depot not found"}"#, r#"{"error": {"code": 7}}"#, "[1, 2]", "topology  "] {
+            assert!(from_error_body(body).is_none(), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn only_exception_objects_in_an_ack_are_failures() {
+        let ack = json!({"counter": "#__L7", "audit": {"exception": {"message": "disk full"}}});
+        let (topology, message, details) = failure(from_ack(&ack));
+        assert_eq!((topology.as_str(), message.as_str()), ("audit", "disk full"));
+        assert_eq!(details, ack);
+        // A topology may return a map with an `error` field
+        assert!(from_ack(&json!({"counter": {"error": "none", "count": 1}})).is_none());
+        assert!(from_ack(&json!("not an object")).is_none());
+    }
+
+    #[tokio::test]
+    async fn an_error_status_naming_a_topology_fails_the_append() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::Status(StatusCode::INTERNAL_SERVER_ERROR, Vec::new(), NESTED.to_string())]);
+        let client = script.client_builder().build().unwrap();
+
+        let err = client.depot_append("profiles", "*edits", json!({"n": 1})).append::<AckReturn>().await.unwrap_err();
+        assert_eq!(err.code(), "RAMA-APPEND-TOPOLOGY");
+        assert!(matches!(err.kind(), ClientError::TopologyFailure { topology, .. } if topology == "counter"), "{:?}", err);
+        // Other requests get the same body as a plain status error
+        let err = client.pstate_query("profiles", "$$profiles").key("alice").select::<Value>().await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(StatusCode::INTERNAL_SERVER_ERROR, _)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn an_ack_reporting_an_exception_fails_the_append() {
+        let cluster = FakeCluster::new();
+        cluster
+            .depot("profiles", "*edits")
+            .ack_return("profiles", "*edits", "counter", "#__L7")
+            .ack_return("profiles", "*edits", "audit", json!({"exception": "java.lang.RuntimeException: boom"}));
+        let client = cluster.client();
+
+        let err = client.depot_append("profiles", "*edits", json!({"n": 1})).append::<AckReturn>().await.unwrap_err();
+        match err.kind() {
+            ClientError::TopologyFailure { topology, message, details } => {
+                assert_eq!((topology.as_str(), message.as_str()), ("audit", "java.lang.RuntimeException: boom"));
+                assert_eq!(details["counter"], json!("#__L7"));
+            }
+            other => panic!("expected a topology failure, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::ClientError;
+use bytes::Bytes;
+use futures::stream::{self, TryStreamExt};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+/// What `HttpTransport` methods return. `Send`, except on wasm32, where browser futures
+/// (`fetch`) aren't.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = futures::future::BoxFuture<'a, Result<TransportResponse, ClientError>>;
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = futures::future::LocalBoxFuture<'a, Result<TransportResponse, ClientError>>;
+
+/// A `TransportResponse` body. `Send`, except on wasm32.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BodyStream = futures::stream::BoxStream<'static, Result<Bytes, ClientError>>;
+#[cfg(target_arch = "wasm32")]
+pub type BodyStream = futures::stream::LocalBoxStream<'static, Result<Bytes, ClientError>>;
+
+/// Sends the HTTP requests a `Client` makes. The default is `ReqwestTransport`.
+///
+/// The client owns everything Rama-specific (redirects, supervisor caching, retries,
+/// auth, default headers); a transport only moves bytes. Implement this to run the client
+/// on another HTTP stack, or to answer requests in-process (e.g. in tests).
+/// Install one with `ClientBuilder::transport` or `Client::with_transport`.
+pub trait HttpTransport: Send + Sync {
+    /// Sends a POST with `body`. Redirects must be returned, not followed.
+    fn post(&self, url: Url, headers: HeaderMap, body: Bytes) -> TransportFuture<'_>;
+
+    /// Sends a GET, as used by `Client::preflight`. Redirects must be returned, not followed.
+    fn get(&self, url: Url, headers: HeaderMap) -> TransportFuture<'_>;
+}
+
+/// A response from an `HttpTransport`, with its body still unread.
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: BodyStream,
+}
+
+impl TransportResponse {
+    /// A response whose whole body is already in memory.
+    pub fn from_bytes(status: StatusCode, headers: HeaderMap, body: impl Into<Bytes>) -> Self {
+        let body = body.into();
+        Self { status, headers, body: Box::pin(stream::once(async move { Ok(body) })) }
+    }
+
+    /// Reads the whole body.
+    pub async fn bytes(self) -> Result<Bytes, ClientError> {
+        let mut chunks: Vec<Bytes> = self.body.try_collect().await?;
+        // A single chunk (the common case for small bodies) needs no copy.
+        if chunks.len() == 1 {
+            return Ok(chunks.remove(0));
+        }
+        Ok(Bytes::from(chunks.concat()))
+    }
+
+    /// Reads the whole body as text, replacing invalid UTF-8.
+    pub async fn text(self) -> Result<String, ClientError> {
+        Ok(String::from_utf8_lossy(&self.bytes().await?).into_owned())
+    }
+}
+
+impl fmt::Debug for TransportResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransportResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The default `HttpTransport`, backed by `reqwest`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Wraps a `reqwest::Client`. It must not follow redirects (see
+    /// `reqwest::redirect::Policy::none`): the Rama client handles 308s itself.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<TransportResponse, ClientError> {
+        let response = request.send().await.map_err(|mut e| {
+            // reqwest includes the URL in its message; keep credentials out of it.
+            if let Some(url) = e.url_mut() {
+                crate::redact::strip_userinfo(url);
+            }
+            e
+        })?;
+        Ok(TransportResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: Box::pin(response.bytes_stream().map_err(ClientError::Http)),
+        })
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn post(&self, url: Url, headers: HeaderMap, body: Bytes) -> TransportFuture<'_> {
+        Box::pin(self.send(self.client.post(url).headers(headers).body(body)))
+    }
+
+    fn get(&self, url: Url, headers: HeaderMap) -> TransportFuture<'_> {
+        Box::pin(self.send(self.client.get(url).headers(headers)))
+    }
+}
+
+// The installed transport, with a Debug impl for `Client`.
+#[derive(Clone)]
+pub(crate) struct Transport(pub(crate) Arc<dyn HttpTransport>);
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transport")
+    }
+}
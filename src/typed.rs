@@ -0,0 +1,172 @@
+use crate::builder::{decode_rama_keyword, decode_rama_long, pstate_name, Keyword, ToRamaValue};
+use crate::pager::decode_entries;
+use crate::{Client, ClientError};
+use futures::future::try_join_all;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::marker::PhantomData;
+
+/// A PState key type for `TypedPState`: encoded as a navigator per `ToRamaValue`, and
+/// parsed back from a response.
+pub trait RamaKey: ToRamaValue + Sized {
+    /// Parses a key from a response. Map keys arrive as JSON object keys (strings).
+    fn from_response(key: &Value) -> Option<Self>;
+}
+
+impl RamaKey for i64 {
+    fn from_response(key: &Value) -> Option<Self> {
+        decode_rama_long(key).or_else(|| key.as_str()?.parse().ok())
+    }
+}
+
+impl RamaKey for String {
+    fn from_response(key: &Value) -> Option<Self> {
+        key.as_str().map(str::to_string)
+    }
+}
+
+impl RamaKey for Keyword {
+    fn from_response(key: &Value) -> Option<Self> {
+        decode_rama_keyword(key).map(|k| Keyword(k.to_string()))
+    }
+}
+
+/// A PState whose top level is a map from `K` to `V`, for the common key lookups without
+/// building paths by hand. Created with `Client::typed_pstate`.
+///
+/// A thin layer over `PStateQueryBuilder`; use `Client::pstate_query` for anything else.
+#[derive(Debug)]
+pub struct TypedPState<'a, K, V> {
+    client: &'a Client,
+    module: String,
+    pstate: String,
+    _types: PhantomData<fn(K) -> V>,
+}
+
+impl<K, V> Clone for TypedPState<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self { client: self.client, module: self.module.clone(), pstate: self.pstate.clone(), _types: PhantomData }
+    }
+}
+
+impl<K: RamaKey, V: DeserializeOwned> TypedPState<'_, K, V> {
+    /// The value stored under `key`, or None if there is none.
+    pub async fn get(&self, key: &K) -> Result<Option<V>, ClientError> {
+        let query = self.client.pstate_query(&self.module, &self.pstate).key(key);
+        match query.select_one().await {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if matches!(e.kind(), ClientError::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The values stored under each of `keys`, in the same order, fetched concurrently.
+    /// Fails if any lookup fails.
+    pub async fn get_many(&self, keys: &[K]) -> Result<Vec<Option<V>>, ClientError> {
+        try_join_all(keys.iter().map(|key| self.get(key))).await
+    }
+
+    /// The entries with keys from `start` (inclusive) to `end` (exclusive), in key order.
+    /// The PState's top level must be a sorted map.
+    pub async fn select_range(&self, start: &K, end: &K) -> Result<Vec<(K, V)>, ClientError>
+    where
+        K: Ord,
+    {
+        let query = self
+            .client
+            .pstate_query(&self.module, &self.pstate)
+            .sorted_map_range(start, end);
+        let submap: Value = match query.select_one().await {
+            Err(e) if matches!(e.kind(), ClientError::NotFound { .. }) => Value::Null,
+            result => result?,
+        };
+        let mut entries = decode_entries(submap, K::from_response)?;
+        // Map key order in JSON isn't guaranteed to match the PState's, so restore it.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries)
+    }
+}
+
+impl Client {
+    /// A typed view of a key-value PState. See `TypedPState`.
+    pub fn typed_pstate<K: RamaKey, V: DeserializeOwned>(&self, module: &str, pstate: &str) -> TypedPState<'_, K, V> {
+        TypedPState { client: self, module: module.to_string(), pstate: pstate_name(pstate), _types: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RamaKey;
+    use crate::builder::Keyword;
+    use crate::testing::{FakeCluster, Reply, Scripted};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn keys_parse_back_from_responses() {
+        assert_eq!(i64::from_response(&json!("#__L42")), Some(42));
+        assert_eq!(i64::from_response(&json!(42)), Some(42));
+        assert_eq!(i64::from_response(&json!("42")), Some(42));
+        assert_eq!(i64::from_response(&json!("x")), None);
+        assert_eq!(String::from_response(&json!("alice")), Some("alice".into()));
+        assert_eq!(String::from_response(&json!(1)), None);
+        assert_eq!(Keyword::from_response(&json!("#__Kuser/admin")), Some(Keyword("user/admin".into())));
+        assert_eq!(Keyword::from_response(&json!("admin")), None);
+    }
+
+    fn cluster() -> FakeCluster {
+        let cluster = FakeCluster::new();
+        cluster
+            .pstate("m", "$$byId", json!({"#__L1": {"name": "Alice"}, "#__L2": {"name": "Bob"}}))
+            .pstate("m", "$$byName", json!({"alice": 30, "bob": 25}))
+            .pstate("m", "$$byRole", json!({"#__Kadmin": ["alice"]}));
+        cluster
+    }
+
+    #[tokio::test]
+    async fn long_keys_are_sent_long_encoded() {
+        let cluster = cluster();
+        let client = cluster.client();
+        let by_id = client.typed_pstate::<i64, Value>("m", "byId");
+
+        assert_eq!(by_id.get(&1).await.unwrap(), Some(json!({"name": "Alice"})));
+        assert_eq!(by_id.get(&3).await.unwrap(), None);
+        assert_eq!(cluster.requests().last().unwrap().body, json!(["#__L3"]));
+        assert_eq!(by_id.get_many(&[2, 3, 1]).await.unwrap(), [Some(json!({"name": "Bob"})), None, Some(json!({"name": "Alice"}))]);
+    }
+
+    #[tokio::test]
+    async fn string_keys_are_sent_as_is() {
+        let cluster = cluster();
+        let client = cluster.client();
+        let by_name = client.typed_pstate::<String, u32>("m", "$$byName");
+
+        assert_eq!(by_name.get(&"alice".to_string()).await.unwrap(), Some(30));
+        assert_eq!(cluster.requests().last().unwrap().body, json!(["alice"]));
+        assert_eq!(by_name.get_many(&["bob".into(), "carol".into()]).await.unwrap(), [Some(25), None]);
+    }
+
+    #[tokio::test]
+    async fn keyword_keys_are_sent_keyword_encoded() {
+        let cluster = cluster();
+        let client = cluster.client();
+        let by_role = client.typed_pstate::<Keyword, Vec<String>>("m", "$$byRole");
+
+        assert_eq!(by_role.get(&Keyword("admin".into())).await.unwrap(), Some(vec!["alice".to_string()]));
+        assert_eq!(cluster.requests().last().unwrap().body, json!(["#__Kadmin"]));
+        assert_eq!(by_role.get(&Keyword("guest".into())).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn select_range_returns_entries_in_key_order() {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect("supervisor-1:1984")]);
+        script.script("supervisor-1:1984", [Reply::ok(json!({"#__L3": "c", "#__L1": "a", "#__L2": "b"})), Reply::ok(Value::Null)]);
+        let client = script.client_builder().build().unwrap();
+        let by_id = client.typed_pstate::<i64, String>("m", "$$byId");
+
+        let entries = by_id.select_range(&1, &4).await.unwrap();
+        assert_eq!(entries, [(1, "a".to_string()), (2, "b".to_string()), (3, "c".to_string())]);
+        assert_eq!(script.bodies()[0], r##"[["sortedMapRange","#__L1","#__L4"]]"##.as_bytes());
+        assert_eq!(by_id.select_range(&10, &20).await.unwrap(), []);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::latency::host_key;
+use crate::transport::{HttpTransport, ReqwestTransport, TransportFuture};
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use url::Url;
+
+// Sends requests for the base URL's host through a Unix socket and everything else
+// (supervisors) over TCP.
+#[derive(Debug)]
+pub(crate) struct UnixSocketTransport {
+    socket: ReqwestTransport,
+    tcp: ReqwestTransport,
+    // `host:port` of the base URL
+    socket_authority: String,
+}
+
+impl UnixSocketTransport {
+    pub(crate) fn new(base_url: &Url, socket: ReqwestTransport, tcp: ReqwestTransport) -> Self {
+        Self { socket, tcp, socket_authority: host_key(base_url) }
+    }
+
+    fn route(&self, url: &Url) -> &ReqwestTransport {
+        if host_key(url) == self.socket_authority {
+            &self.socket
+        } else {
+            &self.tcp
+        }
+    }
+}
+
+impl HttpTransport for UnixSocketTransport {
+    fn post(&self, url: Url, headers: HeaderMap, body: Bytes) -> TransportFuture<'_> {
+        self.route(&url).post(url, headers, body)
+    }
+
+    fn get(&self, url: Url, headers: HeaderMap) -> TransportFuture<'_> {
+        self.route(&url).get(url, headers)
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use crate::testing::MockServer;
+    use crate::ClientBuilder;
+    use std::path::PathBuf;
+
+    const BASE_URL: &str = "http://rama-conductor.invalid:1984";
+
+    // A fresh socket path per test, so tests can run in parallel
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rama-client-{}-{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    // A conductor's 308 sending the select to the supervisor at `host_port`
+    fn redirect_to(host_port: &str) -> (u16, Vec<(&'static str, String)>, Vec<u8>) {
+        let location = format!("http://{}/rest/profiles/pstate/$$profiles/select", host_port);
+        (308, vec![("location", location), ("supervisor-locations", format!("[\"{}\"]", host_port))], Vec::new())
+    }
+
+    fn request_lines(server: &MockServer) -> Vec<String> {
+        server.requests().iter().map(|request| request.head.lines().next().unwrap().to_string()).collect()
+    }
+
+    fn hosts(server: &MockServer) -> Vec<String> {
+        server.requests().iter().map(|request| request.header("host").unwrap().to_string()).collect()
+    }
+
+    async fn select_alice(builder: ClientBuilder) -> Vec<u32> {
+        let client = builder.build().unwrap();
+        client.pstate_query("profiles", "$$profiles").key("alice").select().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_round_trip_through_the_socket() {
+        let path = socket_path("round-trip");
+        let proxy = MockServer::start_unix(&path, |_| (200, Vec::new(), b"[30]".to_vec())).await;
+
+        // The base URL's host doesn't resolve; only the socket is dialed
+        assert_eq!(select_alice(ClientBuilder::new(BASE_URL).unix_socket(&path)).await, [30]);
+        assert_eq!(request_lines(&proxy), ["POST /rest/profiles/pstate/$$profiles/select HTTP/1.1"]);
+        assert_eq!(hosts(&proxy), ["rama-conductor.invalid:1984"]);
+        assert_eq!(proxy.requests()[0].body, br#"["alice"]"#);
+    }
+
+    #[tokio::test]
+    async fn supervisor_redirects_are_followed_over_tcp_by_default() {
+        let supervisor = MockServer::start(|_| (200, Vec::new(), b"[30]".to_vec())).await;
+        let path = socket_path("redirect-tcp");
+        let supervisor_host = supervisor.url.trim_start_matches("http://").to_string();
+        let proxy = MockServer::start_unix(&path, move |_| redirect_to(&supervisor_host)).await;
+
+        assert_eq!(select_alice(ClientBuilder::new(BASE_URL).unix_socket(&path)).await, [30]);
+        assert_eq!(proxy.requests().len(), 1);
+        assert_eq!(hosts(&supervisor), [supervisor.url.trim_start_matches("http://")]);
+    }
+
+    #[tokio::test]
+    async fn supervisor_redirects_can_stay_on_the_socket() {
+        let path = socket_path("redirect-socket");
+        // The proxy routes on the Host header: the conductor redirects, the supervisor answers
+        let proxy = MockServer::start_unix(&path, |request| match request.header("host") {
+            Some("supervisor-1.invalid:1984") => (200, Vec::new(), b"[30]".to_vec()),
+            _ => redirect_to("supervisor-1.invalid:1984"),
+        })
+        .await;
+
+        let builder = ClientBuilder::new(BASE_URL).unix_socket(&path).redirects_over_socket(true);
+        assert_eq!(select_alice(builder).await, [30]);
+        assert_eq!(hosts(&proxy), ["rama-conductor.invalid:1984", "supervisor-1.invalid:1984"]);
+    }
+}
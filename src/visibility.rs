@@ -0,0 +1,113 @@
+use crate::builder::PreparedQuery;
+use crate::logging::debug;
+use crate::{Client, ClientError};
+use serde::de::DeserializeOwned;
+use crate::rt::Instant;
+use std::time::Duration;
+
+/// Client-side read-your-writes by polling: re-run a query until a predicate shows the
+/// write is visible.
+///
+/// Rama's REST append acks carry no offset or progress token to route or block a later
+/// read on, so this is the only read-your-writes mode available. Used by
+/// `Client::await_visible` and `DepotAppendBuilder::append_and_await`.
+#[derive(Debug, Clone, Copy)]
+pub struct VisibilityPolling {
+    /// Give up with `ClientError::Timeout` after this long.
+    pub timeout: Duration,
+    /// Delay between polls.
+    pub interval: Duration,
+}
+
+impl Default for VisibilityPolling {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(5), interval: Duration::from_millis(50) }
+    }
+}
+
+impl Client {
+    /// Runs `query` until `visible` accepts its result, and returns that result.
+    ///
+    /// Query errors are returned immediately; only "not visible yet" is retried.
+    pub async fn await_visible<R, F>(&self, query: &PreparedQuery, visible: F, polling: VisibilityPolling) -> Result<R, ClientError>
+    where
+        R: DeserializeOwned,
+        F: Fn(&R) -> bool,
+    {
+        let started = Instant::now();
+        let mut polls = 0;
+        loop {
+            let result: R = query.execute(self).await?;
+            polls += 1;
+            if visible(&result) {
+                debug!("Write visible in PState '{}' after {} poll(s) ({:?})", query.pstate(), polls, started.elapsed());
+                return Ok(result);
+            }
+
+            // Guard: Out of time
+            if started.elapsed() + polling.interval > polling.timeout {
+                return Err(ClientError::Timeout(polling.timeout));
+            }
+            crate::rt::sleep(polling.interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VisibilityPolling;
+    use crate::testing::{Reply, Scripted};
+    use crate::{Client, ClientError};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const SUPERVISOR: &str = "supervisor-1:1984";
+
+    fn client(replies: Vec<Reply>) -> (Arc<Scripted>, Client) {
+        let script = Scripted::new();
+        script.script(Scripted::CONDUCTOR, [Reply::redirect(SUPERVISOR)]);
+        script.script(SUPERVISOR, replies);
+        let client = script.client_builder().build().unwrap();
+        (script, client)
+    }
+
+    fn polling(timeout_ms: u64) -> VisibilityPolling {
+        VisibilityPolling { timeout: Duration::from_millis(timeout_ms), interval: Duration::from_millis(5) }
+    }
+
+    #[tokio::test]
+    async fn polls_until_the_write_is_visible() {
+        let (script, client) = client(vec![Reply::ok(json!({})), Reply::ok(json!([[]])), Reply::ok(json!([[]])), Reply::ok(json!([["alice"]]))]);
+        let query = client.pstate_query("profiles", "$$followers").key("bob").prepare();
+
+        let (ack, followers): (Value, Vec<Vec<String>>) = client
+            .depot_append("profiles", "*follows", json!({"from": "alice", "to": "bob"}))
+            .append_and_await(&query, |followers: &Vec<Vec<String>>| followers[0].contains(&"alice".to_string()), polling(1_000))
+            .await
+            .unwrap();
+        assert_eq!(ack, json!({}));
+        assert_eq!(followers, [["alice"]]);
+        // The append (through the conductor), then three polls
+        assert_eq!(script.hosts(), [Scripted::CONDUCTOR, SUPERVISOR, SUPERVISOR, SUPERVISOR, SUPERVISOR]);
+    }
+
+    #[tokio::test]
+    async fn times_out_if_the_write_never_shows() {
+        let (_, client) = client(vec![Reply::ok(json!([[]]))]);
+        let query = client.pstate_query("profiles", "$$followers").key("bob").prepare();
+
+        let err = client.await_visible(&query, |followers: &Vec<Vec<String>>| !followers[0].is_empty(), polling(50)).await.unwrap_err();
+        assert!(matches!(err, ClientError::Timeout(timeout) if timeout == Duration::from_millis(50)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn query_errors_are_not_retried() {
+        let (script, client) = client(vec![Reply::Status(reqwest::StatusCode::BAD_REQUEST, Vec::new(), String::new())]);
+        let query = client.pstate_query("profiles", "$$followers").key("bob").prepare();
+
+        let err = client.await_visible(&query, |_: &Value| true, polling(1_000)).await.unwrap_err();
+        assert!(matches!(err.kind(), ClientError::UnexpectedStatus(..)), "{:?}", err);
+        assert_eq!(script.hosts().len(), 2);
+    }
+}
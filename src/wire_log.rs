@@ -0,0 +1,210 @@
+use crate::interceptor::{RequestContext, RequestInterceptor};
+use crate::logging::info;
+use crate::redact::{truncated, Redacted};
+use crate::ClientError;
+use reqwest::header::{HeaderMap, HeaderName};
+use reqwest::StatusCode;
+use serde_json::Value;
+
+// Replaces the value of every redacted field.
+const REDACTED: &str = "***";
+
+/// A built-in interceptor that logs each request as sent: method, URL, selected headers
+/// and body, with sensitive JSON fields replaced by `"***"`.
+///
+/// Register it with `ClientBuilder::interceptor`. It logs at `info` under the
+/// `rama_client` target, once per attempt (redirects and retries included), and logs the
+/// status of each response. It only reads the request, never changes it.
+///
+/// The body logged is the serialized bytes about to go on the wire. Fields named in the
+/// redaction list are redacted at any depth of nested objects and arrays; names match
+/// case-insensitively. Bodies that aren't JSON (e.g. gzip-compressed ones) are logged as
+/// their size only, since they can't be redacted.
+#[derive(Debug, Clone)]
+pub struct WireLogger {
+    redact_fields: Vec<String>,
+    headers: Vec<HeaderName>,
+    max_body_bytes: usize,
+}
+
+impl Default for WireLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WireLogger {
+    /// Redacts `password`, `secret` and `token` fields, logs the `content-type` and
+    /// `content-encoding` headers, and logs up to 4 KiB of each body.
+    pub fn new() -> Self {
+        Self {
+            redact_fields: ["password", "secret", "token"].map(String::from).to_vec(),
+            headers: vec![reqwest::header::CONTENT_TYPE, reqwest::header::CONTENT_ENCODING],
+            max_body_bytes: 4096,
+        }
+    }
+
+    /// Replaces the list of JSON field names whose values are logged as `"***"`.
+    pub fn redact_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact_fields = fields.into_iter().map(|field| field.into().to_ascii_lowercase()).collect();
+        self
+    }
+
+    /// Replaces the list of headers logged. Headers marked sensitive (e.g. `Authorization`)
+    /// are logged as `***` even if listed.
+    pub fn log_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.headers = headers.into_iter().collect();
+        self
+    }
+
+    /// Bodies longer than this (after redaction) are cut, noting their full length.
+    pub fn max_body_bytes(mut self, max: usize) -> Self {
+        self.max_body_bytes = max;
+        self
+    }
+
+    // The selected headers as `name: value` pairs.
+    fn logged_headers(&self, headers: &HeaderMap) -> String {
+        let mut logged = Vec::new();
+        for name in &self.headers {
+            for value in headers.get_all(name) {
+                let value = match value.to_str() {
+                    _ if value.is_sensitive() => REDACTED,
+                    Ok(value) => value,
+                    Err(_) => "<non-ASCII>",
+                };
+                logged.push(format!("{}: {}", name, value));
+            }
+        }
+        logged.join(", ")
+    }
+
+    // The body as it should appear in the log: redacted JSON, cut to the size cap.
+    fn logged_body(&self, body: &[u8]) -> String {
+        // Guard: Not JSON, so it can't be redacted
+        let Ok(mut value) = serde_json::from_slice::<Value>(body) else {
+            return format!("<{} bytes, not JSON>", body.len());
+        };
+        self.redact(&mut value);
+        truncated(&value.to_string(), self.max_body_bytes).into_owned()
+    }
+
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.redact_fields.iter().any(|redacted| key.eq_ignore_ascii_case(redacted)) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+}
+
+impl RequestInterceptor for WireLogger {
+    fn intercept(&self, request: &mut reqwest::Request, ctx: &RequestContext) -> Result<(), ClientError> {
+        let body = match request.body().map(reqwest::Body::as_bytes) {
+            None => "<empty>".to_string(),
+            Some(Some(bytes)) => self.logged_body(bytes),
+            Some(None) => "<streamed>".to_string(),
+        };
+        info!(
+            "--> {} {} [{}] attempt {} [request_id={}] {}",
+            request.method(),
+            Redacted(request.url()),
+            self.logged_headers(request.headers()),
+            ctx.attempt,
+            ctx.request_id,
+            body
+        );
+        Ok(())
+    }
+
+    fn on_response(&self, status: StatusCode, headers: &HeaderMap, ctx: &RequestContext) {
+        info!(
+            "<-- {} for {}/{} [{}] attempt {} [request_id={}]",
+            status,
+            ctx.module,
+            ctx.path_suffix,
+            self.logged_headers(headers),
+            ctx.attempt,
+            ctx.request_id
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WireLogger;
+    use crate::testing::FakeCluster;
+    use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn logged(logger: &WireLogger, body: &Value) -> String {
+        logger.logged_body(&serde_json::to_vec(body).unwrap())
+    }
+
+    #[test]
+    fn redacts_fields_at_any_depth() {
+        let logger = WireLogger::new().redact_fields(["password", "SSN"]);
+        let body = json!({
+            "data": {"user": "alice", "Password": "hunter2", "profile": {"ssn": "123-45-6789", "tags": [{"ssn": 1}]}},
+            "ackLevel": "ack",
+        });
+        let redacted: Value = serde_json::from_str(&logged(&logger, &body)).unwrap();
+        assert_eq!(redacted, json!({
+            "data": {"user": "alice", "Password": "***", "profile": {"ssn": "***", "tags": [{"ssn": "***"}]}},
+            "ackLevel": "ack",
+        }));
+        // The default list
+        let defaults = logged(&WireLogger::new(), &json!([{"token": "t", "secret": {"nested": 1}, "other": 2}]));
+        assert_eq!(defaults, r#"[{"other":2,"secret":"***","token":"***"}]"#);
+    }
+
+    #[test]
+    fn cuts_large_bodies_with_a_marker() {
+        let logger = WireLogger::new().max_body_bytes(32);
+        let body = json!({"bio": "x".repeat(100)});
+        let cut = logged(&logger, &body);
+        let full = body.to_string().len();
+        assert_eq!(cut, format!("{}... ({} bytes total)", &body.to_string()[..32], full));
+        assert_eq!(logger.logged_body(b"\x1f\x8b\x08 gzip"), "<8 bytes, not JSON>");
+    }
+
+    #[test]
+    fn logs_selected_headers_and_hides_sensitive_ones() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let mut auth = HeaderValue::from_static("Bearer t0ken");
+        auth.set_sensitive(true);
+        headers.insert(AUTHORIZATION, auth);
+        headers.insert("x-other", HeaderValue::from_static("unlisted"));
+
+        assert_eq!(WireLogger::new().logged_headers(&headers), "content-type: application/json");
+        let logger = WireLogger::new().log_headers([CONTENT_TYPE, AUTHORIZATION]);
+        assert_eq!(logger.logged_headers(&headers), "content-type: application/json, authorization: ***");
+    }
+
+    #[tokio::test]
+    async fn the_request_is_sent_unchanged() {
+        let cluster = FakeCluster::new();
+        cluster.depot("profiles", "*edits");
+        let client = cluster.client_builder().interceptor(Arc::new(WireLogger::new().max_body_bytes(8))).build().unwrap();
+        let record = json!({"user": "alice", "password": "hunter2", "bio": "x".repeat(100)});
+        client.depot_append("profiles", "*edits", &record).append::<Value>().await.unwrap();
+        assert_eq!(cluster.appended("profiles", "*edits"), [record]);
+    }
+}
@@ -0,0 +1,38 @@
+//! The client on async-std, without the `tokio` feature: timers fall back to
+//! `futures-timer` and the appender worker gets its own thread. Run with
+//! `cargo test --no-default-features --features test-util --test async_std`.
+#![cfg(all(not(feature = "tokio"), not(target_arch = "wasm32")))]
+
+use rama_client::testing::FakeCluster;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[async_std::test]
+async fn selects_on_async_std() {
+    let cluster = FakeCluster::new();
+    cluster.pstate("profiles", "$$profiles", json!({"alice": {"age": 30}}));
+    // Delays go through the runtime-agnostic timers
+    cluster.latency("*", Duration::from_millis(5));
+    let client = cluster.client();
+
+    let age: u32 = client.pstate_query("profiles", "$$profiles").key("alice").key("age").select_one().await.unwrap();
+    assert_eq!(age, 30);
+}
+
+#[async_std::test]
+async fn appends_on_async_std() {
+    let cluster = FakeCluster::new();
+    cluster.depot("telemetry", "*events");
+    let client = cluster.client();
+
+    client.depot_append("telemetry", "*events", json!({"n": 0})).append::<Value>().await.unwrap();
+    let appender = client.depot_appender::<Value>("telemetry", "*events");
+    for n in 1..4 {
+        appender.send(json!({"n": n})).await.unwrap();
+    }
+    appender.close().await.unwrap();
+
+    let mut appended = cluster.appended("telemetry", "*events");
+    appended.sort_by_key(|record| record["n"].as_u64());
+    assert_eq!(appended, (0..4).map(|n| json!({"n": n})).collect::<Vec<_>>());
+}
@@ -0,0 +1,39 @@
+//! TLS with whichever backend is enabled. The compile checks run with each backend on its
+//! own (`--no-default-features --features tokio,native-tls`, then `tokio,rustls`); the
+//! round trip needs a conductor behind TLS, so it only runs with `RAMA_HTTPS_URL` set:
+//!
+//! ```text
+//! RAMA_HTTPS_URL=https://conductor:8888 RAMA_HTTPS_MODULE=com.example.Module \
+//!     RAMA_HTTPS_ACCEPT_INVALID_CERTS=1 cargo test --test https
+//! ```
+#![cfg(all(any(feature = "native-tls", feature = "rustls"), feature = "tokio", not(target_arch = "wasm32")))]
+
+use rama_client::Client;
+use std::env;
+
+#[test]
+fn builds_https_clients_with_the_enabled_backend() {
+    let client = Client::builder("https://conductor.example:8888").build().unwrap();
+    assert_eq!(client.base_url().as_str(), "https://conductor.example:8888/");
+
+    // Self-signed dev clusters
+    let client = Client::builder("https://conductor.example:8888").danger_accept_invalid_certs(true).build().unwrap();
+    assert_eq!(client.base_url().scheme(), "https");
+}
+
+#[tokio::test]
+async fn pings_a_conductor_over_https() {
+    let Ok(url) = env::var("RAMA_HTTPS_URL") else {
+        eprintln!("RAMA_HTTPS_URL not set; skipping the https round trip");
+        return;
+    };
+    let client = Client::builder(url)
+        .danger_accept_invalid_certs(env::var_os("RAMA_HTTPS_ACCEPT_INVALID_CERTS").is_some())
+        .build()
+        .unwrap();
+
+    client.ping().await.unwrap();
+    if let Ok(module) = env::var("RAMA_HTTPS_MODULE") {
+        client.ping_module(&module).await.unwrap();
+    }
+}